@@ -19,7 +19,7 @@ use aptos_block_executor::{
     executor::BlockExecutor,
     task::{
         Transaction as BlockExecutorTransaction,
-        TransactionOutput as BlockExecutorTransactionOutput,
+        TransactionOutput as BlockExecutorTransactionOutput, DEFAULT_ESTIMATED_TRANSACTION_COST,
     },
     txn_commit_hook::TransactionCommitHook,
 };
@@ -29,15 +29,16 @@ use aptos_types::{
     block_executor::partitioner::{
         BlockExecutorTransactions, SubBlock, SubBlocksForShard, TransactionWithDependencies,
     },
+    contract_event::ContractEvent,
     executable::ExecutableTestType,
     fee_statement::FeeStatement,
     state_store::state_key::StateKey,
     transaction::{Transaction, TransactionOutput, TransactionStatus},
-    write_set::WriteOp,
+    write_set::{WriteOp, WriteSet, WriteSetMut},
 };
 use aptos_vm_logging::{flush_speculative_logs, init_speculative_logs};
 use aptos_vm_types::output::VMOutput;
-use move_core_types::vm_status::VMStatus;
+use move_core_types::vm_status::{StatusCode, VMStatus};
 use once_cell::sync::OnceCell;
 use rayon::{prelude::*, ThreadPool};
 use std::sync::Arc;
@@ -45,6 +46,18 @@ use std::sync::Arc;
 impl BlockExecutorTransaction for PreprocessedTransaction {
     type Key = StateKey;
     type Value = WriteOp;
+
+    fn estimated_cost(&self) -> u64 {
+        match self {
+            // User transactions can consume up to their max gas amount
+            PreprocessedTransaction::UserTransaction(txn) => txn.max_gas_amount(),
+            // System transactions (and invalid ones) are cheap to execute
+            PreprocessedTransaction::WaypointWriteSet(_)
+            | PreprocessedTransaction::BlockMetadata(_)
+            | PreprocessedTransaction::InvalidSignature
+            | PreprocessedTransaction::StateCheckpoint => DEFAULT_ESTIMATED_TRANSACTION_COST,
+        }
+    }
 }
 
 // Wrapper to avoid orphan rule
@@ -52,6 +65,7 @@ impl BlockExecutorTransaction for PreprocessedTransaction {
 pub struct AptosTransactionOutput {
     vm_output: Mutex<Option<VMOutput>>,
     committed_output: OnceCell<TransactionOutput>,
+    committed_fee_statement: OnceCell<FeeStatement>,
 }
 
 impl AptosTransactionOutput {
@@ -59,6 +73,7 @@ impl AptosTransactionOutput {
         Self {
             vm_output: Mutex::new(Some(output)),
             committed_output: OnceCell::new(),
+            committed_fee_statement: OnceCell::new(),
         }
     }
 
@@ -87,44 +102,61 @@ impl BlockExecutorTransactionOutput for AptosTransactionOutput {
         Self::new(VMOutput::empty_with_status(TransactionStatus::Retry))
     }
 
-    /// Should never be called after incorporate_delta_writes, as it
-    /// will consume vm_output to prepare an output with deltas.
+    /// Once incorporate_delta_writes is called, the writes are read from
+    /// the committed output (i.e., they include the materialized deltas).
     fn get_writes(&self) -> Vec<(StateKey, WriteOp)> {
-        self.vm_output
-            .lock()
-            .as_ref()
-            .expect("Output to be set to get writes")
-            .write_set()
-            .iter()
-            .map(|(key, op)| (key.clone(), op.clone()))
-            .collect()
+        match self.committed_output.get() {
+            Some(output) => output
+                .write_set()
+                .iter()
+                .map(|(key, op)| (key.clone(), op.clone()))
+                .collect(),
+            None => self
+                .vm_output
+                .lock()
+                .as_ref()
+                .expect("Output to be set to get writes")
+                .write_set()
+                .iter()
+                .map(|(key, op)| (key.clone(), op.clone()))
+                .collect(),
+        }
     }
 
-    /// Should never be called after incorporate_delta_writes, as it
-    /// will consume vm_output to prepare an output with deltas.
+    /// Once incorporate_delta_writes is called, there are no deltas
+    /// (as they are materialized in the writes of the committed output).
     fn get_deltas(&self) -> Vec<(StateKey, DeltaOp)> {
-        self.vm_output
-            .lock()
-            .as_ref()
-            .expect("Output to be set to get deltas")
-            .delta_change_set()
-            .iter()
-            .map(|(key, op)| (key.clone(), *op))
-            .collect()
+        match self.committed_output.get() {
+            Some(_) => vec![],
+            None => self
+                .vm_output
+                .lock()
+                .as_ref()
+                .expect("Output to be set to get deltas")
+                .delta_change_set()
+                .iter()
+                .map(|(key, op)| (key.clone(), *op))
+                .collect(),
+        }
     }
 
     /// Can be called (at most) once after transaction is committed to internally
     /// include the delta outputs with the transaction outputs.
     fn incorporate_delta_writes(&self, delta_writes: Vec<(StateKey, WriteOp)>) {
+        let vm_output = self
+            .vm_output
+            .lock()
+            .take()
+            .expect("Output must be set to combine with deltas");
+        assert!(
+            self.committed_fee_statement
+                .set(vm_output.fee_statement().clone())
+                .is_ok(),
+            "Could not store the committed fee statement"
+        );
         assert!(
             self.committed_output
-                .set(
-                    self.vm_output
-                        .lock()
-                        .take()
-                        .expect("Output must be set to combine with deltas")
-                        .output_with_delta_writes(delta_writes),
-                )
+                .set(vm_output.output_with_delta_writes(delta_writes))
                 .is_ok(),
             "Could not combine VMOutput with deltas"
         );
@@ -138,14 +170,45 @@ impl BlockExecutorTransactionOutput for AptosTransactionOutput {
     }
 
     // Return the fee statement of the transaction.
-    // Should never be called after vm_output is consumed.
     fn fee_statement(&self) -> FeeStatement {
-        self.vm_output
-            .lock()
-            .as_ref()
-            .expect("Output to be set to get fee statement")
-            .fee_statement()
-            .clone()
+        match self.committed_fee_statement.get() {
+            Some(fee_statement) => fee_statement.clone(),
+            None => self
+                .vm_output
+                .lock()
+                .as_ref()
+                .expect("Output to be set to get fee statement")
+                .fee_statement()
+                .clone(),
+        }
+    }
+
+    /// Return the events emitted by the transaction (in emission order).
+    fn get_events(&self) -> Vec<ContractEvent> {
+        match self.committed_output.get() {
+            Some(output) => output.events().to_vec(),
+            None => self
+                .vm_output
+                .lock()
+                .as_ref()
+                .expect("Output to be set to get events")
+                .events()
+                .to_vec(),
+        }
+    }
+
+    /// Return the status of the transaction.
+    fn status(&self) -> TransactionStatus {
+        match self.committed_output.get() {
+            Some(output) => output.status().clone(),
+            None => self
+                .vm_output
+                .lock()
+                .as_ref()
+                .expect("Output to be set to get status")
+                .status()
+                .clone(),
+        }
     }
 }
 
@@ -273,6 +336,75 @@ impl BlockAptosVM {
                 unreachable!("[Execution]: Must be handled by sequential fallback")
             },
             Err(Error::UserError(err)) => Err(err),
+            Err(Error::InvalidCommittedPrefix(_)) => {
+                unreachable!("[Execution]: Only returned when resuming a block")
+            },
+        }
+    }
+
+    /// Executes the block (sequentially) and returns its net write set, i.e.,
+    /// the last write op to each state key by the committed transactions. This
+    /// is useful for state-diff tooling, as applying the net write set to the
+    /// state view is equivalent to applying each transaction output in order.
+    pub fn execute_block_write_set<
+        S: StateView + Sync,
+        L: TransactionCommitHook<Output = AptosTransactionOutput>,
+    >(
+        executor_thread_pool: Arc<ThreadPool>,
+        transactions: BlockExecutorTransactions<Transaction>,
+        state_view: &S,
+        maybe_block_gas_limit: Option<u64>,
+        transaction_commit_listener: Option<L>,
+    ) -> Result<WriteSet, VMStatus> {
+        let signature_verified_block =
+            executor_thread_pool.install(|| Self::verify_transactions(transactions));
+
+        let num_txns = signature_verified_block.num_txns();
+        let is_speculation_enabled = state_view.id() != StateViewId::Miscellaneous;
+        if is_speculation_enabled {
+            init_speculative_logs(num_txns);
+        }
+
+        // The net write set is computed during sequential execution,
+        // so the concurrency level is irrelevant.
+        let executor = BlockExecutor::<
+            PreprocessedTransaction,
+            AptosExecutorTask<S>,
+            S,
+            L,
+            ExecutableTestType,
+        >::new(
+            1,
+            executor_thread_pool,
+            maybe_block_gas_limit,
+            transaction_commit_listener,
+        );
+
+        let ret =
+            executor.execute_block_write_set(state_view, signature_verified_block, state_view);
+        match ret {
+            Ok(net_write_set) => {
+                // Flush the speculative logs (transactions that were
+                // never executed don't have any logs to flush).
+                if is_speculation_enabled {
+                    flush_speculative_logs(num_txns);
+                }
+
+                WriteSetMut::new(net_write_set)
+                    .freeze()
+                    .map_err(|error| VMStatus::Error {
+                        status_code: StatusCode::UNKNOWN_INVARIANT_VIOLATION_ERROR,
+                        sub_status: None,
+                        message: Some(error.to_string()),
+                    })
+            },
+            Err(Error::ModulePathReadWrite) => {
+                unreachable!("[Execution]: Sequential execution never reports a module conflict")
+            },
+            Err(Error::UserError(err)) => Err(err),
+            Err(Error::InvalidCommittedPrefix(_)) => {
+                unreachable!("[Execution]: Only returned when resuming a block")
+            },
         }
     }
 }
@@ -0,0 +1,58 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::Result;
+use aptos_state_view::{StateView, StateViewId, TStateView};
+use aptos_types::{
+    on_chain_config::{GasScheduleV2, OnChainConfig},
+    state_store::{
+        state_key::StateKey, state_storage_usage::StateStorageUsage, state_value::StateValue,
+    },
+};
+
+/// A state view that serves the given gas schedule in place of the on-chain
+/// gas schedule of the base view. All other reads go to the base view.
+pub struct GasScheduleOverrideView<'a, S> {
+    base_view: &'a S,
+    gas_schedule_key: StateKey,
+    gas_schedule_value: StateValue,
+}
+
+impl<'a, S: StateView> GasScheduleOverrideView<'a, S> {
+    pub fn new(base_view: &'a S, gas_schedule: &GasScheduleV2) -> Self {
+        let gas_schedule_key = StateKey::access_path(
+            GasScheduleV2::access_path().expect("Gas schedule access path should be valid!"),
+        );
+        let gas_schedule_value = StateValue::new_legacy(
+            bcs::to_bytes(gas_schedule).expect("Gas schedule should be serializable!"),
+        );
+        Self {
+            base_view,
+            gas_schedule_key,
+            gas_schedule_value,
+        }
+    }
+}
+
+impl<'a, S: StateView> TStateView for GasScheduleOverrideView<'a, S> {
+    type Key = StateKey;
+
+    fn id(&self) -> StateViewId {
+        self.base_view.id()
+    }
+
+    fn get_state_value(&self, state_key: &StateKey) -> Result<Option<StateValue>> {
+        if state_key == &self.gas_schedule_key {
+            return Ok(Some(self.gas_schedule_value.clone()));
+        }
+        self.base_view.get_state_value(state_key)
+    }
+
+    fn is_genesis(&self) -> bool {
+        self.base_view.is_genesis()
+    }
+
+    fn get_usage(&self) -> Result<StateStorageUsage> {
+        self.base_view.get_usage()
+    }
+}
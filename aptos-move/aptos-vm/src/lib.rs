@@ -114,6 +114,7 @@ pub mod aptos_vm;
 mod aptos_vm_impl;
 pub mod block_executor;
 mod errors;
+mod gas_schedule_override_view;
 pub mod move_vm_ext;
 pub mod natives;
 pub mod sharded_block_executor;
@@ -123,10 +124,14 @@ mod transaction_validation;
 mod verifier;
 
 pub use crate::aptos_vm::AptosVM;
-use crate::sharded_block_executor::ShardedBlockExecutor;
+use crate::{
+    gas_schedule_override_view::GasScheduleOverrideView,
+    sharded_block_executor::ShardedBlockExecutor,
+};
 use aptos_state_view::StateView;
 use aptos_types::{
     block_executor::partitioner::SubBlocksForShard,
+    on_chain_config::GasScheduleV2,
     transaction::{SignedTransaction, Transaction, TransactionOutput, VMValidatorResult},
     vm_status::VMStatus,
 };
@@ -157,6 +162,18 @@ pub trait VMExecutor: Send + Sync {
         maybe_block_gas_limit: Option<u64>,
     ) -> Result<Vec<TransactionOutput>, VMStatus>;
 
+    /// Executes a block of transactions using the given gas schedule (instead of the
+    /// gas schedule found in `state_view`) and returns output for each one of them.
+    fn execute_block_with_gas_schedule(
+        transactions: Vec<Transaction>,
+        state_view: &(impl StateView + Sync),
+        gas_schedule: &GasScheduleV2,
+        maybe_block_gas_limit: Option<u64>,
+    ) -> Result<Vec<TransactionOutput>, VMStatus> {
+        let state_view = GasScheduleOverrideView::new(state_view, gas_schedule);
+        Self::execute_block(transactions, &state_view, maybe_block_gas_limit)
+    }
+
     /// Executes a block of transactions using a sharded block executor and returns the results.
     fn execute_block_sharded<S: StateView + Sync + Send + 'static>(
         sharded_block_executor: &ShardedBlockExecutor<S>,
@@ -2,6 +2,8 @@
 // Parts of the project are originally copyright © Meta Platforms, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
+use aptos_mvhashmap::types::TxnIndex;
+
 #[derive(Debug, PartialEq, Eq)]
 pub enum Error<E> {
     /// The same module access path for module was both read & written during speculative executions.
@@ -13,6 +15,21 @@ pub enum Error<E> {
     /// Execution of a thread yields a non-recoverable error, such error will be propagated back to
     /// the caller.
     UserError(E),
+    /// The committed prefix used to resume the execution of a block is inconsistent with the block
+    /// (e.g., it does not end at the resume index, or it has unmaterialized deltas).
+    InvalidCommittedPrefix(String),
 }
 
 pub type Result<T, E> = ::std::result::Result<T, Error<E>>;
+
+/// The error returned when the determinism of block execution is verified,
+/// i.e., when the same block is executed twice and the results are compared.
+#[derive(Debug, PartialEq, Eq)]
+pub enum DeterminismError<E> {
+    /// Both executions failed with the same error.
+    ExecutionError(Error<E>),
+    /// The outputs of both executions differ, starting at the given transaction index.
+    DivergentOutputs(TxnIndex),
+    /// Only one of the executions failed, or the executions failed with different errors.
+    DivergentResults,
+}
@@ -24,13 +24,16 @@ use aptos_mvhashmap::{
 };
 use aptos_state_view::TStateView;
 use aptos_types::{
-    block_executor::partitioner::BlockExecutorTransactions, executable::Executable,
-    fee_statement::FeeStatement, write_set::WriteOp,
+    block_executor::partitioner::BlockExecutorTransactions, contract_event::ContractEvent,
+    executable::Executable, fee_statement::FeeStatement, transaction::TransactionStatus,
+    write_set::WriteOp,
 };
 use aptos_vm_logging::{clear_speculative_txn_logs, init_speculative_logs};
+use async_trait::async_trait;
 use num_cpus;
 use rayon::ThreadPool;
 use std::{
+    collections::BTreeMap,
     marker::PhantomData,
     sync::{
         mpsc,
@@ -720,7 +723,37 @@ where
         signature_verified_block: &Vec<T>,
         base_view: &S,
     ) -> Result<Vec<E::Output>, E::Error> {
-        let num_txns = signature_verified_block.len();
+        self.execute_transactions_sequential_from(
+            executor_arguments,
+            signature_verified_block,
+            base_view,
+            &[],
+            &|_| false,
+            None,
+        )
+    }
+
+    /// Sequentially executes the transactions that follow the committed prefix
+    /// (i.e., starting at index `committed_prefix.len()`), using the writes of
+    /// the committed prefix on top of the base view. The returned outputs only
+    /// include the transactions that follow the prefix. Transactions for which
+    /// `skip` returns true are not executed, and a skip output is returned in
+    /// their place (i.e., they have no effect on subsequent transactions). If
+    /// the prefix already halted the block (i.e., due to a SkipRest transaction
+    /// or the block gas limit), all remaining transactions are skipped. If a net
+    /// write set is given, the writes of the executed transactions are folded
+    /// into it (before the outputs are committed).
+    fn execute_transactions_sequential_from(
+        &self,
+        executor_arguments: E::Argument,
+        signature_verified_block: &[T],
+        base_view: &S,
+        committed_prefix: &[E::Output],
+        skip: &dyn Fn(TxnIndex) -> bool,
+        mut net_write_set: Option<&mut BTreeMap<T::Key, T::Value>>,
+    ) -> Result<Vec<E::Output>, E::Error> {
+        let from_index = committed_prefix.len();
+        let num_txns = signature_verified_block.len() - from_index;
         let executor = E::init(executor_arguments);
         let data_map = UnsyncMap::new();
 
@@ -728,7 +761,70 @@ where
 
         let mut accumulated_fee_statement = FeeStatement::zero();
 
-        for (idx, txn) in signature_verified_block.iter().enumerate() {
+        // Apply the writes (and gas) of the committed prefix. Skip outputs (i.e.,
+        // outputs with a retry status) mean the prefix already halted the block.
+        let mut prefix_halted_block = false;
+        for (idx, output) in committed_prefix.iter().enumerate() {
+            if output.status().is_retry() {
+                prefix_halted_block = true;
+                continue;
+            }
+            if prefix_halted_block {
+                return Err(Error::InvalidCommittedPrefix(format!(
+                    "The output at index {} follows a skipped transaction",
+                    idx
+                )));
+            }
+            if !output.get_deltas().is_empty() {
+                return Err(Error::InvalidCommittedPrefix(format!(
+                    "The output at index {} has unmaterialized deltas",
+                    idx
+                )));
+            }
+
+            // If the last transaction of the prefix is a SkipRest transaction, the
+            // remaining transactions must be skipped. This can't be told from the
+            // output, so the transaction is re-executed (on the state before it).
+            if idx + 1 == from_index && from_index < signature_verified_block.len() {
+                let res = executor.execute_transaction(
+                    &LatestView::<T, S, X>::new_btree_view(base_view, &data_map, idx as TxnIndex),
+                    &signature_verified_block[idx],
+                    idx as TxnIndex,
+                    true,
+                );
+                prefix_halted_block = matches!(res, ExecutionStatus::SkipRest(_));
+            }
+
+            for (ap, write_op) in output.get_writes().into_iter() {
+                data_map.write(ap, write_op);
+            }
+            accumulated_fee_statement.add_fee_statement(&output.fee_statement());
+        }
+
+        // If the committed prefix already reached the block gas limit, the
+        // remaining transactions must be skipped (as in a full execution).
+        let prefix_reached_gas_limit = from_index > 0
+            && self
+                .maybe_block_gas_limit
+                .map(|per_block_gas_limit| {
+                    accumulated_fee_statement.execution_gas_used()
+                        + accumulated_fee_statement.io_gas_used()
+                        >= per_block_gas_limit
+                })
+                .unwrap_or(false);
+
+        for (idx, txn) in signature_verified_block
+            .iter()
+            .enumerate()
+            .skip(from_index)
+            .take_while(|_| !prefix_halted_block && !prefix_reached_gas_limit)
+        {
+            // Filtered transactions are not executed (and don't affect the state)
+            if skip(idx as TxnIndex) {
+                ret.push(E::Output::skip_output());
+                continue;
+            }
+
             let res = executor.execute_transaction(
                 &LatestView::<T, S, X>::new_btree_view(base_view, &data_map, idx as TxnIndex),
                 txn,
@@ -746,6 +842,9 @@ where
                     );
                     // Apply the writes.
                     for (ap, write_op) in output.get_writes().into_iter() {
+                        if let Some(net_write_set) = net_write_set.as_mut() {
+                            net_write_set.insert(ap.clone(), write_op.clone());
+                        }
                         data_map.write(ap, write_op);
                     }
                     // Calculating the accumulated gas costs of the committed txns.
@@ -797,7 +896,10 @@ where
             );
         }
 
-        Self::update_sequential_block_gas_counters(&accumulated_fee_statement, ret.len());
+        Self::update_sequential_block_gas_counters(
+            &accumulated_fee_statement,
+            from_index + ret.len(),
+        );
         ret.resize_with(num_txns, E::Output::skip_output);
         Ok(ret)
     }
@@ -836,10 +938,340 @@ where
                 base_view,
             )
         }
+
+        // Validate the block invariants (if the block executed successfully)
+        if let Ok(outputs) = &ret {
+            if let Err(error) = E::init(executor_arguments).validate_block_invariants(outputs) {
+                ret = Err(Error::UserError(error));
+            }
+        }
+
         self.executor_thread_pool.spawn(move || {
             // Explicit async drops.
             drop(signature_verified_txns);
         });
         ret
     }
+
+    /// Executes the block (using `execute_block`) and returns the outputs along
+    /// with the gas used by each committed transaction (in index order), e.g., for
+    /// exporting per-transaction gas accounting. The gas used sums to the total gas
+    /// used by the block. Note: skipped transactions (e.g., transactions after the
+    /// block gas limit was reached) are not committed (their outputs have a retry
+    /// status), so the gas breakdown may be shorter than the outputs.
+    pub fn execute_block_with_gas_breakdown(
+        &self,
+        executor_arguments: E::Argument,
+        signature_verified_block: BlockExecutorTransactions<T>,
+        base_view: &S,
+    ) -> Result<(Vec<E::Output>, Vec<u64>), E::Error> {
+        let outputs =
+            self.execute_block(executor_arguments, signature_verified_block, base_view)?;
+        let num_committed_txns = outputs
+            .iter()
+            .position(|output| output.status().is_retry())
+            .unwrap_or(outputs.len());
+        let gas_used = outputs[..num_committed_txns]
+            .iter()
+            .map(|output| output.gas_used())
+            .collect();
+        Ok((outputs, gas_used))
+    }
+
+    /// Executes the block (using `execute_block`) and returns the outputs along
+    /// with the events emitted by the block, e.g., for indexing. The events are
+    /// flattened in transaction order, and then in emission order (i.e., they are
+    /// identical to the events obtained by walking the outputs).
+    pub fn execute_block_with_events(
+        &self,
+        executor_arguments: E::Argument,
+        signature_verified_block: BlockExecutorTransactions<T>,
+        base_view: &S,
+    ) -> Result<(Vec<E::Output>, Vec<ContractEvent>), E::Error> {
+        let outputs =
+            self.execute_block(executor_arguments, signature_verified_block, base_view)?;
+        let events = outputs
+            .iter()
+            .flat_map(|output| output.get_events())
+            .collect();
+        Ok((outputs, events))
+    }
+
+    /// Executes the block (using `execute_block`) and returns the outputs along
+    /// with the status of each transaction, e.g., for debugging failed transactions.
+    /// The statuses are aligned with the outputs by index (i.e., they are identical
+    /// to the statuses obtained by walking the outputs).
+    pub fn execute_block_with_status(
+        &self,
+        executor_arguments: E::Argument,
+        signature_verified_block: BlockExecutorTransactions<T>,
+        base_view: &S,
+    ) -> Result<(Vec<E::Output>, Vec<TransactionStatus>), E::Error> {
+        let outputs =
+            self.execute_block(executor_arguments, signature_verified_block, base_view)?;
+        let statuses = outputs.iter().map(|output| output.status()).collect();
+        Ok((outputs, statuses))
+    }
+
+    /// Executes the block sequentially, skipping the transactions for which `skip`
+    /// returns true (e.g., transactions filtered out by a block filter). Skipped
+    /// transactions are not executed and a skip output is returned in their place,
+    /// so the outputs remain aligned with the block indices. Skipped transactions
+    /// have no effect on subsequent transactions (i.e., as if they never ran).
+    pub fn execute_block_filtered(
+        &self,
+        executor_arguments: E::Argument,
+        signature_verified_block: BlockExecutorTransactions<T>,
+        base_view: &S,
+        skip: impl Fn(TxnIndex) -> bool,
+    ) -> Result<Vec<E::Output>, E::Error> {
+        let signature_verified_txns = signature_verified_block.into_txns();
+        let mut ret = self.execute_transactions_sequential_from(
+            executor_arguments,
+            &signature_verified_txns,
+            base_view,
+            &[],
+            &skip,
+            None,
+        );
+
+        // Validate the block invariants (if the block executed successfully)
+        if let Ok(outputs) = &ret {
+            if let Err(error) = E::init(executor_arguments).validate_block_invariants(outputs) {
+                ret = Err(Error::UserError(error));
+            }
+        }
+
+        self.executor_thread_pool.spawn(move || {
+            // Explicit async drops.
+            drop(signature_verified_txns);
+        });
+        ret
+    }
+
+    /// Executes the block and returns its net write set, i.e., the last value
+    /// written to each key by the committed transactions (so applying the net
+    /// write set to the base view is equivalent to applying all outputs in
+    /// order). This is useful for state-diff tooling. The block is executed
+    /// sequentially, so that all deltas are materialized as writes, and the
+    /// writes are folded into the net write set as each transaction commits.
+    /// Note: the block executor is generic over the key and value types, so
+    /// the net write set is returned as an ordered map (the VM turns it into a
+    /// `WriteSet`, see `BlockAptosVM::execute_block_write_set`).
+    pub fn execute_block_write_set(
+        &self,
+        executor_arguments: E::Argument,
+        signature_verified_block: BlockExecutorTransactions<T>,
+        base_view: &S,
+    ) -> Result<BTreeMap<T::Key, T::Value>, E::Error> {
+        let signature_verified_txns = signature_verified_block.into_txns();
+        let mut net_write_set = BTreeMap::new();
+        let mut ret = self.execute_transactions_sequential_from(
+            executor_arguments,
+            &signature_verified_txns,
+            base_view,
+            &[],
+            &|_| false,
+            Some(&mut net_write_set),
+        );
+
+        // Validate the block invariants (if the block executed successfully)
+        if let Ok(outputs) = &ret {
+            if let Err(error) = E::init(executor_arguments).validate_block_invariants(outputs) {
+                ret = Err(Error::UserError(error));
+            }
+        }
+
+        self.executor_thread_pool.spawn(move || {
+            // Explicit async drops.
+            drop(signature_verified_txns);
+        });
+        ret.map(|_| net_write_set)
+    }
+
+    /// Resumes the execution of a partially executed block (e.g., after a crash),
+    /// given the outputs of the committed prefix of the block (i.e., the outputs of
+    /// all transactions before `from_index`). The remaining transactions are executed
+    /// sequentially, using the state resulting from the prefix, and only their outputs
+    /// are returned. The prefix followed by the returned outputs is identical to the
+    /// outputs of a full `execute_block`. Note: the prefix must only contain committed
+    /// outputs with materialized deltas (i.e., outputs that still expose their writes
+    /// and fee statement once committed), and the block invariants are not validated
+    /// (as the prefix outputs are borrowed). An inconsistent prefix is rejected with
+    /// `Error::InvalidCommittedPrefix`.
+    pub fn resume_block(
+        &self,
+        executor_arguments: E::Argument,
+        signature_verified_block: BlockExecutorTransactions<T>,
+        base_view: &S,
+        committed_prefix: &[E::Output],
+        from_index: TxnIndex,
+    ) -> Result<Vec<E::Output>, E::Error> {
+        let signature_verified_txns = signature_verified_block.into_txns();
+        if committed_prefix.len() != from_index as usize {
+            return Err(Error::InvalidCommittedPrefix(format!(
+                "The committed prefix has {} outputs, but the resume index is {}",
+                committed_prefix.len(),
+                from_index
+            )));
+        }
+        if committed_prefix.len() > signature_verified_txns.len() {
+            return Err(Error::InvalidCommittedPrefix(format!(
+                "The committed prefix has {} outputs, but the block only has {} transactions",
+                committed_prefix.len(),
+                signature_verified_txns.len()
+            )));
+        }
+
+        let ret = self.execute_transactions_sequential_from(
+            executor_arguments,
+            &signature_verified_txns,
+            base_view,
+            committed_prefix,
+            &|_| false,
+            None,
+        );
+
+        self.executor_thread_pool.spawn(move || {
+            // Explicit async drops.
+            drop(signature_verified_txns);
+        });
+        ret
+    }
+}
+
+impl<T, E, S, L, X> BlockExecutor<T, E, S, L, X>
+where
+    T: Transaction,
+    E: ExecutorTask<Txn = T>,
+    E::Output: PartialEq,
+    S: TStateView<Key = T::Key> + Sync,
+    L: TransactionCommitHook<Output = E::Output>,
+    X: Executable + 'static,
+{
+    /// Executes the block twice (using `execute_block`) and verifies that both
+    /// executions produce identical results. This is useful for detecting
+    /// nondeterministic transactions. If the results are identical, the outputs
+    /// of the first execution are returned. Otherwise, a `DeterminismError` is
+    /// returned, e.g., if a transaction writes a different value on each
+    /// execution, `DivergentOutputs` identifies the index of that transaction
+    /// (or of the first such transaction, if there are several). Note: the
+    /// commit hook (if any) is invoked for both executions.
+    pub fn execute_block_checked_deterministic(
+        &self,
+        executor_arguments: E::Argument,
+        signature_verified_block: BlockExecutorTransactions<T>,
+        base_view: &S,
+    ) -> ::std::result::Result<Vec<E::Output>, DeterminismError<E::Error>> {
+        let signature_verified_txns = signature_verified_block.into_txns();
+        let first_result = self.execute_block(
+            executor_arguments,
+            BlockExecutorTransactions::Unsharded(signature_verified_txns.clone()),
+            base_view,
+        );
+        let second_result = self.execute_block(
+            executor_arguments,
+            BlockExecutorTransactions::Unsharded(signature_verified_txns),
+            base_view,
+        );
+
+        match (first_result, second_result) {
+            (Ok(first_outputs), Ok(second_outputs)) => {
+                match first_outputs
+                    .iter()
+                    .zip(second_outputs.iter())
+                    .position(|(first_output, second_output)| first_output != second_output)
+                {
+                    Some(txn_idx) => Err(DeterminismError::DivergentOutputs(txn_idx as TxnIndex)),
+                    None => Ok(first_outputs),
+                }
+            },
+            (Err(first_error), Err(second_error)) if first_error == second_error => {
+                Err(DeterminismError::ExecutionError(first_error))
+            },
+            _ => Err(DeterminismError::DivergentResults),
+        }
+    }
+}
+
+/// A cheap estimate of the cost of executing a block of transactions.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct BlockCostEstimate {
+    /// The number of transactions in the block.
+    pub num_txns: usize,
+    /// The estimated gas required to execute all transactions in the block.
+    pub estimated_gas: u64,
+}
+
+impl<T, E, S, L, X> BlockExecutor<T, E, S, L, X>
+where
+    T: Transaction,
+{
+    /// Estimates the cost of executing the given block, based only on the number and
+    /// metadata of the transactions (i.e., without executing them). This allows schedulers
+    /// to estimate the cost of a block before committing a worker to it. The estimate is
+    /// monotonic in the number of transactions, as each transaction contributes a cost of
+    /// at least one.
+    pub fn estimate_block_cost(&self, txns: &BlockExecutorTransactions<T>) -> BlockCostEstimate {
+        let estimated_gas = match txns {
+            BlockExecutorTransactions::Unsharded(txns) => estimate_txns_gas(txns.iter()),
+            BlockExecutorTransactions::Sharded(sub_blocks) => {
+                estimate_txns_gas(sub_blocks.iter().map(|txn| txn.txn()))
+            },
+        };
+        BlockCostEstimate {
+            num_txns: txns.num_txns(),
+            estimated_gas,
+        }
+    }
+}
+
+fn estimate_txns_gas<'a, T: Transaction>(txns: impl Iterator<Item = &'a T>) -> u64 {
+    txns.fold(0, |estimated_gas: u64, txn| {
+        estimated_gas.saturating_add(txn.estimated_cost().max(1))
+    })
+}
+
+/// An async companion to [`BlockExecutor::execute_block`], for callers that run
+/// the executor inside a Tokio runtime. The CPU-bound block execution is moved
+/// onto the blocking thread pool, so the runtime's worker threads are not blocked.
+#[async_trait]
+pub trait AsyncBlockExecutor<T: Transaction, E: ExecutorTask<Txn = T>, S> {
+    /// Executes the block of transactions and returns exactly the same
+    /// outputs (or error) as the synchronous `execute_block`.
+    async fn execute_block_async(
+        self: Arc<Self>,
+        executor_arguments: E::Argument,
+        signature_verified_block: BlockExecutorTransactions<T>,
+        base_view: Arc<S>,
+    ) -> Result<Vec<E::Output>, E::Error>;
+}
+
+#[async_trait]
+impl<T, E, S, L, X> AsyncBlockExecutor<T, E, S> for BlockExecutor<T, E, S, L, X>
+where
+    T: Transaction,
+    E: ExecutorTask<Txn = T> + Send + 'static,
+    E::Argument: Send + 'static,
+    S: TStateView<Key = T::Key> + Send + Sync + 'static,
+    L: TransactionCommitHook<Output = E::Output> + 'static,
+    X: Executable + 'static,
+{
+    async fn execute_block_async(
+        self: Arc<Self>,
+        executor_arguments: E::Argument,
+        signature_verified_block: BlockExecutorTransactions<T>,
+        base_view: Arc<S>,
+    ) -> Result<Vec<E::Output>, E::Error> {
+        tokio::task::spawn_blocking(move || {
+            self.execute_block(
+                executor_arguments,
+                signature_verified_block,
+                base_view.as_ref(),
+            )
+        })
+        .await
+        // Propagate any panic in the execution, as the synchronous path would
+        .unwrap_or_else(|error| std::panic::resume_unwind(error.into_panic()))
+    }
 }
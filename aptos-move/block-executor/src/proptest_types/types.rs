@@ -15,9 +15,11 @@ use aptos_state_view::{StateViewId, TStateView};
 use aptos_types::{
     access_path::AccessPath,
     account_address::AccountAddress,
+    contract_event::ContractEvent,
     executable::ModulePath,
     fee_statement::FeeStatement,
     state_store::{state_storage_usage::StateStorageUsage, state_value::StateValue},
+    transaction::{ExecutionStatus as TransactionExecutionStatus, TransactionStatus},
     write_set::{TransactionWrite, WriteOp},
 };
 use claims::{assert_none, assert_ok};
@@ -461,20 +463,25 @@ where
                     writes_and_deltas[write_idx].1.clone(),
                     reads_result,
                     OnceCell::new(),
+                    false,
                 ))
             },
-            Transaction::SkipRest => ExecutionStatus::SkipRest(Output::skip_output()),
+            // The skip rest transaction is committed (it only halts the block)
+            Transaction::SkipRest => {
+                ExecutionStatus::SkipRest(Output(vec![], vec![], vec![], OnceCell::new(), false))
+            },
             Transaction::Abort => ExecutionStatus::Abort(txn_idx as usize),
         }
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 pub struct Output<K, V>(
     Vec<(K, V)>,
     Vec<(K, DeltaOp)>,
     Vec<Option<Vec<u8>>>,
     pub(crate) OnceCell<Vec<(K, WriteOp)>>,
+    bool, // Whether this is a skip output (i.e., the transaction wasn't committed)
 );
 
 impl<K, V> Output<K, V>
@@ -507,7 +514,7 @@ where
     }
 
     fn skip_output() -> Self {
-        Self(vec![], vec![], vec![], OnceCell::new())
+        Self(vec![], vec![], vec![], OnceCell::new(), true)
     }
 
     fn incorporate_delta_writes(&self, delta_writes: Vec<(K, WriteOp)>) {
@@ -515,11 +522,30 @@ where
     }
 
     fn gas_used(&self) -> u64 {
-        1
+        self.fee_statement().gas_used()
     }
 
     fn fee_statement(&self) -> FeeStatement {
-        FeeStatement::new(1, 1, 0, 0, 0)
+        // Skip outputs aren't executed, so they don't consume any gas
+        if self.4 {
+            FeeStatement::zero()
+        } else {
+            FeeStatement::new(1, 1, 0, 0, 0)
+        }
+    }
+
+    fn get_events(&self) -> Vec<ContractEvent> {
+        vec![] // The test transactions don't emit events
+    }
+
+    fn status(&self) -> TransactionStatus {
+        // The test transactions always succeed (aborts halt the block instead),
+        // but skipped transactions must be retried (as they weren't committed).
+        if self.4 {
+            TransactionStatus::Retry
+        } else {
+            TransactionStatus::Keep(TransactionExecutionStatus::Success)
+        }
     }
 }
 
@@ -696,14 +722,14 @@ impl<V: Debug + Clone + PartialEq + Eq + TransactionWrite> ExpectedOutput<V> {
                     .iter()
                     .take(*skip_at)
                     .zip(expected_results.iter())
-                    .for_each(|(Output(_, _, result, _), expected_results)| {
+                    .for_each(|(Output(_, _, result, ..), expected_results)| {
                         Self::check_result(expected_results, result)
                     });
 
                 results
                     .iter()
                     .skip(*skip_at)
-                    .for_each(|Output(_, _, result, _)| assert!(result.is_empty()))
+                    .for_each(|Output(_, _, result, ..)| assert!(result.is_empty()))
             },
             (Self::ExceedBlockGasLimit(last_committed, expected_results), Ok(results)) => {
                 // Check_result asserts internally, so no need to return a bool.
@@ -711,14 +737,14 @@ impl<V: Debug + Clone + PartialEq + Eq + TransactionWrite> ExpectedOutput<V> {
                     .iter()
                     .take(*last_committed + 1)
                     .zip(expected_results.iter())
-                    .for_each(|(Output(_, _, result, _), expected_results)| {
+                    .for_each(|(Output(_, _, result, ..), expected_results)| {
                         Self::check_result(expected_results, result)
                     });
 
                 results
                     .iter()
                     .skip(*last_committed + 1)
-                    .for_each(|Output(_, _, result, _)| assert!(result.is_empty()))
+                    .for_each(|Output(_, _, result, ..)| assert!(result.is_empty()))
             },
             (Self::DeltaFailure(fail_idx, expected_results), Ok(results)) => {
                 // Check_result asserts internally, so no need to return a bool.
@@ -726,14 +752,14 @@ impl<V: Debug + Clone + PartialEq + Eq + TransactionWrite> ExpectedOutput<V> {
                     .iter()
                     .take(*fail_idx)
                     .zip(expected_results.iter())
-                    .for_each(|(Output(_, _, result, _), expected_results)| {
+                    .for_each(|(Output(_, _, result, ..), expected_results)| {
                         Self::check_result(expected_results, result)
                     });
             },
             (Self::Success(expected_results), Ok(results)) => results
                 .iter()
                 .zip(expected_results.iter())
-                .for_each(|(Output(_, _, result, _), expected_result)| {
+                .for_each(|(Output(_, _, result, ..), expected_result)| {
                     Self::check_result(expected_result, result);
                 }),
             _ => panic!("Incomparable execution outcomes"),
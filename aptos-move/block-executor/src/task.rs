@@ -6,8 +6,10 @@ use aptos_aggregator::delta_change_set::DeltaOp;
 use aptos_mvhashmap::types::TxnIndex;
 use aptos_state_view::TStateView;
 use aptos_types::{
+    contract_event::ContractEvent,
     executable::ModulePath,
     fee_statement::FeeStatement,
+    transaction::TransactionStatus,
     write_set::{TransactionWrite, WriteOp},
 };
 use std::{fmt::Debug, hash::Hash};
@@ -30,8 +32,18 @@ pub enum ExecutionStatus<T, E> {
 pub trait Transaction: Sync + Send + Clone + 'static {
     type Key: PartialOrd + Ord + Send + Sync + Clone + Hash + Eq + ModulePath + Debug;
     type Value: Send + Sync + Clone + TransactionWrite;
+
+    /// Returns a cheap estimate of the cost (in gas units) of executing the transaction.
+    /// The estimate should only be derived from the transaction metadata (i.e., without
+    /// executing the transaction), and it need not be exact.
+    fn estimated_cost(&self) -> u64 {
+        DEFAULT_ESTIMATED_TRANSACTION_COST
+    }
 }
 
+/// The estimated cost of a transaction that doesn't provide its own estimate.
+pub const DEFAULT_ESTIMATED_TRANSACTION_COST: u64 = 1;
+
 /// Inference result of a transaction.
 pub struct Accesses<K> {
     pub keys_read: Vec<K>,
@@ -65,6 +77,14 @@ pub trait ExecutorTask: Sync {
         txn_idx: TxnIndex,
         materialize_deltas: bool,
     ) -> ExecutionStatus<Self::Output, Self::Error>;
+
+    /// Validate the aggregate invariants of the block (e.g., total supply conservation
+    /// or event count bounds), given the outputs of all transactions. This is called
+    /// once the block has been executed successfully, and an error aborts the block.
+    /// By default, no invariants are checked.
+    fn validate_block_invariants(&self, _outputs: &[Self::Output]) -> Result<(), Self::Error> {
+        Ok(())
+    }
 }
 
 /// Trait for execution result of a single transaction.
@@ -72,7 +92,9 @@ pub trait TransactionOutput: Send + Sync + Debug {
     /// Type of transaction and its associated key and value.
     type Txn: Transaction;
 
-    /// Get the writes of a transaction from its output.
+    /// Get the writes of a transaction from its output. The writes (along with the
+    /// deltas and the fee statement) must remain readable once the output is
+    /// committed, e.g., so that a block can be resumed from its committed prefix.
     fn get_writes(
         &self,
     ) -> Vec<(
@@ -99,4 +121,16 @@ pub trait TransactionOutput: Send + Sync + Debug {
 
     /// Return the fee statement of the transaction.
     fn fee_statement(&self) -> FeeStatement;
+
+    /// Get the events emitted by the transaction (in emission order). By default,
+    /// transactions are assumed not to emit any events.
+    fn get_events(&self) -> Vec<ContractEvent> {
+        vec![]
+    }
+
+    /// Return the status of the transaction (e.g., kept with a success or abort
+    /// status, discarded, or to be retried). Skip outputs must be reported as
+    /// retried, as the executor relies on this to detect them (e.g., when
+    /// resuming a block or counting the committed transactions).
+    fn status(&self) -> TransactionStatus;
 }
@@ -3,20 +3,30 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    executor::BlockExecutor,
+    errors::{DeterminismError, Error},
+    executor::{AsyncBlockExecutor, BlockExecutor},
     proptest_types::types::{
         DeltaDataView, ExpectedOutput, KeyType, Output, Task, Transaction, ValueType,
     },
     scheduler::{DependencyResult, ExecutionTaskType, Scheduler, SchedulerTask},
+    task::{ExecutionStatus, ExecutorTask, TransactionOutput},
     txn_commit_hook::NoOpTransactionCommitHook,
 };
 use aptos_aggregator::delta_change_set::{delta_add, delta_sub, DeltaOp, DeltaUpdate};
 use aptos_mvhashmap::types::TxnIndex;
+use aptos_state_view::TStateView;
 use aptos_types::{
+    account_address::AccountAddress,
+    block_executor::partitioner::BlockExecutorTransactions,
+    contract_event::ContractEvent,
+    event::EventKey,
     executable::{ExecutableTestType, ModulePath},
-    write_set::TransactionWrite,
+    fee_statement::FeeStatement,
+    transaction::{ExecutionStatus as TransactionExecutionStatus, TransactionStatus},
+    write_set::{TransactionWrite, WriteOp},
 };
 use claims::{assert_matches, assert_some_eq};
+use move_core_types::{language_storage::TypeTag, vm_status::AbortLocation};
 use rand::{prelude::*, random};
 use std::{
     cmp::min,
@@ -36,12 +46,7 @@ where
         phantom: PhantomData,
     };
 
-    let executor_thread_pool = Arc::new(
-        rayon::ThreadPoolBuilder::new()
-            .num_threads(num_cpus::get())
-            .build()
-            .unwrap(),
-    );
+    let executor_thread_pool = create_executor_thread_pool();
 
     let output = BlockExecutor::<
         Transaction<K, V>,
@@ -60,6 +65,42 @@ fn random_value(delete_value: bool) -> ValueType<Vec<u8>> {
     ValueType((0..4).map(|_| (random::<u8>())).collect(), !delete_value)
 }
 
+type TestKey = KeyType<[u8; 32]>;
+type TestValue = ValueType<Vec<u8>>;
+type TestDataView = DeltaDataView<TestKey, TestValue>;
+
+/// A block executor over the test keys and values, using the given task.
+type TestBlockExecutor<E> = BlockExecutor<
+    Transaction<TestKey, TestValue>,
+    E,
+    TestDataView,
+    NoOpTransactionCommitHook<<E as ExecutorTask>::Output, usize>,
+    ExecutableTestType,
+>;
+
+fn create_executor_thread_pool() -> Arc<rayon::ThreadPool> {
+    Arc::new(
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(num_cpus::get())
+            .build()
+            .unwrap(),
+    )
+}
+
+/// Creates a block where each transaction writes a single (random) key
+fn single_write_block(num_txns: usize) -> Vec<Transaction<TestKey, TestValue>> {
+    (0..num_txns)
+        .map(|_| Transaction::Write {
+            incarnation: Arc::new(AtomicUsize::new(0)),
+            reads: vec![vec![]],
+            writes_and_deltas: vec![(
+                vec![(KeyType(random::<[u8; 32]>(), false), random_value(false))],
+                vec![],
+            )],
+        })
+        .collect()
+}
+
 #[test]
 fn empty_block() {
     // This test checks that we do not trigger asserts due to an empty block, e.g. in the
@@ -265,6 +306,808 @@ fn early_skips() {
     run_and_assert(transactions)
 }
 
+#[tokio::test]
+async fn execute_block_async_matches_sync() {
+    let mut transactions = vec![];
+    let keys: Vec<_> = (0..TXN_PER_BLOCK)
+        .map(|_| KeyType(random::<[u8; 32]>(), false))
+        .collect();
+
+    // Generate a series of transactions that read and write overlapping keys.
+    for (i, key) in keys.iter().enumerate() {
+        let next_key = keys[(i + 1) % keys.len()];
+        transactions.push(Transaction::Write {
+            incarnation: Arc::new(AtomicUsize::new(0)),
+            reads: vec![vec![*key, next_key]],
+            writes_and_deltas: vec![(vec![(next_key, random_value(false))], vec![])],
+        })
+    }
+
+    let data_view = Arc::new(TestDataView {
+        phantom: PhantomData,
+    });
+    let executor_thread_pool = create_executor_thread_pool();
+    let block_executor = Arc::new(TestBlockExecutor::<Task<TestKey, TestValue>>::new(
+        num_cpus::get(),
+        executor_thread_pool,
+        None,
+        None,
+    ));
+
+    // Execute the block using the sync and async paths
+    let sync_output = block_executor.execute_block(
+        (),
+        BlockExecutorTransactions::Unsharded(transactions.clone()),
+        data_view.as_ref(),
+    );
+    let async_output = block_executor
+        .clone()
+        .execute_block_async(
+            (),
+            BlockExecutorTransactions::Unsharded(transactions.clone()),
+            data_view,
+        )
+        .await;
+
+    // Verify both outputs match the baseline and each other
+    let baseline = ExpectedOutput::generate_baseline(&transactions, None, None);
+    baseline.assert_output(&sync_output);
+    baseline.assert_output(&async_output);
+
+    let sync_output = sync_output.unwrap();
+    let async_output = async_output.unwrap();
+    assert_eq!(sync_output.len(), async_output.len());
+    for (sync_output, async_output) in sync_output.iter().zip(async_output.iter()) {
+        assert_eq!(sync_output.get_writes(), async_output.get_writes());
+        assert_eq!(sync_output.gas_used(), async_output.gas_used());
+    }
+}
+
+#[test]
+fn execute_block_checked_deterministic() {
+    let data_view = TestDataView {
+        phantom: PhantomData,
+    };
+    let executor_thread_pool = create_executor_thread_pool();
+
+    // Create a deterministic block (each transaction always has the same writes)
+    let keys: Vec<_> = (0..10)
+        .map(|_| KeyType(random::<[u8; 32]>(), false))
+        .collect();
+    let mut transactions: Vec<_> = keys
+        .iter()
+        .map(|key| Transaction::Write {
+            incarnation: Arc::new(AtomicUsize::new(0)),
+            reads: vec![vec![]],
+            writes_and_deltas: vec![(vec![(*key, random_value(false))], vec![])],
+        })
+        .collect();
+
+    // Verify the deterministic block passes the check (both sequentially and in parallel)
+    for concurrency_level in [1, num_cpus::get()] {
+        let block_executor = TestBlockExecutor::<Task<TestKey, TestValue>>::new(
+            concurrency_level,
+            executor_thread_pool.clone(),
+            None,
+            None,
+        );
+        let outputs = block_executor
+            .execute_block_checked_deterministic(
+                (),
+                BlockExecutorTransactions::Unsharded(transactions.clone()),
+                &data_view,
+            )
+            .unwrap();
+        assert_eq!(outputs.len(), transactions.len());
+    }
+
+    // Make the transaction at index 5 nondeterministic (i.e., its writes alternate
+    // between executions), and verify the divergence is reported at that index.
+    transactions[5] = Transaction::Write {
+        incarnation: Arc::new(AtomicUsize::new(0)),
+        reads: vec![vec![]],
+        writes_and_deltas: vec![
+            (vec![(keys[5], random_value(false))], vec![]),
+            (vec![(keys[5], random_value(false))], vec![]),
+        ],
+    };
+    let block_executor =
+        TestBlockExecutor::<Task<TestKey, TestValue>>::new(1, executor_thread_pool, None, None);
+    let result = block_executor.execute_block_checked_deterministic(
+        (),
+        BlockExecutorTransactions::Unsharded(transactions),
+        &data_view,
+    );
+    assert_matches!(result, Err(DeterminismError::DivergentOutputs(5)));
+}
+
+/// A task that wraps the naive test task, and validates that the total number
+/// of writes in the block doesn't exceed the maximum given as the argument.
+struct MaxWritesTask<K, V> {
+    max_writes: usize,
+    task: Task<K, V>,
+}
+
+impl<K, V> ExecutorTask for MaxWritesTask<K, V>
+where
+    K: PartialOrd + Ord + Send + Sync + Clone + Hash + Eq + ModulePath + Debug + 'static,
+    V: Send + Sync + Debug + Clone + TransactionWrite + 'static,
+{
+    type Argument = usize;
+    type Error = usize;
+    type Output = Output<K, V>;
+    type Txn = Transaction<K, V>;
+
+    fn init(max_writes: Self::Argument) -> Self {
+        Self {
+            max_writes,
+            task: Task::new(),
+        }
+    }
+
+    fn execute_transaction(
+        &self,
+        view: &impl TStateView<Key = K>,
+        txn: &Self::Txn,
+        txn_idx: TxnIndex,
+        materialize_deltas: bool,
+    ) -> ExecutionStatus<Self::Output, Self::Error> {
+        self.task
+            .execute_transaction(view, txn, txn_idx, materialize_deltas)
+    }
+
+    fn validate_block_invariants(&self, outputs: &[Self::Output]) -> Result<(), Self::Error> {
+        let num_writes: usize = outputs.iter().map(|output| output.get_writes().len()).sum();
+        if num_writes > self.max_writes {
+            Err(num_writes)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[test]
+fn execute_block_with_gas_breakdown() {
+    let data_view = TestDataView {
+        phantom: PhantomData,
+    };
+    let executor_thread_pool = create_executor_thread_pool();
+
+    // The test transactions use one unit of gas each, so the total gas of the
+    // block is the number of committed transactions (with and without a limit).
+    let num_txns = 20;
+    let transactions = single_write_block(num_txns);
+
+    for (block_gas_limit, num_committed_txns) in [(None, num_txns), (Some(10), 10)] {
+        for concurrency_level in [1, num_cpus::get()] {
+            let block_executor = TestBlockExecutor::<Task<TestKey, TestValue>>::new(
+                concurrency_level,
+                executor_thread_pool.clone(),
+                block_gas_limit,
+                None,
+            );
+            let (outputs, gas_used) = block_executor
+                .execute_block_with_gas_breakdown(
+                    (),
+                    BlockExecutorTransactions::Unsharded(transactions.clone()),
+                    &data_view,
+                )
+                .unwrap();
+
+            // Verify there's a gas entry for each committed transaction
+            assert_eq!(outputs.len(), num_txns);
+            assert_eq!(gas_used.len(), num_committed_txns);
+
+            // Verify the per-transaction gas sums to the aggregate gas of the block
+            assert_eq!(gas_used, vec![1; num_committed_txns]);
+            assert_eq!(gas_used.iter().sum::<u64>(), num_committed_txns as u64);
+        }
+    }
+}
+
+#[test]
+fn resume_block() {
+    let data_view = TestDataView {
+        phantom: PhantomData,
+    };
+    let executor_thread_pool = create_executor_thread_pool();
+
+    // Create a block where each transaction writes a key and reads the key written
+    // by the previous transaction (so the resumed transactions depend on the prefix).
+    let num_txns = 20;
+    let keys: Vec<_> = (0..num_txns)
+        .map(|_| KeyType(random::<[u8; 32]>(), false))
+        .collect();
+    let transactions: Vec<_> = (0..num_txns)
+        .map(|idx| Transaction::Write {
+            incarnation: Arc::new(AtomicUsize::new(0)),
+            reads: vec![keys[..idx].iter().rev().take(1).cloned().collect()],
+            writes_and_deltas: vec![(vec![(keys[idx], random_value(false))], vec![])],
+        })
+        .collect();
+
+    let block_executor =
+        TestBlockExecutor::<Task<TestKey, TestValue>>::new(1, executor_thread_pool, None, None);
+
+    // Execute the full block
+    let full_outputs = block_executor
+        .execute_block(
+            (),
+            BlockExecutorTransactions::Unsharded(transactions.clone()),
+            &data_view,
+        )
+        .unwrap();
+    assert_eq!(full_outputs.len(), num_txns);
+
+    // Resume the block from the midpoint and verify the outputs match the full run
+    let from_index = num_txns / 2;
+    let resumed_outputs = block_executor
+        .resume_block(
+            (),
+            BlockExecutorTransactions::Unsharded(transactions.clone()),
+            &data_view,
+            &full_outputs[..from_index],
+            from_index as TxnIndex,
+        )
+        .unwrap();
+    assert_eq!(resumed_outputs.len(), num_txns - from_index);
+    assert_eq!(&full_outputs[from_index..], resumed_outputs.as_slice());
+
+    // Verify that resuming from the start and the end also matches the full run
+    let resumed_outputs = block_executor
+        .resume_block(
+            (),
+            BlockExecutorTransactions::Unsharded(transactions.clone()),
+            &data_view,
+            &[],
+            0,
+        )
+        .unwrap();
+    assert_eq!(full_outputs, resumed_outputs);
+    let resumed_outputs = block_executor
+        .resume_block(
+            (),
+            BlockExecutorTransactions::Unsharded(transactions.clone()),
+            &data_view,
+            &full_outputs,
+            num_txns as TxnIndex,
+        )
+        .unwrap();
+    assert!(resumed_outputs.is_empty());
+
+    // Verify that a prefix that doesn't end at the resume index is rejected
+    let result = block_executor.resume_block(
+        (),
+        BlockExecutorTransactions::Unsharded(transactions),
+        &data_view,
+        &full_outputs[..from_index],
+        (from_index + 1) as TxnIndex,
+    );
+    assert_matches!(result, Err(Error::InvalidCommittedPrefix(_)));
+}
+
+#[test]
+fn resume_block_after_skip_rest() {
+    let data_view = TestDataView {
+        phantom: PhantomData,
+    };
+    let executor_thread_pool = create_executor_thread_pool();
+
+    // Create a block that is halted by a SkipRest transaction
+    let num_txns = 6;
+    let skip_rest_index = 2;
+    let transactions: Vec<_> = (0..num_txns)
+        .map(|idx| {
+            if idx == skip_rest_index {
+                Transaction::SkipRest
+            } else {
+                Transaction::Write {
+                    incarnation: Arc::new(AtomicUsize::new(0)),
+                    reads: vec![vec![]],
+                    writes_and_deltas: vec![(
+                        vec![(KeyType(random::<[u8; 32]>(), false), random_value(false))],
+                        vec![],
+                    )],
+                }
+            }
+        })
+        .collect();
+
+    let block_executor =
+        TestBlockExecutor::<Task<TestKey, TestValue>>::new(1, executor_thread_pool, None, None);
+
+    // Execute the full block
+    let full_outputs = block_executor
+        .execute_block(
+            (),
+            BlockExecutorTransactions::Unsharded(transactions.clone()),
+            &data_view,
+        )
+        .unwrap();
+
+    // Verify that resuming before, at and after the SkipRest transaction (i.e.,
+    // from a prefix that ends with it, or a prefix that has skip outputs) matches
+    // the full run.
+    for from_index in [skip_rest_index, skip_rest_index + 1, skip_rest_index + 2] {
+        let resumed_outputs = block_executor
+            .resume_block(
+                (),
+                BlockExecutorTransactions::Unsharded(transactions.clone()),
+                &data_view,
+                &full_outputs[..from_index],
+                from_index as TxnIndex,
+            )
+            .unwrap();
+        assert_eq!(&full_outputs[from_index..], resumed_outputs.as_slice());
+    }
+}
+
+#[test]
+fn execute_block_filtered() {
+    let data_view = TestDataView {
+        phantom: PhantomData,
+    };
+    let executor_thread_pool = create_executor_thread_pool();
+
+    // Create a block where each transaction writes a key and reads the key written
+    // by the previous transaction (so the last transaction reads the skipped write).
+    let num_txns = 3;
+    let keys: Vec<_> = (0..num_txns)
+        .map(|_| KeyType(random::<[u8; 32]>(), false))
+        .collect();
+    let transactions: Vec<_> = (0..num_txns)
+        .map(|idx| Transaction::Write {
+            incarnation: Arc::new(AtomicUsize::new(0)),
+            reads: vec![keys[..idx].iter().rev().take(1).cloned().collect()],
+            writes_and_deltas: vec![(vec![(keys[idx], random_value(false))], vec![])],
+        })
+        .collect();
+
+    let block_executor =
+        TestBlockExecutor::<Task<TestKey, TestValue>>::new(1, executor_thread_pool, None, None);
+
+    // Execute the full block (without filtering)
+    let full_outputs = block_executor
+        .execute_block(
+            (),
+            BlockExecutorTransactions::Unsharded(transactions.clone()),
+            &data_view,
+        )
+        .unwrap();
+
+    // Execute the block, skipping the middle transaction
+    let filtered_outputs = block_executor
+        .execute_block_filtered(
+            (),
+            BlockExecutorTransactions::Unsharded(transactions.clone()),
+            &data_view,
+            |txn_idx| txn_idx == 1,
+        )
+        .unwrap();
+    assert_eq!(filtered_outputs.len(), num_txns);
+    assert_eq!(filtered_outputs[0], full_outputs[0]);
+    assert_eq!(filtered_outputs[1], Output::skip_output());
+
+    // Verify the last transaction doesn't see the skipped write (i.e., its output
+    // is identical to executing the block without the skipped transaction).
+    assert_ne!(filtered_outputs[2], full_outputs[2]);
+    let unfiltered_outputs = block_executor
+        .execute_block(
+            (),
+            BlockExecutorTransactions::Unsharded(vec![
+                transactions[0].clone(),
+                transactions[2].clone(),
+            ]),
+            &data_view,
+        )
+        .unwrap();
+    assert_eq!(filtered_outputs[2], unfiltered_outputs[1]);
+}
+
+#[test]
+fn execute_block_write_set() {
+    let data_view = TestDataView {
+        phantom: PhantomData,
+    };
+    let executor_thread_pool = create_executor_thread_pool();
+
+    // Create a block where two transactions write the same key
+    let shared_key = KeyType(random::<[u8; 32]>(), false);
+    let other_key = KeyType(random::<[u8; 32]>(), false);
+    let first_value = random_value(false);
+    let final_value = random_value(false);
+    let other_value = random_value(false);
+    let transactions = vec![
+        Transaction::Write {
+            incarnation: Arc::new(AtomicUsize::new(0)),
+            reads: vec![vec![]],
+            writes_and_deltas: vec![(vec![(shared_key, first_value)], vec![])],
+        },
+        Transaction::Write {
+            incarnation: Arc::new(AtomicUsize::new(0)),
+            reads: vec![vec![shared_key]],
+            writes_and_deltas: vec![(vec![(other_key, other_value.clone())], vec![])],
+        },
+        Transaction::Write {
+            incarnation: Arc::new(AtomicUsize::new(0)),
+            reads: vec![vec![]],
+            writes_and_deltas: vec![(vec![(shared_key, final_value.clone())], vec![])],
+        },
+    ];
+
+    let block_executor =
+        TestBlockExecutor::<Task<TestKey, TestValue>>::new(1, executor_thread_pool, None, None);
+
+    // Verify only the final value of the shared key appears in the net write set
+    let net_write_set = block_executor
+        .execute_block_write_set(
+            (),
+            BlockExecutorTransactions::Unsharded(transactions.clone()),
+            &data_view,
+        )
+        .unwrap();
+    assert_eq!(
+        net_write_set,
+        BTreeMap::from([(shared_key, final_value), (other_key, other_value)])
+    );
+
+    // Verify the net write set is identical to applying all outputs in order
+    let outputs = block_executor
+        .execute_block(
+            (),
+            BlockExecutorTransactions::Unsharded(transactions),
+            &data_view,
+        )
+        .unwrap();
+    let mut applied_writes = BTreeMap::new();
+    for output in outputs {
+        for (key, value) in output.get_writes() {
+            applied_writes.insert(key, value);
+        }
+    }
+    assert_eq!(net_write_set, applied_writes);
+}
+
+/// An output that wraps the output of the naive test task, along with the
+/// events emitted by, and the status of, the transaction.
+#[derive(Debug)]
+struct WrappedOutput<K, V> {
+    output: Output<K, V>,
+    events: Vec<ContractEvent>,
+    status: TransactionStatus,
+}
+
+impl<K, V> TransactionOutput for WrappedOutput<K, V>
+where
+    K: PartialOrd + Ord + Send + Sync + Clone + Hash + Eq + ModulePath + Debug + 'static,
+    V: Send + Sync + Debug + Clone + TransactionWrite + 'static,
+{
+    type Txn = Transaction<K, V>;
+
+    fn get_writes(&self) -> Vec<(K, V)> {
+        self.output.get_writes()
+    }
+
+    fn get_deltas(&self) -> Vec<(K, DeltaOp)> {
+        self.output.get_deltas()
+    }
+
+    fn skip_output() -> Self {
+        Self {
+            output: Output::skip_output(),
+            events: vec![],
+            status: TransactionStatus::Retry,
+        }
+    }
+
+    fn incorporate_delta_writes(&self, delta_writes: Vec<(K, WriteOp)>) {
+        self.output.incorporate_delta_writes(delta_writes)
+    }
+
+    fn gas_used(&self) -> u64 {
+        self.output.gas_used()
+    }
+
+    fn fee_statement(&self) -> FeeStatement {
+        self.output.fee_statement()
+    }
+
+    fn get_events(&self) -> Vec<ContractEvent> {
+        self.events.clone()
+    }
+
+    fn status(&self) -> TransactionStatus {
+        self.status.clone()
+    }
+}
+
+/// Returns the events emitted by the transaction at the given index, i.e.,
+/// `txn_idx % 3` events (so some transactions emit no events).
+fn get_test_events(txn_idx: TxnIndex) -> Vec<ContractEvent> {
+    (0..txn_idx % 3)
+        .map(|sequence_number| {
+            ContractEvent::new(
+                EventKey::new(txn_idx as u64, AccountAddress::ZERO),
+                sequence_number as u64,
+                TypeTag::U64,
+                vec![txn_idx as u8],
+            )
+        })
+        .collect()
+}
+
+/// Returns the status of the transaction at the given index, i.e., every
+/// third transaction is kept with a move abort, and all other transactions
+/// are kept with a success.
+fn get_test_status(txn_idx: TxnIndex) -> TransactionStatus {
+    if txn_idx % 3 == 2 {
+        TransactionStatus::Keep(TransactionExecutionStatus::MoveAbort {
+            location: AbortLocation::Script,
+            code: txn_idx as u64,
+            info: None,
+        })
+    } else {
+        TransactionStatus::Keep(TransactionExecutionStatus::Success)
+    }
+}
+
+/// Returns a success status for every transaction
+fn get_success_status(_txn_idx: TxnIndex) -> TransactionStatus {
+    TransactionStatus::Keep(TransactionExecutionStatus::Success)
+}
+
+/// Returns no events for every transaction
+fn get_no_events(_txn_idx: TxnIndex) -> Vec<ContractEvent> {
+    vec![]
+}
+
+/// A task that wraps the naive test task, and attaches the events and status
+/// returned by the given functions (i.e., the task argument) to the output of
+/// each transaction.
+struct WrappedTask<K, V> {
+    task: Task<K, V>,
+    events_fn: fn(TxnIndex) -> Vec<ContractEvent>,
+    status_fn: fn(TxnIndex) -> TransactionStatus,
+}
+
+impl<K, V> ExecutorTask for WrappedTask<K, V>
+where
+    K: PartialOrd + Ord + Send + Sync + Clone + Hash + Eq + ModulePath + Debug + 'static,
+    V: Send + Sync + Debug + Clone + TransactionWrite + 'static,
+{
+    type Argument = (
+        fn(TxnIndex) -> Vec<ContractEvent>,
+        fn(TxnIndex) -> TransactionStatus,
+    );
+    type Error = usize;
+    type Output = WrappedOutput<K, V>;
+    type Txn = Transaction<K, V>;
+
+    fn init((events_fn, status_fn): Self::Argument) -> Self {
+        Self {
+            task: Task::new(),
+            events_fn,
+            status_fn,
+        }
+    }
+
+    fn execute_transaction(
+        &self,
+        view: &impl TStateView<Key = K>,
+        txn: &Self::Txn,
+        txn_idx: TxnIndex,
+        materialize_deltas: bool,
+    ) -> ExecutionStatus<Self::Output, Self::Error> {
+        let wrap = |output| WrappedOutput {
+            output,
+            events: (self.events_fn)(txn_idx),
+            status: (self.status_fn)(txn_idx),
+        };
+        match self
+            .task
+            .execute_transaction(view, txn, txn_idx, materialize_deltas)
+        {
+            ExecutionStatus::Success(output) => ExecutionStatus::Success(wrap(output)),
+            ExecutionStatus::SkipRest(output) => ExecutionStatus::SkipRest(wrap(output)),
+            ExecutionStatus::Abort(error) => ExecutionStatus::Abort(error),
+        }
+    }
+}
+
+#[test]
+fn execute_block_with_events() {
+    let data_view = TestDataView {
+        phantom: PhantomData,
+    };
+    let executor_thread_pool = create_executor_thread_pool();
+
+    let num_txns = 20;
+    let transactions = single_write_block(num_txns);
+
+    for concurrency_level in [1, num_cpus::get()] {
+        let block_executor = TestBlockExecutor::<WrappedTask<TestKey, TestValue>>::new(
+            concurrency_level,
+            executor_thread_pool.clone(),
+            None,
+            None,
+        );
+        let (outputs, events) = block_executor
+            .execute_block_with_events(
+                (get_test_events, get_success_status),
+                BlockExecutorTransactions::Unsharded(transactions.clone()),
+                &data_view,
+            )
+            .unwrap();
+        assert_eq!(outputs.len(), num_txns);
+
+        // Verify the flattened events match the events of each output
+        let expected_events: Vec<_> = outputs
+            .iter()
+            .flat_map(|output| output.get_events())
+            .collect();
+        assert_eq!(events, expected_events);
+
+        // Verify the events are in transaction, then emission, order
+        let expected_num_events: usize = (0..num_txns).map(|txn_idx| txn_idx % 3).sum();
+        assert_eq!(events.len(), expected_num_events);
+        let event_order: Vec<_> = events
+            .iter()
+            .map(|event| (event.event_data()[0], event.sequence_number()))
+            .collect();
+        let mut sorted_event_order = event_order.clone();
+        sorted_event_order.sort();
+        assert_eq!(event_order, sorted_event_order);
+    }
+}
+
+#[test]
+fn execute_block_with_status() {
+    let data_view = TestDataView {
+        phantom: PhantomData,
+    };
+    let executor_thread_pool = create_executor_thread_pool();
+
+    let num_txns = 20;
+    let transactions = single_write_block(num_txns);
+
+    for concurrency_level in [1, num_cpus::get()] {
+        let block_executor = TestBlockExecutor::<WrappedTask<TestKey, TestValue>>::new(
+            concurrency_level,
+            executor_thread_pool.clone(),
+            None,
+            None,
+        );
+        let (outputs, statuses) = block_executor
+            .execute_block_with_status(
+                (get_no_events, get_test_status),
+                BlockExecutorTransactions::Unsharded(transactions.clone()),
+                &data_view,
+            )
+            .unwrap();
+        assert_eq!(outputs.len(), num_txns);
+
+        // Verify the statuses match the statuses embedded in the outputs
+        let embedded_statuses: Vec<_> = outputs.iter().map(|output| output.status()).collect();
+        assert_eq!(statuses, embedded_statuses);
+
+        // Verify the statuses mix successful and aborted transactions (by index)
+        let expected_statuses: Vec<_> = (0..num_txns as TxnIndex).map(get_test_status).collect();
+        assert_eq!(statuses, expected_statuses);
+        assert!(statuses.iter().any(|status| matches!(
+            status,
+            TransactionStatus::Keep(TransactionExecutionStatus::MoveAbort { .. })
+        )));
+    }
+}
+
+#[test]
+fn execute_block_with_status_retries_skipped_transactions() {
+    let data_view = TestDataView {
+        phantom: PhantomData,
+    };
+    let executor_thread_pool = create_executor_thread_pool();
+
+    // Each test transaction uses one unit of gas, so the block gas limit
+    // is reached (and the remaining transactions skipped) after 10 transactions.
+    let num_txns = 20;
+    let block_gas_limit = 10;
+    let transactions = single_write_block(num_txns);
+
+    for concurrency_level in [1, num_cpus::get()] {
+        let block_executor = TestBlockExecutor::<Task<TestKey, TestValue>>::new(
+            concurrency_level,
+            executor_thread_pool.clone(),
+            Some(block_gas_limit),
+            None,
+        );
+        let (outputs, statuses) = block_executor
+            .execute_block_with_status(
+                (),
+                BlockExecutorTransactions::Unsharded(transactions.clone()),
+                &data_view,
+            )
+            .unwrap();
+        assert_eq!(outputs.len(), num_txns);
+
+        // Verify the committed transactions are kept, and the skipped ones are retried
+        let num_committed_txns = block_gas_limit as usize;
+        for (txn_idx, status) in statuses.iter().enumerate() {
+            if txn_idx < num_committed_txns {
+                assert_eq!(
+                    status,
+                    &TransactionStatus::Keep(TransactionExecutionStatus::Success)
+                );
+            } else {
+                assert_eq!(status, &TransactionStatus::Retry);
+            }
+        }
+    }
+}
+
+#[test]
+fn validate_block_invariants() {
+    let data_view = TestDataView {
+        phantom: PhantomData,
+    };
+    let executor_thread_pool = create_executor_thread_pool();
+
+    let num_txns = 10;
+    let transactions = single_write_block(num_txns);
+
+    for concurrency_level in [1, num_cpus::get()] {
+        let block_executor = TestBlockExecutor::<MaxWritesTask<TestKey, TestValue>>::new(
+            concurrency_level,
+            executor_thread_pool.clone(),
+            None,
+            None,
+        );
+
+        // Verify the block passes validation when the invariant holds
+        let outputs = block_executor
+            .execute_block(
+                num_txns,
+                BlockExecutorTransactions::Unsharded(transactions.clone()),
+                &data_view,
+            )
+            .unwrap();
+        assert_eq!(outputs.len(), num_txns);
+
+        // Verify the block fails validation when the invariant is violated
+        let result = block_executor.execute_block(
+            num_txns - 1,
+            BlockExecutorTransactions::Unsharded(transactions.clone()),
+            &data_view,
+        );
+        assert_eq!(result.unwrap_err(), Error::UserError(num_txns));
+    }
+}
+
+#[test]
+fn estimate_block_cost_grows_with_block_size() {
+    let executor_thread_pool = create_executor_thread_pool();
+    let block_executor = TestBlockExecutor::<Task<TestKey, TestValue>>::new(
+        num_cpus::get(),
+        executor_thread_pool,
+        None,
+        None,
+    );
+
+    // Estimate the cost of increasingly large blocks
+    let mut previous_estimate = None;
+    for num_txns in [0, 1, 10, 100] {
+        let transactions = single_write_block(num_txns);
+        let estimate =
+            block_executor.estimate_block_cost(&BlockExecutorTransactions::Unsharded(transactions));
+        assert_eq!(estimate.num_txns, num_txns);
+
+        // Verify a larger block yields a larger estimate
+        if let Some(previous_estimate) = previous_estimate {
+            assert!(estimate.estimated_gas > previous_estimate);
+        }
+        previous_estimate = Some(estimate.estimated_gas);
+    }
+}
+
 #[test]
 fn scheduler_tasks() {
     let s = Scheduler::new(5);
@@ -0,0 +1,66 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::MoveHarness;
+use aptos_cached_packages::aptos_stdlib;
+use aptos_types::{
+    account_address::AccountAddress,
+    account_config::CORE_CODE_ADDRESS,
+    on_chain_config::{GasScheduleV2, OnChainConfig},
+    transaction::{ExecutionStatus, Transaction, TransactionOutput, TransactionStatus},
+};
+use aptos_vm::{AptosVM, VMExecutor};
+
+#[test]
+fn execute_block_with_gas_schedule() {
+    let mut h = MoveHarness::new();
+
+    // Create a block with a simple transfer
+    let alice = h.new_account_at(AccountAddress::from_hex_literal("0xa11ce").unwrap());
+    let bob = h.new_account_at(AccountAddress::from_hex_literal("0xb0b").unwrap());
+    let txn = h.create_transaction_payload(
+        &alice,
+        aptos_stdlib::aptos_coin_transfer(*bob.address(), 1000),
+    );
+    let block = vec![Transaction::UserTransaction(txn)];
+
+    // Create a gas schedule that doubles the minimum transaction gas
+    let gas_schedule: GasScheduleV2 = h
+        .read_resource(&CORE_CODE_ADDRESS, GasScheduleV2::struct_tag())
+        .unwrap();
+    let mut expensive_gas_schedule = gas_schedule.clone();
+    for (key, value) in expensive_gas_schedule.entries.iter_mut() {
+        if key == "txn.min_transaction_gas_units" {
+            *value *= 2;
+        }
+    }
+
+    // Execute the block using the on-chain gas schedule and the injected gas schedules
+    let state_view = h.executor.data_store();
+    let default_output = AptosVM::execute_block(block.clone(), state_view, None).unwrap();
+    let injected_default_output =
+        AptosVM::execute_block_with_gas_schedule(block.clone(), state_view, &gas_schedule, None)
+            .unwrap();
+    let expensive_output =
+        AptosVM::execute_block_with_gas_schedule(block, state_view, &expensive_gas_schedule, None)
+            .unwrap();
+
+    // Verify that injecting the on-chain gas schedule doesn't change the outputs
+    assert_eq!(default_output, injected_default_output);
+
+    // Verify that the gas used reflects the expensive gas schedule
+    let default_gas_used = get_successful_gas_used(&default_output);
+    let expensive_gas_used = get_successful_gas_used(&expensive_output);
+    assert!(expensive_gas_used > default_gas_used);
+}
+
+/// Returns the gas used by the single (successful) transaction in the outputs
+fn get_successful_gas_used(outputs: &[TransactionOutput]) -> u64 {
+    assert_eq!(outputs.len(), 1);
+    let output = &outputs[0];
+    assert_eq!(
+        output.status(),
+        &TransactionStatus::Keep(ExecutionStatus::Success)
+    );
+    output.gas_used()
+}
@@ -12,6 +12,7 @@ mod error_map;
 mod fee_payer;
 mod fungible_asset;
 mod gas;
+mod gas_schedule_override;
 mod generate_upgrade_script;
 mod governance_updates;
 mod infinite_loop;
@@ -434,6 +434,24 @@ impl FakeExecutor {
         )
     }
 
+    /// Executes the block and returns its net write set (see
+    /// `BlockAptosVM::execute_block_write_set`). The data store is not modified.
+    pub fn execute_transaction_block_write_set(
+        &self,
+        txn_block: Vec<Transaction>,
+    ) -> Result<WriteSet, VMStatus> {
+        BlockAptosVM::execute_block_write_set::<
+            _,
+            NoOpTransactionCommitHook<AptosTransactionOutput, VMStatus>,
+        >(
+            self.executor_thread_pool.clone(),
+            BlockExecutorTransactions::Unsharded(txn_block),
+            &self.data_store,
+            None,
+            None,
+        )
+    }
+
     pub fn execute_transaction_block(
         &self,
         txn_block: Vec<Transaction>,
@@ -7,7 +7,10 @@ use aptos_language_e2e_tests::{
 };
 use aptos_types::{
     account_config::{DepositEvent, WithdrawEvent},
-    transaction::{ExecutionStatus, SignedTransaction, TransactionOutput, TransactionStatus},
+    transaction::{
+        ExecutionStatus, SignedTransaction, Transaction, TransactionOutput, TransactionStatus,
+    },
+    write_set::WriteSetMut,
 };
 use std::{convert::TryFrom, time::Instant};
 
@@ -129,6 +132,62 @@ fn few_peer_to_peer_with_event() {
     }
 }
 
+#[test]
+fn few_peer_to_peer_net_write_set() {
+    let mut executor = FakeExecutor::from_head_genesis();
+
+    // create and publish a sender and a receiver with 3_000_000 coins each
+    let sender = executor.create_raw_account_data(3_000_000, 10);
+    let receiver = executor.create_raw_account_data(3_000_000, 10);
+    executor.add_account_data(&sender);
+    executor.add_account_data(&receiver);
+
+    // create several transfers that write the same keys
+    let transfer_amount = 1_000;
+    let txns: Vec<_> = (10..14)
+        .map(|sequence_number| {
+            Transaction::UserTransaction(peer_to_peer_txn(
+                sender.account(),
+                receiver.account(),
+                sequence_number,
+                transfer_amount,
+                0,
+            ))
+        })
+        .collect();
+
+    // the net write set must hold the last write to each key by the block
+    let outputs = executor.execute_transaction_block(txns.clone()).unwrap();
+    let expected_write_set = WriteSetMut::new(outputs.iter().flat_map(|output| {
+        output
+            .write_set()
+            .iter()
+            .map(|(state_key, write_op)| (state_key.clone(), write_op.clone()))
+    }))
+    .freeze()
+    .unwrap();
+    let net_write_set = executor.execute_transaction_block_write_set(txns).unwrap();
+    assert_eq!(net_write_set, expected_write_set);
+
+    // applying the net write set must be equivalent to applying each output
+    executor.apply_write_set(&net_write_set);
+    let updated_sender_balance = executor
+        .read_coin_store_resource(sender.account())
+        .expect("sender balance must exist");
+    let updated_receiver_balance = executor
+        .read_coin_store_resource(receiver.account())
+        .expect("receiver balance must exist");
+    assert_eq!(
+        3_000_000 + 4 * transfer_amount,
+        updated_receiver_balance.coin()
+    );
+    assert!(updated_sender_balance.coin() < 3_000_000 - 4 * transfer_amount); // gas is also charged
+    let updated_sender = executor
+        .read_account_resource(sender.account())
+        .expect("sender must exist");
+    assert_eq!(14, updated_sender.sequence_number());
+}
+
 // Holder for transaction data; arguments to transactions.
 pub(crate) struct TxnInfo {
     pub sender: Account,
@@ -142,10 +142,17 @@ impl Default for StateSyncDriverConfig {
 #[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq, Serialize)]
 #[serde(default, deny_unknown_fields)]
 pub struct StorageServiceConfig {
+    /// Whether to reject the pre-fusion transaction and transaction output requests
+    /// (i.e., those superseded by the transactions or outputs requests)
+    pub deprecate_pre_fusion_requests: bool,
+    /// Maximum number of requests in a single batch request
+    pub max_batch_request_size: u64,
     /// Maximum number of concurrent storage server tasks
     pub max_concurrent_requests: u64,
     /// Maximum number of epoch ending ledger infos per chunk
     pub max_epoch_chunk_size: u64,
+    /// Maximum number of in-flight requests before low-priority requests are shed
+    pub max_in_flight_requests_before_shedding: u64,
     /// Maximum number of invalid requests per peer
     pub max_invalid_requests_per_peer: u64,
     /// Maximum number of items in the lru cache before eviction
@@ -173,8 +180,11 @@ pub struct StorageServiceConfig {
 impl Default for StorageServiceConfig {
     fn default() -> Self {
         Self {
+            deprecate_pre_fusion_requests: false,
+            max_batch_request_size: 10,
             max_concurrent_requests: 4000,
             max_epoch_chunk_size: MAX_EPOCH_CHUNK_SIZE,
+            max_in_flight_requests_before_shedding: 3000,
             max_invalid_requests_per_peer: 500,
             max_lru_cache_size: 500, // At ~0.6MiB per chunk, this should take no more than 0.5GiB
             max_network_channel_size: 4000,
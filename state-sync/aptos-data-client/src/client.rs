@@ -33,8 +33,11 @@ use aptos_storage_service_types::{
         StateValuesWithProofRequest, StorageServiceRequest, TransactionOutputsWithProofRequest,
         TransactionsOrOutputsWithProofRequest, TransactionsWithProofRequest,
     },
-    responses::{StorageServerSummary, StorageServiceResponse, TransactionOrOutputListWithProof},
-    Epoch, StorageServiceMessage,
+    responses::{
+        requires_compression, StorageServerSummary, StorageServiceResponse,
+        TransactionOrOutputListWithProof,
+    },
+    Epoch, StorageServiceError, StorageServiceMessage,
 };
 use aptos_time_service::TimeService;
 use aptos_types::{
@@ -349,8 +352,12 @@ impl AptosDataClient {
 
         let (context, storage_response) = response.into_parts();
 
-        // Ensure the response obeys the compression requirements
-        if request.use_compression && !storage_response.is_compressed() {
+        // Ensure the response obeys the compression requirements. Note: trivially
+        // small responses are accepted raw, even if compression was requested.
+        if request.use_compression
+            && !storage_response.is_compressed()
+            && requires_compression(&storage_response.get_label())
+        {
             return Err(Error::InvalidResponse(format!(
                 "Requested compressed data, but the response was uncompressed! Response: {:?}",
                 storage_response.get_label()
@@ -450,8 +457,12 @@ impl AptosDataClient {
                         },
                         _ => Error::UnexpectedErrorEncountered(rpc_error.to_string()),
                     },
-                    aptos_storage_service_client::Error::StorageServiceError(err) => {
-                        Error::UnexpectedErrorEncountered(err.to_string())
+                    aptos_storage_service_client::Error::StorageServiceError(err) => match err {
+                        // Surface unsupported requests distinctly (these shouldn't be retried)
+                        StorageServiceError::UnsupportedRequest(_) => {
+                            Error::UnsupportedRequest(err.to_string())
+                        },
+                        _ => Error::UnexpectedErrorEncountered(err.to_string()),
                     },
                     _ => Error::UnexpectedErrorEncountered(error.to_string()),
                 };
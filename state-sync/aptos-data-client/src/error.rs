@@ -21,6 +21,8 @@ pub enum Error {
     TimeoutWaitingForResponse(String),
     #[error("Unexpected error encountered: {0}")]
     UnexpectedErrorEncountered(String),
+    #[error("Unsupported request: {0}")]
+    UnsupportedRequest(String),
 }
 
 impl Error {
@@ -33,6 +35,7 @@ impl Error {
             Self::InvalidResponse(_) => "invalid_response",
             Self::TimeoutWaitingForResponse(_) => "timeout_waiting_for_response",
             Self::UnexpectedErrorEncountered(_) => "unexpected_error_encountered",
+            Self::UnsupportedRequest(_) => "unsupported_request",
         }
     }
 }
@@ -27,7 +27,8 @@ use aptos_storage_interface::DbReader;
 use aptos_storage_service_client::StorageServiceClient;
 use aptos_storage_service_server::network::{NetworkRequest, ResponseSender};
 use aptos_storage_service_types::{
-    responses::TransactionOrOutputListWithProof, Epoch, StorageServiceMessage,
+    requests::RequestPriority, responses::TransactionOrOutputListWithProof, Epoch,
+    StorageServiceMessage,
 };
 use aptos_time_service::{MockTimeService, TimeService};
 use aptos_types::{
@@ -169,8 +170,13 @@ impl MockNetwork {
                 let res_tx = network_request.res_tx;
 
                 let message: StorageServiceMessage = bcs::from_bytes(data.as_ref()).unwrap();
-                let storage_service_request = match message {
-                    StorageServiceMessage::Request(request) => request,
+                let (storage_service_request, priority) = match message {
+                    StorageServiceMessage::Request(request) => {
+                        (request, RequestPriority::default())
+                    },
+                    StorageServiceMessage::PrioritizedRequest(request, priority) => {
+                        (request, priority)
+                    },
                     _ => panic!("unexpected: {:?}", message),
                 };
                 let response_sender = ResponseSender::new(res_tx);
@@ -179,6 +185,7 @@ impl MockNetwork {
                     peer_network_id,
                     protocol_id,
                     storage_service_request,
+                    priority,
                     response_sender,
                 })
             },
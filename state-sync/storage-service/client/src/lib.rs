@@ -10,8 +10,9 @@ use aptos_network::{
     protocols::network::RpcError,
 };
 use aptos_storage_service_types::{
-    requests::StorageServiceRequest, responses::StorageServiceResponse, StorageServiceError,
-    StorageServiceMessage,
+    requests::{RequestPriority, StorageServiceRequest},
+    responses::StorageServiceResponse,
+    StorageServiceError, StorageServiceMessage,
 };
 use std::{sync::Arc, time::Duration};
 use thiserror::Error;
@@ -48,18 +49,42 @@ impl<NetworkClient: NetworkClientInterface<StorageServiceMessage>>
         timeout: Duration,
         request: StorageServiceRequest,
     ) -> Result<StorageServiceResponse, Error> {
+        self.send_request_with_priority(recipient, timeout, request, RequestPriority::High)
+            .await
+    }
+
+    /// Sends the request with the given priority. High-priority requests are
+    /// sent as regular requests (so that older servers can still decode them).
+    /// Low-priority requests are sent as prioritized requests, which require an
+    /// upgraded server: older servers can't decode the message, so the request
+    /// will fail with a network error (instead of a storage service response).
+    /// Callers that can't rule out older servers should use `send_request`.
+    pub async fn send_request_with_priority(
+        &self,
+        recipient: PeerNetworkId,
+        timeout: Duration,
+        request: StorageServiceRequest,
+        priority: RequestPriority,
+    ) -> Result<StorageServiceResponse, Error> {
+        let message = match priority {
+            RequestPriority::High => StorageServiceMessage::Request(request),
+            priority => StorageServiceMessage::PrioritizedRequest(request, priority),
+        };
         let response = self
             .network_client
-            .send_to_peer_rpc(StorageServiceMessage::Request(request), timeout, recipient)
+            .send_to_peer_rpc(message, timeout, recipient)
             .await
             .map_err(|error| Error::NetworkError(error.to_string()))?;
         match response {
             StorageServiceMessage::Response(Ok(response)) => Ok(response),
             StorageServiceMessage::Response(Err(err)) => Err(Error::StorageServiceError(err)),
-            StorageServiceMessage::Request(request) => Err(Error::NetworkError(format!(
-                "Got storage service request instead of response! Request: {:?}",
-                request
-            ))),
+            StorageServiceMessage::Request(request)
+            | StorageServiceMessage::PrioritizedRequest(request, _) => {
+                Err(Error::NetworkError(format!(
+                    "Got storage service request instead of response! Request: {:?}",
+                    request
+                )))
+            },
         }
     }
 
@@ -8,12 +8,16 @@ use thiserror::Error;
 pub enum Error {
     #[error("Invalid request received: {0}")]
     InvalidRequest(String),
+    #[error("Server overloaded: {0}")]
+    Overloaded(String),
     #[error("Storage error encountered: {0}")]
     StorageErrorEncountered(String),
     #[error("Too many invalid requests: {0}")]
     TooManyInvalidRequests(String),
     #[error("Unexpected error encountered: {0}")]
     UnexpectedErrorEncountered(String),
+    #[error("Unsupported request received: {0}")]
+    UnsupportedRequest(String),
 }
 
 impl Error {
@@ -21,9 +25,11 @@ impl Error {
     pub fn get_label(&self) -> &'static str {
         match self {
             Error::InvalidRequest(_) => "invalid_request",
+            Error::Overloaded(_) => "overloaded",
             Error::StorageErrorEncountered(_) => "storage_error",
             Error::TooManyInvalidRequests(_) => "too_many_invalid_requests",
             Error::UnexpectedErrorEncountered(_) => "unexpected_error",
+            Error::UnsupportedRequest(_) => "unsupported_request",
         }
     }
 }
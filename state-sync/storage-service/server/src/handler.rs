@@ -18,12 +18,14 @@ use aptos_infallible::{Mutex, RwLock};
 use aptos_logger::{debug, error, sample, sample::SampleRate, trace, warn};
 use aptos_storage_service_types::{
     requests::{
-        DataRequest, EpochEndingLedgerInfoRequest, StateValuesWithProofRequest,
-        StorageServiceRequest, TransactionOutputsWithProofRequest,
+        DataRequest, EpochChangeProofBetweenRequest, EpochEndingLedgerInfoRequest, RequestPriority,
+        StateValuesWithProofRequest, StorageServiceRequest, TransactionByHashWithProofRequest,
+        TransactionOutputsWithProofRequest, TransactionRangeProofRequest,
         TransactionsOrOutputsWithProofRequest, TransactionsWithProofRequest,
     },
     responses::{
-        DataResponse, ServerProtocolVersion, StorageServerSummary, StorageServiceResponse,
+        DataResponse, MinimalEpochChangeProof, ServerProtocolVersion, StorageServerSummary,
+        StorageServiceResponse,
     },
     StorageServiceError,
 };
@@ -37,6 +39,33 @@ const INVALID_REQUEST_LOG_FREQUENCY_SECS: u64 = 5; // The frequency to log inval
 const STORAGE_SERVER_VERSION: u64 = 1;
 const SUMMARY_LOG_FREQUENCY_SECS: u64 = 5; // The frequency to log the storage server summary (secs)
 
+/// A storage service response, tagged with whether it was served from the
+/// response cache or freshly computed. The tag is only used by the server
+/// (e.g., for metrics), and is never sent to the client.
+#[derive(Clone, Debug)]
+pub(crate) struct TaggedResponse {
+    pub response: StorageServiceResponse,
+    pub cached: bool,
+}
+
+impl TaggedResponse {
+    /// Creates a response that was served from the cache
+    fn cached(response: StorageServiceResponse) -> Self {
+        Self {
+            response,
+            cached: true,
+        }
+    }
+
+    /// Creates a response that was freshly computed
+    fn fresh(response: StorageServiceResponse) -> Self {
+        Self {
+            response,
+            cached: false,
+        }
+    }
+}
+
 /// The `Handler` is the "pure" inbound request handler. It contains all the
 /// necessary context and state needed to construct a response to an inbound
 /// request. We usually clone/create a new handler for every request.
@@ -75,6 +104,7 @@ impl<T: StorageReaderInterface> Handler<T> {
         &self,
         peer_network_id: PeerNetworkId,
         request: StorageServiceRequest,
+        priority: RequestPriority,
         response_sender: ResponseSender,
     ) {
         // Update the request count
@@ -84,6 +114,19 @@ impl<T: StorageReaderInterface> Handler<T> {
             request.get_label(),
         );
 
+        // Shed the request if it's low-priority and the server is overloaded.
+        // Note: this is checked before the request is counted as in-flight.
+        if let Err(error) = self.request_moderator.check_load(&request, priority) {
+            increment_counter(
+                &metrics::STORAGE_ERRORS_ENCOUNTERED,
+                peer_network_id.network_id(),
+                error.get_label().into(),
+            );
+            let response = Err(StorageServiceError::Overloaded(error.to_string()));
+            self.send_response(request, response, response_sender);
+            return;
+        }
+
         // Handle any optimistic fetch requests
         if request.data_request.is_optimistic_fetch() {
             self.handle_optimistic_fetch_request(peer_network_id, request, response_sender);
@@ -109,6 +152,9 @@ impl<T: StorageReaderInterface> Handler<T> {
             request.get_label(),
         );
 
+        // Track the request as in-flight (until the guard is dropped)
+        let _in_flight_request = self.request_moderator.start_request();
+
         // Process the request and handle any errors
         match self.validate_and_handle_request(peer_network_id, &request) {
             Err(error) => {
@@ -136,16 +182,26 @@ impl<T: StorageReaderInterface> Handler<T> {
                     Error::TooManyInvalidRequests(error) => {
                         Err(StorageServiceError::TooManyInvalidRequests(error))
                     },
+                    Error::UnsupportedRequest(error) => {
+                        Err(StorageServiceError::UnsupportedRequest(error))
+                    },
                     error => Err(StorageServiceError::InternalError(error.to_string())),
                 }
             },
-            Ok(response) => {
-                // Update the successful response counter
+            Ok(TaggedResponse { response, cached }) => {
+                // Update the successful response counters
                 increment_counter(
                     &metrics::STORAGE_RESPONSES_SENT,
                     peer_network_id.network_id(),
                     response.get_label(),
                 );
+                if cached {
+                    increment_counter(
+                        &metrics::CACHED_STORAGE_RESPONSES_SENT,
+                        peer_network_id.network_id(),
+                        response.get_label(),
+                    );
+                }
 
                 // Return the response
                 Ok(response)
@@ -158,7 +214,7 @@ impl<T: StorageReaderInterface> Handler<T> {
         &self,
         peer_network_id: &PeerNetworkId,
         request: &StorageServiceRequest,
-    ) -> Result<StorageServiceResponse, Error> {
+    ) -> Result<TaggedResponse, Error> {
         // Validate the request with the moderator
         self.request_moderator
             .validate_request(peer_network_id, request)?;
@@ -168,11 +224,25 @@ impl<T: StorageReaderInterface> Handler<T> {
             DataRequest::GetServerProtocolVersion => {
                 let data_response = self.get_server_protocol_version();
                 StorageServiceResponse::new(data_response, request.use_compression)
+                    .map(TaggedResponse::fresh)
                     .map_err(|error| error.into())
             },
             DataRequest::GetStorageServerSummary => {
                 let data_response = self.get_storage_server_summary();
                 StorageServiceResponse::new(data_response, request.use_compression)
+                    .map(TaggedResponse::fresh)
+                    .map_err(|error| error.into())
+            },
+            DataRequest::GetLatestLedgerInfo => {
+                let data_response = self.get_latest_ledger_info()?;
+                StorageServiceResponse::new(data_response, request.use_compression)
+                    .map(TaggedResponse::fresh)
+                    .map_err(|error| error.into())
+            },
+            DataRequest::GetBatch(data_requests) => {
+                let data_response = self.get_batch(data_requests)?;
+                StorageServiceResponse::new(data_response, request.use_compression)
+                    .map(TaggedResponse::fresh)
                     .map_err(|error| error.into())
             },
             _ => self.process_cachable_request(peer_network_id, request),
@@ -232,12 +302,13 @@ impl<T: StorageReaderInterface> Handler<T> {
     }
 
     /// Processes a storage service request for which the response
-    /// might already be cached.
-    fn process_cachable_request(
+    /// might already be cached. The returned response is tagged with
+    /// whether it was served from the cache.
+    pub(crate) fn process_cachable_request(
         &self,
         peer_network_id: &PeerNetworkId,
         request: &StorageServiceRequest,
-    ) -> aptos_storage_service_types::Result<StorageServiceResponse, Error> {
+    ) -> aptos_storage_service_types::Result<TaggedResponse, Error> {
         increment_counter(
             &metrics::LRU_CACHE_EVENT,
             peer_network_id.network_id(),
@@ -251,17 +322,37 @@ impl<T: StorageReaderInterface> Handler<T> {
                 peer_network_id.network_id(),
                 LRU_CACHE_HIT.into(),
             );
-            return Ok(response.clone());
+            return Ok(TaggedResponse::cached(response.clone()));
         }
 
         // Fetch the data response from storage
-        let data_response = match &request.data_request {
+        let data_response = self.get_cachable_data_response(&request.data_request)?;
+        let storage_response = StorageServiceResponse::new(data_response, request.use_compression)?;
+
+        // Cache the response before returning
+        let _ = self
+            .lru_response_cache
+            .lock()
+            .put(request.clone(), storage_response.clone());
+
+        Ok(TaggedResponse::fresh(storage_response))
+    }
+
+    /// Fetches the data response for the given (cachable) data request from storage
+    fn get_cachable_data_response(
+        &self,
+        data_request: &DataRequest,
+    ) -> aptos_storage_service_types::Result<DataResponse, Error> {
+        match data_request {
             DataRequest::GetStateValuesWithProof(request) => {
                 self.get_state_value_chunk_with_proof(request)
             },
             DataRequest::GetEpochEndingLedgerInfos(request) => {
                 self.get_epoch_ending_ledger_infos(request)
             },
+            DataRequest::GetEpochChangeProofBetween(request) => {
+                self.get_epoch_change_proof_between(request)
+            },
             DataRequest::GetNumberOfStatesAtVersion(version) => {
                 self.get_number_of_states_at_version(*version)
             },
@@ -274,20 +365,49 @@ impl<T: StorageReaderInterface> Handler<T> {
             DataRequest::GetTransactionsOrOutputsWithProof(request) => {
                 self.get_transactions_or_outputs_with_proof(request)
             },
+            DataRequest::GetTransactionRangeProof(request) => {
+                self.get_transaction_range_proof(request)
+            },
+            DataRequest::GetTransactionByHashWithProof(request) => {
+                self.get_transaction_by_hash_with_proof(request)
+            },
             _ => Err(Error::UnexpectedErrorEncountered(format!(
                 "Received an unexpected request: {:?}",
-                request
+                data_request
             ))),
-        }?;
-        let storage_response = StorageServiceResponse::new(data_response, request.use_compression)?;
-
-        // Cache the response before returning
-        let _ = self
-            .lru_response_cache
-            .lock()
-            .put(request.clone(), storage_response.clone());
+        }
+    }
 
-        Ok(storage_response)
+    /// Fetches the data responses for the given batch of data requests. The
+    /// inner requests are served (uncompressed) in the order they were given,
+    /// and the whole batch fails if any inner request fails. Nested batches
+    /// and optimistic fetches cannot be batched.
+    fn get_batch(
+        &self,
+        data_requests: &[DataRequest],
+    ) -> aptos_storage_service_types::Result<DataResponse, Error> {
+        let mut data_responses = Vec::with_capacity(data_requests.len());
+        for data_request in data_requests {
+            let data_response = match data_request {
+                DataRequest::GetBatch(_) => {
+                    return Err(Error::InvalidRequest(
+                        "Nested batch requests are not supported!".into(),
+                    ))
+                },
+                data_request if data_request.is_optimistic_fetch() => {
+                    return Err(Error::InvalidRequest(format!(
+                        "Optimistic fetches cannot be batched: {:?}",
+                        data_request
+                    )))
+                },
+                DataRequest::GetServerProtocolVersion => self.get_server_protocol_version(),
+                DataRequest::GetStorageServerSummary => self.get_storage_server_summary(),
+                DataRequest::GetLatestLedgerInfo => self.get_latest_ledger_info()?,
+                data_request => self.get_cachable_data_response(data_request)?,
+            };
+            data_responses.push(data_response);
+        }
+        DataResponse::new_batch(data_responses).map_err(|error| error.into())
     }
 
     fn get_state_value_chunk_with_proof(
@@ -316,6 +436,41 @@ impl<T: StorageReaderInterface> Handler<T> {
         Ok(DataResponse::EpochEndingLedgerInfos(epoch_change_proof))
     }
 
+    fn get_epoch_change_proof_between(
+        &self,
+        request: &EpochChangeProofBetweenRequest,
+    ) -> aptos_storage_service_types::Result<DataResponse, Error> {
+        // The proof must contain the epoch ending ledger infos for all
+        // epochs in [start_epoch, end_epoch), otherwise it doesn't link them.
+        let last_epoch = request
+            .end_epoch
+            .checked_sub(1)
+            .filter(|last_epoch| *last_epoch >= request.start_epoch)
+            .ok_or_else(|| {
+                Error::InvalidRequest(format!(
+                    "The start epoch must be less than the end epoch! Request: {:?}",
+                    request
+                ))
+            })?;
+        let epoch_change_proof = self
+            .storage
+            .get_epoch_ending_ledger_infos(request.start_epoch, last_epoch)?;
+
+        // Verify the proof wasn't truncated (e.g., due to chunk limits)
+        let expected_num_ledger_infos = last_epoch - request.start_epoch + 1;
+        if (epoch_change_proof.ledger_info_with_sigs.len() as u64) < expected_num_ledger_infos {
+            return Err(Error::UnexpectedErrorEncountered(format!(
+                "The epoch change proof is incomplete! Expected {:?} ledger infos, found {:?}.",
+                expected_num_ledger_infos,
+                epoch_change_proof.ledger_info_with_sigs.len()
+            )));
+        }
+
+        Ok(DataResponse::EpochChangeProofBetween(
+            MinimalEpochChangeProof(epoch_change_proof),
+        ))
+    }
+
     fn get_number_of_states_at_version(
         &self,
         version: Version,
@@ -337,6 +492,19 @@ impl<T: StorageReaderInterface> Handler<T> {
         DataResponse::StorageServerSummary(storage_server_summary)
     }
 
+    fn get_latest_ledger_info(&self) -> aptos_storage_service_types::Result<DataResponse, Error> {
+        let synced_ledger_info = self
+            .cached_storage_server_summary
+            .read()
+            .data_summary
+            .synced_ledger_info
+            .clone()
+            .ok_or_else(|| {
+                Error::StorageErrorEncountered("No synced ledger info was found!".into())
+            })?;
+        Ok(DataResponse::LatestLedgerInfo(synced_ledger_info))
+    }
+
     fn get_transaction_outputs_with_proof(
         &self,
         request: &TransactionOutputsWithProofRequest,
@@ -384,6 +552,40 @@ impl<T: StorageReaderInterface> Handler<T> {
             outputs_with_proof,
         )))
     }
+
+    fn get_transaction_range_proof(
+        &self,
+        request: &TransactionRangeProofRequest,
+    ) -> aptos_storage_service_types::Result<DataResponse, Error> {
+        let transaction_range_proof = self.storage.get_transaction_range_proof(
+            request.proof_version,
+            request.start_version,
+            request.end_version,
+        )?;
+
+        Ok(DataResponse::TransactionRangeProof(transaction_range_proof))
+    }
+
+    fn get_transaction_by_hash_with_proof(
+        &self,
+        request: &TransactionByHashWithProofRequest,
+    ) -> aptos_storage_service_types::Result<DataResponse, Error> {
+        let transaction_with_proof = self.storage.get_transaction_by_hash_with_proof(
+            request.transaction_hash,
+            request.proof_version,
+            request.include_events,
+        )?;
+
+        // Only return the transaction if it falls within the requested
+        // version range (i.e., the range the request was serviced against).
+        let transaction_with_proof = transaction_with_proof.filter(|transaction_with_proof| {
+            (request.start_version..=request.end_version).contains(&transaction_with_proof.version)
+        });
+
+        Ok(DataResponse::TransactionByHashWithProof(
+            transaction_with_proof,
+        ))
+    }
 }
 
 /// Logs the response sent by storage for a peer request
@@ -302,6 +302,7 @@ impl<T: StorageReaderInterface> StorageServiceServer<T> {
             let peer_network_id = network_request.peer_network_id;
             let protocol_id = network_request.protocol_id;
             let storage_service_request = network_request.storage_service_request;
+            let priority = network_request.priority;
             trace!(LogSchema::new(LogEntry::ReceivedStorageRequest)
                 .request(&storage_service_request)
                 .message(&format!(
@@ -331,6 +332,7 @@ impl<T: StorageReaderInterface> StorageServiceServer<T> {
                     .process_request_and_respond(
                         peer_network_id,
                         storage_service_request,
+                        priority,
                         network_request.response_sender,
                     );
                 })
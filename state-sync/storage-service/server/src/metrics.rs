@@ -15,6 +15,16 @@ pub const LRU_CACHE_PROBE: &str = "lru_cache_probe";
 pub const OPTIMISTIC_FETCH_ADD: &str = "optimistic_fetch_add";
 pub const OPTIMISTIC_FETCH_EXPIRE: &str = "optimistic_fetch_expire";
 
+/// Counter for storage service responses sent that were served from the cache
+pub static CACHED_STORAGE_RESPONSES_SENT: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "aptos_storage_service_server_cached_responses_sent",
+        "Counters related to the storage server responses served from the cache",
+        &["network_id", "response_type"]
+    )
+    .unwrap()
+});
+
 /// Gauge for tracking the number of actively ignored peers
 pub static IGNORED_PEER_COUNT: Lazy<IntGaugeVec> = Lazy::new(|| {
     register_int_gauge_vec!(
@@ -3,22 +3,39 @@
 
 use crate::{error::Error, logging::LogEntry, metrics, LogSchema};
 use aptos_config::{
-    config::StorageServiceConfig,
+    config::{StorageServiceConfig, MAX_APPLICATION_MESSAGE_SIZE},
     network_id::{NetworkId, PeerNetworkId},
 };
 use aptos_infallible::RwLock;
 use aptos_logger::warn;
 use aptos_network::application::storage::PeersAndMetadata;
 use aptos_storage_service_types::{
-    requests::StorageServiceRequest, responses::StorageServerSummary,
+    requests::{DataRequest, RequestPriority, StorageServiceRequest},
+    responses::{estimate_response_bytes, DeprecatedRequests, StorageServerSummary},
 };
 use aptos_time_service::{TimeService, TimeServiceTrait};
 use std::{
     collections::HashMap,
-    sync::Arc,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
     time::{Duration, Instant},
 };
 
+/// A guard that tracks a single in-flight request. The request is
+/// no longer considered in-flight once the guard is dropped.
+#[derive(Debug)]
+pub struct InFlightRequestGuard {
+    num_in_flight_requests: Arc<AtomicU64>,
+}
+
+impl Drop for InFlightRequestGuard {
+    fn drop(&mut self) {
+        self.num_in_flight_requests.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
 /// A simple struct that tracks the state of an unhealthy peer
 #[derive(Clone, Debug)]
 pub struct UnhealthyPeerState {
@@ -104,6 +121,8 @@ impl UnhealthyPeerState {
 /// "unhealthy" and will ignore requests from that peer for some time.
 pub struct RequestModerator {
     cached_storage_server_summary: Arc<RwLock<StorageServerSummary>>,
+    deprecated_requests: DeprecatedRequests,
+    num_in_flight_requests: Arc<AtomicU64>,
     peers_and_metadata: Arc<PeersAndMetadata>,
     storage_service_config: StorageServiceConfig,
     time_service: TimeService,
@@ -119,6 +138,8 @@ impl RequestModerator {
     ) -> Self {
         Self {
             cached_storage_server_summary,
+            deprecated_requests: DeprecatedRequests::from_config(&storage_service_config),
+            num_in_flight_requests: Arc::new(AtomicU64::new(0)),
             unhealthy_peer_states: Arc::new(RwLock::new(HashMap::new())),
             peers_and_metadata,
             storage_service_config,
@@ -126,6 +147,42 @@ impl RequestModerator {
         }
     }
 
+    /// Marks a request as in-flight until the returned guard is dropped
+    pub fn start_request(&self) -> InFlightRequestGuard {
+        self.num_in_flight_requests.fetch_add(1, Ordering::Relaxed);
+        InFlightRequestGuard {
+            num_in_flight_requests: self.num_in_flight_requests.clone(),
+        }
+    }
+
+    /// Returns the number of requests that are currently in-flight
+    pub fn get_num_in_flight_requests(&self) -> u64 {
+        self.num_in_flight_requests.load(Ordering::Relaxed)
+    }
+
+    /// Verifies that the server can handle a request with the given priority.
+    /// If the server is overloaded, low-priority requests are shed. Note: we
+    /// don't penalize peers for shed requests (they're still valid).
+    pub fn check_load(
+        &self,
+        request: &StorageServiceRequest,
+        priority: RequestPriority,
+    ) -> Result<(), Error> {
+        let num_in_flight_requests = self.get_num_in_flight_requests();
+        if priority == RequestPriority::Low
+            && num_in_flight_requests
+                >= self
+                    .storage_service_config
+                    .max_in_flight_requests_before_shedding
+        {
+            return Err(Error::Overloaded(format!(
+                "Too many in-flight requests ({:?}). Unable to handle low-priority request: {:?}",
+                num_in_flight_requests, request
+            )));
+        }
+        Ok(())
+    }
+
     /// Validates the given request and verifies that the peer is behaving
     /// correctly. If the request fails validation, an error is returned.
     pub fn validate_request(
@@ -143,9 +200,21 @@ impl RequestModerator {
             }
         }
 
+        // Verify the request kind is still supported. Note: we don't penalize
+        // peers for sending deprecated requests, as they may be running older
+        // software, but we return a distinct error to avoid any retries.
+        if let Err(error) = self.deprecated_requests.check_request_supported(request) {
+            return Err(Error::UnsupportedRequest(error.to_string()));
+        }
+
         // Get the latest storage server summary
         let storage_server_summary = self.cached_storage_server_summary.read().clone();
 
+        // If the request is a batch, verify the batch before touching storage
+        if let DataRequest::GetBatch(data_requests) = &request.data_request {
+            self.validate_batch_request(request, data_requests, &storage_server_summary)?;
+        }
+
         // Verify the request is serviceable using the current storage server summary
         if !storage_server_summary.can_service(request) {
             // Increment the invalid request count for the peer
@@ -178,6 +247,50 @@ impl RequestModerator {
         Ok(()) // The request is valid
     }
 
+    /// Validates the given batch request, i.e., verifies that the batch isn't
+    /// too long, that every batched request kind is still supported, and that
+    /// the response is not estimated to exceed the max message size.
+    fn validate_batch_request(
+        &self,
+        request: &StorageServiceRequest,
+        data_requests: &[DataRequest],
+        storage_server_summary: &StorageServerSummary,
+    ) -> Result<(), Error> {
+        // Verify the batch doesn't contain too many requests
+        let max_batch_request_size = self.storage_service_config.max_batch_request_size;
+        if data_requests.len() as u64 > max_batch_request_size {
+            return Err(Error::InvalidRequest(format!(
+                "The batch request is too long! Number of requests: {}, max: {}",
+                data_requests.len(),
+                max_batch_request_size
+            )));
+        }
+
+        // Verify each batched request kind is still supported
+        for data_request in data_requests {
+            let inner_request =
+                StorageServiceRequest::new(data_request.clone(), request.use_compression);
+            if let Err(error) = self
+                .deprecated_requests
+                .check_request_supported(&inner_request)
+            {
+                return Err(Error::UnsupportedRequest(error.to_string()));
+            }
+        }
+
+        // Verify the batch response won't exceed the max message size
+        let estimated_bytes =
+            estimate_response_bytes(request, &storage_server_summary.protocol_metadata);
+        if estimated_bytes > MAX_APPLICATION_MESSAGE_SIZE {
+            return Err(Error::InvalidRequest(format!(
+                "The batch response is estimated to be too large! Estimated size: {}, max size: {}",
+                estimated_bytes, MAX_APPLICATION_MESSAGE_SIZE
+            )));
+        }
+
+        Ok(())
+    }
+
     /// Refresh the unhealthy peer states and garbage collect disconnected peers
     pub fn refresh_unhealthy_peer_states(&self) -> Result<(), Error> {
         // Get the currently connected peers
@@ -9,8 +9,9 @@ use aptos_network::{
     ProtocolId,
 };
 use aptos_storage_service_types::{
-    requests::StorageServiceRequest, responses::StorageServiceResponse, Result,
-    StorageServiceMessage,
+    requests::{RequestPriority, StorageServiceRequest},
+    responses::StorageServiceResponse,
+    Result, StorageServiceMessage,
 };
 use bytes::Bytes;
 use futures::{
@@ -28,6 +29,7 @@ pub struct NetworkRequest {
     pub peer_network_id: PeerNetworkId,
     pub protocol_id: ProtocolId,
     pub storage_service_request: StorageServiceRequest,
+    pub priority: RequestPriority,
     pub response_sender: ResponseSender,
 }
 
@@ -65,18 +67,26 @@ impl StorageServiceNetworkEvents {
         event: Event<StorageServiceMessage>,
     ) -> Option<NetworkRequest> {
         match event {
-            Event::RpcRequest(
-                peer_id,
-                StorageServiceMessage::Request(storage_service_request),
-                protocol_id,
-                response_tx,
-            ) => {
+            Event::RpcRequest(peer_id, message, protocol_id, response_tx) => {
+                // Requests without an explicit priority are high-priority
+                let (storage_service_request, priority) = match message {
+                    StorageServiceMessage::Request(storage_service_request) => {
+                        (storage_service_request, RequestPriority::default())
+                    },
+                    StorageServiceMessage::PrioritizedRequest(
+                        storage_service_request,
+                        priority,
+                    ) => (storage_service_request, priority),
+                    StorageServiceMessage::Response(_) => return None, // We only handle requests
+                };
+
                 let response_sender = ResponseSender::new(response_tx);
                 let peer_network_id = PeerNetworkId::new(network_id, peer_id);
                 Some(NetworkRequest {
                     peer_network_id,
                     protocol_id,
                     storage_service_request,
+                    priority,
                     response_sender,
                 })
             },
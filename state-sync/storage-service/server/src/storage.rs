@@ -3,6 +3,7 @@
 
 use crate::{error::Error, metrics::increment_network_frame_overflow};
 use aptos_config::config::StorageServiceConfig;
+use aptos_crypto::HashValue;
 use aptos_logger::debug;
 use aptos_storage_interface::DbReader;
 use aptos_storage_service_types::responses::{
@@ -10,8 +11,11 @@ use aptos_storage_service_types::responses::{
 };
 use aptos_types::{
     epoch_change::EpochChangeProof,
+    proof::TransactionAccumulatorRangeProof,
     state_store::state_value::StateValueChunkWithProof,
-    transaction::{TransactionListWithProof, TransactionOutputListWithProof, Version},
+    transaction::{
+        TransactionListWithProof, TransactionOutputListWithProof, TransactionWithProof, Version,
+    },
 };
 use serde::Serialize;
 use std::{cmp::min, sync::Arc};
@@ -88,6 +92,26 @@ pub trait StorageReaderInterface: Clone + Send + 'static {
         start_index: u64,
         end_index: u64,
     ) -> aptos_storage_service_types::Result<StateValueChunkWithProof, Error>;
+    /// Returns the transaction accumulator range proof (relative to the
+    /// `proof_version`) for the transactions starting at `start_version`
+    /// and ending at `end_version` (inclusive). No transaction data is
+    /// returned, only the proof.
+    fn get_transaction_range_proof(
+        &self,
+        proof_version: u64,
+        start_version: u64,
+        end_version: u64,
+    ) -> aptos_storage_service_types::Result<TransactionAccumulatorRangeProof, Error>;
+
+    /// Returns the transaction with the given hash (if it exists), with a
+    /// proof relative to the `proof_version`. If `include_events` is true,
+    /// events are also returned.
+    fn get_transaction_by_hash_with_proof(
+        &self,
+        transaction_hash: HashValue,
+        proof_version: u64,
+        include_events: bool,
+    ) -> aptos_storage_service_types::Result<Option<TransactionWithProof>, Error>;
 }
 
 /// The underlying implementation of the StorageReaderInterface, used by the
@@ -261,7 +285,8 @@ impl StorageReaderInterface for StorageReader {
                 return Ok(transaction_list_with_proof);
             } else {
                 increment_network_frame_overflow(
-                    DataResponse::TransactionsWithProof(transaction_list_with_proof).get_label(),
+                    DataResponse::TransactionsWithProof(transaction_list_with_proof)
+                        .canonical_label(),
                 );
                 let new_num_transactions_to_fetch = num_transactions_to_fetch / 2;
                 debug!("The request for {:?} transactions was too large (num bytes: {:?}). Retrying with {:?}.",
@@ -314,7 +339,7 @@ impl StorageReaderInterface for StorageReader {
                 return Ok(epoch_change_proof);
             } else {
                 increment_network_frame_overflow(
-                    DataResponse::EpochEndingLedgerInfos(epoch_change_proof).get_label(),
+                    DataResponse::EpochEndingLedgerInfos(epoch_change_proof).canonical_label(),
                 );
                 let new_num_ledger_infos_to_fetch = num_ledger_infos_to_fetch / 2;
                 debug!("The request for {:?} ledger infos was too large (num bytes: {:?}). Retrying with {:?}.",
@@ -360,7 +385,8 @@ impl StorageReaderInterface for StorageReader {
                 return Ok(output_list_with_proof);
             } else {
                 increment_network_frame_overflow(
-                    DataResponse::TransactionOutputsWithProof(output_list_with_proof).get_label(),
+                    DataResponse::TransactionOutputsWithProof(output_list_with_proof)
+                        .canonical_label(),
                 );
                 let new_num_outputs_to_fetch = num_outputs_to_fetch / 2;
                 debug!("The request for {:?} outputs was too large (num bytes: {:?}). Retrying with {:?}.",
@@ -413,7 +439,7 @@ impl StorageReaderInterface for StorageReader {
                         None,
                         Some(output_list_with_proof),
                     ))
-                    .get_label(),
+                    .canonical_label(),
                 );
                 let new_num_outputs_to_fetch = num_outputs_to_fetch / 2;
                 debug!("The request for {:?} outputs was too large (num bytes: {:?}). Current number of data reductions: {:?}",
@@ -479,7 +505,7 @@ impl StorageReaderInterface for StorageReader {
             } else {
                 increment_network_frame_overflow(
                     DataResponse::StateValueChunkWithProof(state_value_chunk_with_proof)
-                        .get_label(),
+                        .canonical_label(),
                 );
                 let new_num_state_values_to_fetch = num_state_values_to_fetch / 2;
                 debug!("The request for {:?} state values was too large (num bytes: {:?}). Retrying with {:?}.",
@@ -494,6 +520,33 @@ impl StorageReaderInterface for StorageReader {
             version, start_index, end_index
         )))
     }
+
+    fn get_transaction_range_proof(
+        &self,
+        proof_version: u64,
+        start_version: u64,
+        end_version: u64,
+    ) -> aptos_storage_service_types::Result<TransactionAccumulatorRangeProof, Error> {
+        // Calculate the number of transactions covered by the proof
+        let num_transactions = inclusive_range_len(start_version, end_version)?;
+
+        // Fetch the range proof (the proof size is logarithmic in the ledger
+        // size, so we don't need to worry about overflowing the network frame).
+        self.storage
+            .get_transaction_accumulator_range_proof(start_version, num_transactions, proof_version)
+            .map_err(|error| Error::StorageErrorEncountered(error.to_string()))
+    }
+
+    fn get_transaction_by_hash_with_proof(
+        &self,
+        transaction_hash: HashValue,
+        proof_version: u64,
+        include_events: bool,
+    ) -> aptos_storage_service_types::Result<Option<TransactionWithProof>, Error> {
+        self.storage
+            .get_transaction_by_hash(transaction_hash, proof_version, include_events)
+            .map_err(|error| Error::StorageErrorEncountered(error.to_string()))
+    }
 }
 
 /// Calculate `(start..=end).len()`. Returns an error if `end < start` or
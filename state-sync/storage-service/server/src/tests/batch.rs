@@ -0,0 +1,189 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::tests::{mock, mock::MockClient, utils};
+use aptos_config::config::StorageServiceConfig;
+use aptos_storage_service_types::{
+    requests::{
+        DataRequest, NewTransactionsWithProofRequest, StateValuesWithProofRequest,
+        TransactionsWithProofRequest,
+    },
+    responses::{DataResponse, ServerProtocolVersion, StorageServiceResponse},
+    StorageServiceError,
+};
+use claims::assert_matches;
+use mockall::predicate::eq;
+
+#[tokio::test]
+async fn test_get_batch() {
+    // Create test data
+    let version = 101;
+    let number_of_states: u64 = 560;
+
+    // Create the mock db reader
+    let mut db_reader = mock::create_mock_db_reader();
+    db_reader
+        .expect_get_state_leaf_count()
+        .times(2)
+        .with(eq(version))
+        .returning(move |_| Ok(number_of_states as usize));
+
+    // Create the storage client and server
+    let (mut mock_client, mut service, _, _, _) = MockClient::new(Some(db_reader), None);
+    utils::update_storage_server_summary(&mut service, version, 10);
+    tokio::spawn(service.start());
+
+    // Process a batch request (with and without compression)
+    for use_compression in [true, false] {
+        let data_requests = vec![
+            DataRequest::GetServerProtocolVersion,
+            DataRequest::GetNumberOfStatesAtVersion(version),
+        ];
+        let response = get_batch(&mut mock_client, data_requests, use_compression)
+            .await
+            .unwrap();
+
+        // Verify the responses are returned in order (and respect the compression flag)
+        assert_eq!(response.is_compressed(), use_compression);
+        let data_responses: Vec<DataResponse> = response.try_into().unwrap();
+        assert_eq!(data_responses, vec![
+            DataResponse::ServerProtocolVersion(ServerProtocolVersion {
+                protocol_version: 1,
+            }),
+            DataResponse::NumberOfStatesAtVersion(number_of_states),
+        ]);
+    }
+}
+
+#[tokio::test]
+async fn test_get_batch_invalid() {
+    // Create test data
+    let version = 101;
+
+    // Create the storage client and server
+    let (mut mock_client, mut service, _, _, _) = MockClient::new(None, None);
+    utils::update_storage_server_summary(&mut service, version, 10);
+    tokio::spawn(service.start());
+
+    // Create the invalid batches (i.e., with nested batches or optimistic fetches)
+    let nested_batch = DataRequest::GetBatch(vec![DataRequest::GetServerProtocolVersion]);
+    let optimistic_fetch =
+        DataRequest::GetNewTransactionsWithProof(NewTransactionsWithProofRequest {
+            known_version: version,
+            known_epoch: 10,
+            include_events: false,
+        });
+    for invalid_request in [nested_batch, optimistic_fetch] {
+        // Process the batch request
+        let data_requests = vec![DataRequest::GetServerProtocolVersion, invalid_request];
+        let error = get_batch(&mut mock_client, data_requests, false)
+            .await
+            .unwrap_err();
+
+        // Verify the request is rejected
+        assert_matches!(error, StorageServiceError::InvalidRequest(_));
+    }
+}
+
+#[tokio::test]
+async fn test_get_batch_deprecated_request() {
+    // Create test data
+    let version = 101;
+
+    // Create a storage service config that deprecates the pre-fusion requests
+    let storage_service_config = StorageServiceConfig {
+        deprecate_pre_fusion_requests: true,
+        ..Default::default()
+    };
+
+    // Create the storage client and server
+    let (mut mock_client, mut service, _, _, _) =
+        MockClient::new(None, Some(storage_service_config));
+    utils::update_storage_server_summary(&mut service, version, 10);
+    tokio::spawn(service.start());
+
+    // Process a batch request that wraps a deprecated request
+    let data_requests = vec![
+        DataRequest::GetServerProtocolVersion,
+        DataRequest::GetTransactionsWithProof(TransactionsWithProofRequest {
+            proof_version: version,
+            start_version: 0,
+            end_version: version,
+            include_events: false,
+        }),
+    ];
+    let error = get_batch(&mut mock_client, data_requests, false)
+        .await
+        .unwrap_err();
+
+    // Verify the request is rejected as unsupported
+    assert_matches!(error, StorageServiceError::UnsupportedRequest(_));
+}
+
+#[tokio::test]
+async fn test_get_batch_too_long() {
+    // Create test data
+    let version = 101;
+    let max_batch_request_size = 5;
+
+    // Create the storage client and server
+    let storage_service_config = StorageServiceConfig {
+        max_batch_request_size,
+        ..Default::default()
+    };
+    let (mut mock_client, mut service, _, _, _) =
+        MockClient::new(None, Some(storage_service_config));
+    utils::update_storage_server_summary(&mut service, version, 10);
+    tokio::spawn(service.start());
+
+    // Process a batch request that contains too many requests
+    let data_requests =
+        vec![DataRequest::GetServerProtocolVersion; max_batch_request_size as usize + 1];
+    let error = get_batch(&mut mock_client, data_requests, false)
+        .await
+        .unwrap_err();
+
+    // Verify the request is rejected
+    assert_matches!(error, StorageServiceError::InvalidRequest(_));
+}
+
+#[tokio::test]
+async fn test_get_batch_too_large() {
+    // Create test data
+    let version = 101;
+    let max_state_chunk_size = StorageServiceConfig::default().max_state_chunk_size;
+
+    // Create the storage client and server (the mock db expects no calls)
+    let storage_service_config = StorageServiceConfig {
+        max_batch_request_size: 100,
+        ..Default::default()
+    };
+    let (mut mock_client, mut service, _, _, _) =
+        MockClient::new(None, Some(storage_service_config));
+    utils::update_storage_server_summary(&mut service, version, 10);
+    tokio::spawn(service.start());
+
+    // Process a batch request whose response is estimated to exceed the max message size
+    let state_values_request = DataRequest::GetStateValuesWithProof(StateValuesWithProofRequest {
+        version,
+        start_index: 0,
+        end_index: max_state_chunk_size - 1,
+    });
+    let data_requests = vec![state_values_request; 20];
+    let error = get_batch(&mut mock_client, data_requests, false)
+        .await
+        .unwrap_err();
+
+    // Verify the request is rejected before storage is read
+    assert_matches!(error, StorageServiceError::InvalidRequest(_));
+}
+
+/// Sends a batch request and processes the response
+async fn get_batch(
+    mock_client: &mut MockClient,
+    data_requests: Vec<DataRequest>,
+    use_compression: bool,
+) -> Result<StorageServiceResponse, StorageServiceError> {
+    let data_request = DataRequest::GetBatch(data_requests);
+    utils::send_storage_request(mock_client, use_compression, data_request).await
+}
@@ -1,17 +1,29 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::tests::{mock, mock::MockClient, utils};
-use aptos_config::config::StorageServiceConfig;
+use crate::{
+    handler::Handler,
+    moderator::RequestModerator,
+    storage::StorageReader,
+    tests::{mock, mock::MockClient, utils},
+};
+use aptos_config::{config::StorageServiceConfig, network_id::PeerNetworkId};
 use aptos_crypto::hash::HashValue;
-use aptos_storage_service_types::responses::{DataResponse, StorageServiceResponse};
+use aptos_infallible::{Mutex, RwLock};
+use aptos_storage_service_types::{
+    requests::{DataRequest, StorageServiceRequest, TransactionsWithProofRequest},
+    responses::{DataResponse, StorageServerSummary, StorageServiceResponse},
+};
+use aptos_time_service::TimeService;
 use aptos_types::{
     proof::definition::SparseMerkleRangeProof, state_store::state_value::StateValueChunkWithProof,
 };
+use lru::LruCache;
 use mockall::{
     predicate::{always, eq},
     Sequence,
 };
+use std::{collections::HashMap, sync::Arc};
 
 #[tokio::test]
 async fn test_cachable_requests_compression() {
@@ -221,3 +233,83 @@ async fn test_cachable_requests_eviction() {
         utils::get_state_values_with_proof(&mut mock_client, version, start_index, end_index, true)
             .await;
 }
+
+#[tokio::test]
+async fn test_cachable_requests_tagged() {
+    // Create test data
+    let start_version = 0;
+    let end_version = 454;
+    let proof_version = end_version;
+    let include_events = false;
+    let transaction_list_with_proof = utils::create_transaction_list_with_proof(
+        start_version,
+        end_version,
+        proof_version,
+        include_events,
+    );
+
+    // Create the mock db reader (the data should only be fetched once)
+    let mut db_reader = mock::create_mock_db_reader();
+    let transaction_list_with_proof_clone = transaction_list_with_proof.clone();
+    db_reader
+        .expect_get_transactions()
+        .times(1)
+        .with(
+            eq(start_version),
+            eq(end_version - start_version + 1),
+            eq(proof_version),
+            eq(include_events),
+        )
+        .return_once(move |_, _, _, _| Ok(transaction_list_with_proof_clone));
+
+    // Create the request handler
+    let storage_service_config = StorageServiceConfig::default();
+    let time_service = TimeService::mock();
+    let cached_storage_server_summary = Arc::new(RwLock::new(StorageServerSummary::default()));
+    let handler = Handler::new(
+        cached_storage_server_summary.clone(),
+        Arc::new(Mutex::new(HashMap::new())),
+        Arc::new(Mutex::new(LruCache::new(
+            storage_service_config.max_lru_cache_size as usize,
+        ))),
+        Arc::new(RequestModerator::new(
+            cached_storage_server_summary,
+            mock::create_peers_and_metadata(vec![]),
+            storage_service_config,
+            time_service.clone(),
+        )),
+        StorageReader::new(storage_service_config, Arc::new(db_reader)),
+        time_service,
+    );
+
+    // Create the request
+    let data_request = DataRequest::GetTransactionsWithProof(TransactionsWithProofRequest {
+        proof_version,
+        start_version,
+        end_version,
+        include_events,
+    });
+    let request = StorageServiceRequest::new(data_request, true);
+
+    // Process the request and verify the response is freshly computed
+    let peer_network_id = PeerNetworkId::random();
+    let fresh_response = handler
+        .process_cachable_request(&peer_network_id, &request)
+        .unwrap();
+    assert!(!fresh_response.cached);
+
+    // Process the request again and verify the response is served from the cache
+    let cached_response = handler
+        .process_cachable_request(&peer_network_id, &request)
+        .unwrap();
+    assert!(cached_response.cached);
+
+    // Verify the tag doesn't affect the responses themselves
+    assert_eq!(cached_response.response, fresh_response.response);
+    match cached_response.response.get_data_response().unwrap() {
+        DataResponse::TransactionsWithProof(response) => {
+            assert_eq!(response, transaction_list_with_proof);
+        },
+        response => panic!("Expected transactions with proof but got: {:?}", response),
+    };
+}
@@ -6,8 +6,11 @@ use aptos_bitvec::BitVec;
 use aptos_config::config::StorageServiceConfig;
 use aptos_crypto::HashValue;
 use aptos_storage_service_types::{
-    requests::{DataRequest, EpochEndingLedgerInfoRequest, StorageServiceRequest},
-    responses::DataResponse,
+    requests::{
+        DataRequest, EpochChangeProofBetweenRequest, EpochEndingLedgerInfoRequest,
+        StorageServiceRequest,
+    },
+    responses::{DataResponse, MinimalEpochChangeProof},
     Epoch, StorageServiceError,
 };
 use aptos_types::{
@@ -180,6 +183,77 @@ async fn test_get_epoch_ending_ledger_infos_not_serviceable() {
     }
 }
 
+#[tokio::test]
+async fn test_get_epoch_change_proof_between() {
+    // Create test data
+    let start_epoch = 11;
+    let end_epoch = 20;
+    let epoch_change_proof = EpochChangeProof {
+        ledger_info_with_sigs: create_epoch_ending_ledger_infos(start_epoch, end_epoch),
+        more: false,
+    };
+
+    // Create the mock db reader (the proof ends at epoch: `end_epoch - 1`)
+    let mut db_reader = mock::create_mock_db_reader();
+    utils::expect_get_epoch_ending_ledger_infos(
+        &mut db_reader,
+        start_epoch,
+        end_epoch,
+        epoch_change_proof.clone(),
+    );
+
+    // Create the storage client and server
+    let (mut mock_client, mut service, _, _, _) = MockClient::new(Some(db_reader), None);
+    utils::update_storage_server_summary(&mut service, 1000, end_epoch);
+    tokio::spawn(service.start());
+
+    // Create a request to fetch the epoch change proof
+    let data_request = DataRequest::GetEpochChangeProofBetween(EpochChangeProofBetweenRequest {
+        start_epoch,
+        end_epoch,
+    });
+    let storage_request = StorageServiceRequest::new(data_request, true);
+
+    // Process the request
+    let response = mock_client.process_request(storage_request).await.unwrap();
+
+    // Verify the response is correct
+    match response.get_data_response().unwrap() {
+        DataResponse::EpochChangeProofBetween(MinimalEpochChangeProof(
+            response_epoch_change_proof,
+        )) => {
+            assert_eq!(response_epoch_change_proof, epoch_change_proof)
+        },
+        _ => panic!("Expected an epoch change proof but got: {:?}", response),
+    };
+}
+
+#[tokio::test]
+async fn test_get_epoch_change_proof_between_invalid() {
+    // Create the storage client and server
+    let (mut mock_client, mut service, _, _, _) = MockClient::new(None, None);
+    utils::update_storage_server_summary(&mut service, 1000, 100);
+    tokio::spawn(service.start());
+
+    // Test invalid ranges (i.e., the end epoch doesn't exceed the start epoch)
+    let start_epoch = 11;
+    for end_epoch in [0, 10, 11] {
+        let data_request =
+            DataRequest::GetEpochChangeProofBetween(EpochChangeProofBetweenRequest {
+                start_epoch,
+                end_epoch,
+            });
+        let storage_request = StorageServiceRequest::new(data_request, true);
+
+        // Process and verify the response
+        let response = mock_client
+            .process_request(storage_request)
+            .await
+            .unwrap_err();
+        assert_matches!(response, StorageServiceError::InvalidRequest(_));
+    }
+}
+
 /// Creates a test epoch change proof
 fn create_epoch_ending_ledger_infos(
     start_epoch: Epoch,
@@ -21,8 +21,9 @@ use aptos_network::{
 use aptos_storage_interface::{DbReader, ExecutedTrees, Order};
 use aptos_storage_service_notifications::StorageServiceNotifier;
 use aptos_storage_service_types::{
-    requests::StorageServiceRequest, responses::StorageServiceResponse, StorageServiceError,
-    StorageServiceMessage,
+    requests::{RequestPriority, StorageServiceRequest},
+    responses::StorageServiceResponse,
+    StorageServiceError, StorageServiceMessage,
 };
 use aptos_time_service::{MockTimeService, TimeService};
 use aptos_types::{
@@ -142,6 +143,17 @@ impl MockClient {
         self.wait_for_response(receiver).await
     }
 
+    /// Send the given storage request (with the given priority) and wait for a response
+    pub async fn process_request_with_priority(
+        &mut self,
+        request: StorageServiceRequest,
+        priority: RequestPriority,
+    ) -> Result<StorageServiceResponse, StorageServiceError> {
+        let message = StorageServiceMessage::PrioritizedRequest(request, priority);
+        let receiver = self.send_message(message, None, None).await;
+        self.wait_for_response(receiver).await
+    }
+
     /// Send the specified storage request and return the receiver on which to
     /// expect a result.
     pub async fn send_request(
@@ -149,14 +161,24 @@ impl MockClient {
         request: StorageServiceRequest,
         peer_id: Option<AccountAddress>,
         network_id: Option<NetworkId>,
+    ) -> Receiver<Result<bytes::Bytes, aptos_network::protocols::network::RpcError>> {
+        let message = StorageServiceMessage::Request(request);
+        self.send_message(message, peer_id, network_id).await
+    }
+
+    /// Send the specified storage message and return the receiver on which to
+    /// expect a result.
+    async fn send_message(
+        &mut self,
+        message: StorageServiceMessage,
+        peer_id: Option<AccountAddress>,
+        network_id: Option<NetworkId>,
     ) -> Receiver<Result<bytes::Bytes, aptos_network::protocols::network::RpcError>> {
         // Create the inbound rpc request
         let peer_id = peer_id.unwrap_or_else(PeerId::random);
         let network_id = network_id.unwrap_or_else(get_random_network_id);
         let protocol_id = ProtocolId::StorageServiceRpc;
-        let data = protocol_id
-            .to_bytes(&StorageServiceMessage::Request(request))
-            .unwrap();
+        let data = protocol_id.to_bytes(&message).unwrap();
         let (res_tx, res_rx) = oneshot::channel();
         let inbound_rpc = InboundRpcRequest {
             protocol_id,
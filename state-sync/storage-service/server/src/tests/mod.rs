@@ -1,6 +1,7 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
+mod batch;
 mod cache;
 mod epoch_ending;
 mod mock;
@@ -13,6 +14,7 @@ mod protocol_version;
 mod request_moderator;
 mod state_values;
 mod storage_summary;
+mod transaction_by_hash;
 mod transaction_outputs;
 mod transactions;
 mod transactions_or_outputs;
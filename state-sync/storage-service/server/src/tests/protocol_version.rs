@@ -6,7 +6,6 @@ use aptos_storage_service_types::{
     requests::DataRequest,
     responses::{DataResponse, ServerProtocolVersion, StorageServiceResponse},
 };
-use claims::assert_matches;
 
 // Useful test constants
 const PROTOCOL_VERSION: u64 = 1;
@@ -17,18 +16,20 @@ async fn test_get_server_protocol_version() {
     let (mut mock_client, service, _, _, _) = MockClient::new(None, None);
     tokio::spawn(service.start());
 
-    // Process a request to fetch the protocol version
-    let response = get_protocol_version(&mut mock_client, true).await;
+    // Process a request to fetch the protocol version (with and without compression)
+    for use_compression in [true, false] {
+        let response = get_protocol_version(&mut mock_client, use_compression).await;
 
-    // Verify the response is correct
-    let expected_data_response = DataResponse::ServerProtocolVersion(ServerProtocolVersion {
-        protocol_version: PROTOCOL_VERSION,
-    });
-    assert_matches!(response, StorageServiceResponse::CompressedResponse(_, _));
-    assert_eq!(
-        response.get_data_response().unwrap(),
-        expected_data_response
-    );
+        // Verify the response is correct (and respects the compression flag)
+        let expected_data_response = DataResponse::ServerProtocolVersion(ServerProtocolVersion {
+            protocol_version: PROTOCOL_VERSION,
+        });
+        assert_eq!(response.is_compressed(), use_compression);
+        assert_eq!(
+            response.get_data_response().unwrap(),
+            expected_data_response
+        );
+    }
 }
 
 /// Sends a protocol version request and processes the response
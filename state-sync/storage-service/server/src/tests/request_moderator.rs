@@ -17,13 +17,13 @@ use aptos_network::{
     transport::{ConnectionId, ConnectionMetadata},
 };
 use aptos_storage_service_types::{
-    requests::{DataRequest, StorageServiceRequest, TransactionsWithProofRequest},
+    requests::{DataRequest, RequestPriority, StorageServiceRequest, TransactionsWithProofRequest},
     responses::StorageServiceResponse,
     StorageServiceError,
 };
 use aptos_time_service::MockTimeService;
 use aptos_types::{account_address::AccountAddress, network_address::NetworkAddress, PeerId};
-use claims::assert_matches;
+use claims::{assert_matches, assert_ok};
 use std::{collections::HashMap, future::Future, str::FromStr, sync::Arc, time::Duration};
 use tokio::time::timeout;
 
@@ -372,6 +372,109 @@ async fn test_request_moderator_peer_garbage_collect() {
     assert_eq!(unhealthy_peer_states.read().len(), 1);
 }
 
+#[tokio::test]
+async fn test_request_moderator_sheds_low_priority_requests() {
+    // Create a storage service config with a low shedding threshold
+    let max_in_flight_requests_before_shedding = 5;
+    let storage_service_config = StorageServiceConfig {
+        max_in_flight_requests_before_shedding,
+        ..Default::default()
+    };
+
+    // Create the storage client and server
+    let (mut mock_client, service, _, _, _) = MockClient::new(None, Some(storage_service_config));
+    let request_moderator = service.get_request_moderator();
+    tokio::spawn(service.start());
+
+    // Verify that requests of all priorities are processed without load
+    for priority in [RequestPriority::High, RequestPriority::Low] {
+        let response = mock_client
+            .process_request_with_priority(create_protocol_version_request(), priority)
+            .await;
+        assert_ok!(response);
+    }
+
+    // Simulate load by holding the maximum number of in-flight requests
+    let in_flight_requests: Vec<_> = (0..max_in_flight_requests_before_shedding)
+        .map(|_| request_moderator.start_request())
+        .collect();
+    assert_eq!(
+        request_moderator.get_num_in_flight_requests(),
+        max_in_flight_requests_before_shedding
+    );
+
+    // Verify that low-priority requests are shed under load
+    let response = mock_client
+        .process_request_with_priority(create_protocol_version_request(), RequestPriority::Low)
+        .await;
+    assert_matches!(response.unwrap_err(), StorageServiceError::Overloaded(_));
+
+    // Verify that high-priority requests are still processed under load
+    let response = mock_client
+        .process_request_with_priority(create_protocol_version_request(), RequestPriority::High)
+        .await;
+    assert_ok!(response);
+
+    // Remove the load and verify that low-priority requests are processed again
+    drop(in_flight_requests);
+    assert_eq!(request_moderator.get_num_in_flight_requests(), 0);
+    let response = mock_client
+        .process_request_with_priority(create_protocol_version_request(), RequestPriority::Low)
+        .await;
+    assert_ok!(response);
+}
+
+#[tokio::test]
+async fn test_request_moderator_rejects_deprecated_requests() {
+    // Create test data
+    let highest_synced_version = 100;
+    let highest_synced_epoch = 10;
+
+    // Create a storage service config that deprecates the pre-fusion requests
+    let max_invalid_requests_per_peer = 5;
+    let storage_service_config = StorageServiceConfig {
+        deprecate_pre_fusion_requests: true,
+        max_invalid_requests_per_peer,
+        ..Default::default()
+    };
+
+    // Create the storage client and server
+    let (mut mock_client, mut service, _, _, _) =
+        MockClient::new(None, Some(storage_service_config));
+    utils::update_storage_server_summary(
+        &mut service,
+        highest_synced_version,
+        highest_synced_epoch,
+    );
+    let request_moderator = service.get_request_moderator();
+    let unhealthy_peer_states = request_moderator.get_unhealthy_peer_states();
+    tokio::spawn(service.start());
+
+    // Send many deprecated requests and verify we get an unsupported request error
+    let peer_network_id = PeerNetworkId::new(NetworkId::Public, PeerId::random());
+    for _ in 0..max_invalid_requests_per_peer * 2 {
+        let response = send_invalid_transaction_request(
+            highest_synced_version,
+            &mut mock_client,
+            peer_network_id,
+        )
+        .await;
+        assert_matches!(
+            response.unwrap_err(),
+            StorageServiceError::UnsupportedRequest(_)
+        );
+    }
+
+    // Verify the peer was never penalized for the deprecated requests
+    assert!(unhealthy_peer_states.read().is_empty());
+
+    // Verify that other request kinds are still processed
+    let response = mock_client
+        .process_request(create_protocol_version_request())
+        .await;
+    assert_ok!(response);
+}
+
 /// Advances the given timer by the amount of time it takes to refresh the moderator
 async fn advance_moderator_refresh_time(mock_time: &MockTimeService) {
     let default_storage_config = StorageServiceConfig::default();
@@ -395,6 +498,11 @@ fn create_connection_metadata(peer_id: AccountAddress, connection_id: u32) -> Co
     )
 }
 
+/// Creates a protocol version request
+fn create_protocol_version_request() -> StorageServiceRequest {
+    StorageServiceRequest::new(DataRequest::GetServerProtocolVersion, true)
+}
+
 /// Sends a request to get a transaction list with proof at an invalid version
 async fn send_invalid_transaction_request(
     highest_synced_version: u64,
@@ -260,6 +260,48 @@ async fn test_get_storage_server_summary_notification() {
     }
 }
 
+#[tokio::test]
+async fn test_get_latest_ledger_info() {
+    // Create test data
+    let highest_version = 506;
+    let highest_epoch = 30;
+    let highest_ledger_info =
+        utils::create_test_ledger_info_with_sigs(highest_epoch, highest_version);
+
+    // Create the mock db reader
+    let db_reader = create_db_reader_with_expectations(10, 50, highest_ledger_info.clone());
+
+    // Create the storage client and server
+    let (mut mock_client, service, _, mock_time, _) = MockClient::new(Some(db_reader), None);
+    tokio::spawn(service.start());
+
+    // Fetch the latest ledger info and verify the request is rejected
+    // (the storage summary cache does not yet contain a synced ledger info).
+    let response = get_latest_ledger_info(&mut mock_client, true).await;
+    assert!(matches!(
+        response,
+        Err(StorageServiceError::InvalidRequest(_))
+    ));
+
+    // Elapse enough time to force a cache update
+    utils::advance_storage_refresh_time(&mock_time).await;
+
+    // Fetch the latest ledger info and verify the response is correct
+    for use_compression in [true, false] {
+        let response = get_latest_ledger_info(&mut mock_client, use_compression)
+            .await
+            .unwrap();
+        assert_eq!(
+            response,
+            StorageServiceResponse::new(
+                DataResponse::LatestLedgerInfo(highest_ledger_info.clone()),
+                use_compression,
+            )
+            .unwrap()
+        );
+    }
+}
+
 /// Creates a mock database reader with the necessary
 /// expectations to satisfy the storage server summary request.
 fn create_db_reader_with_expectations(
@@ -289,6 +331,15 @@ fn create_db_reader_with_expectations(
     db_reader
 }
 
+/// Sends a latest ledger info request and processes the response
+async fn get_latest_ledger_info(
+    mock_client: &mut MockClient,
+    use_compression: bool,
+) -> Result<StorageServiceResponse, StorageServiceError> {
+    let data_request = DataRequest::GetLatestLedgerInfo;
+    utils::send_storage_request(mock_client, use_compression, data_request).await
+}
+
 /// Sends a storage summary request and processes the response
 async fn get_storage_server_summary(
     mock_client: &mut MockClient,
@@ -0,0 +1,103 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::tests::{mock, mock::MockClient, utils};
+use aptos_crypto::HashValue;
+use aptos_storage_service_types::{
+    requests::{DataRequest, StorageServiceRequest, TransactionByHashWithProofRequest},
+    responses::{DataResponse, StorageServiceResponse},
+    StorageServiceError,
+};
+use aptos_types::{
+    proof::{TransactionAccumulatorProof, TransactionInfoWithProof},
+    transaction::{ExecutionStatus, Transaction, TransactionInfo, TransactionWithProof},
+};
+use mockall::predicate::eq;
+
+#[tokio::test]
+async fn test_get_transaction_by_hash_with_proof() {
+    // Create test data
+    let transaction_hash = HashValue::random();
+    let proof_version = 1000;
+    let transaction_version = 500;
+
+    for (start_version, end_version, expect_transaction) in [
+        (400, 600, true),                      // The transaction is within the range
+        (500, 500, true),                      // The range only contains the transaction
+        (0, transaction_version - 1, false),   // The transaction is after the range
+        (transaction_version + 1, 900, false), // The transaction is before the range
+    ] {
+        // Create the mock db reader
+        let transaction_with_proof = create_transaction_with_proof(transaction_version);
+        let mut db_reader = mock::create_mock_db_reader();
+        let storage_result = transaction_with_proof.clone();
+        db_reader
+            .expect_get_transaction_by_hash()
+            .times(1)
+            .with(eq(transaction_hash), eq(proof_version), eq(false))
+            .returning(move |_, _, _| Ok(Some(storage_result.clone())));
+
+        // Create the storage client and server
+        let (mut mock_client, mut service, _, _, _) = MockClient::new(Some(db_reader), None);
+        utils::update_storage_server_summary(&mut service, proof_version, 10);
+        tokio::spawn(service.start());
+
+        // Process a request to fetch the transaction by hash
+        let response = get_transaction_by_hash_with_proof(
+            &mut mock_client,
+            transaction_hash,
+            proof_version,
+            start_version,
+            end_version,
+        )
+        .await
+        .unwrap();
+
+        // Verify the transaction is only returned if it is within the range
+        let expected_transaction = expect_transaction.then_some(transaction_with_proof);
+        assert_eq!(
+            response.get_data_response().unwrap(),
+            DataResponse::TransactionByHashWithProof(expected_transaction)
+        );
+    }
+}
+
+/// Creates a test transaction with proof at the given version
+fn create_transaction_with_proof(version: u64) -> TransactionWithProof {
+    let transaction_info = TransactionInfo::new(
+        HashValue::random(),
+        HashValue::random(),
+        HashValue::random(),
+        None,
+        0,
+        ExecutionStatus::Success,
+    );
+    let proof =
+        TransactionInfoWithProof::new(TransactionAccumulatorProof::new(vec![]), transaction_info);
+    TransactionWithProof::new(
+        version,
+        Transaction::StateCheckpoint(HashValue::random()),
+        None,
+        proof,
+    )
+}
+
+/// Sends a transaction by hash request and processes the response
+async fn get_transaction_by_hash_with_proof(
+    mock_client: &mut MockClient,
+    transaction_hash: HashValue,
+    proof_version: u64,
+    start_version: u64,
+    end_version: u64,
+) -> Result<StorageServiceResponse, StorageServiceError> {
+    let data_request =
+        DataRequest::GetTransactionByHashWithProof(TransactionByHashWithProofRequest {
+            transaction_hash,
+            proof_version,
+            start_version,
+            end_version,
+            include_events: false,
+        });
+    let storage_request = StorageServiceRequest::new(data_request, true);
+    mock_client.process_request(storage_request).await
+}
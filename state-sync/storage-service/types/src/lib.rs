@@ -4,11 +4,12 @@
 
 #![forbid(unsafe_code)]
 
-use requests::StorageServiceRequest;
+use requests::{RequestPriority, StorageServiceRequest};
 use responses::StorageServiceResponse;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
+pub mod proto;
 pub mod requests;
 pub mod responses;
 
@@ -19,6 +20,10 @@ mod tests;
 /// (if the request/response requires compression).
 const COMPRESSION_SUFFIX_LABEL: &str = "_compressed";
 
+/// The suffix to append to compressed response labels (instead of
+/// `COMPRESSION_SUFFIX_LABEL`) if the compressed data carries a checksum.
+const CHECKSUMMED_COMPRESSION_SUFFIX_LABEL: &str = "_compressed_checksummed";
+
 /// A type alias for different epochs.
 pub type Epoch = u64;
 
@@ -35,6 +40,10 @@ pub enum StorageServiceError {
     InvalidRequest(String),
     #[error("Too many invalid requests! Back off required: {0}")]
     TooManyInvalidRequests(String),
+    #[error("Unsupported storage request: {0}")]
+    UnsupportedRequest(String),
+    #[error("Server overloaded! Back off required: {0}")]
+    Overloaded(String),
 }
 
 /// A single storage service message sent or received over AptosNet.
@@ -46,4 +55,9 @@ pub enum StorageServiceMessage {
     /// A response from the storage service. If there was an error while handling
     /// the request, the service will return an [`StorageServiceError`] error.
     Response(Result<StorageServiceResponse>),
+    /// A request to the storage service with an explicit priority. The priority
+    /// is kept out of the request itself, so that the wire format of existing
+    /// requests (and the server's response cache) is unaffected. Note: older
+    /// servers are unable to decode this message.
+    PrioritizedRequest(StorageServiceRequest, RequestPriority),
 }
@@ -0,0 +1,166 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Protobuf representations of the storage server summary. These are used to
+//! export summaries to systems that don't speak BCS (e.g., external monitoring).
+//! The conversions are lossless, i.e., `from_proto(to_proto(x)) == x`.
+
+use crate::responses::{
+    CompleteDataRange, DataSummary, Error, ProtocolMetadata, StorageServerSummary,
+    UncheckedDataSummary,
+};
+use aptos_types::ledger_info::LedgerInfoWithSignatures;
+use prost::Message;
+
+/// The protobuf representation of a `CompleteDataRange`
+#[derive(Clone, Eq, Message, PartialEq)]
+pub struct CompleteDataRangeProto {
+    #[prost(uint64, tag = "1")]
+    pub lowest: u64,
+    #[prost(uint64, tag = "2")]
+    pub highest: u64,
+}
+
+/// The protobuf representation of a `ProtocolMetadata`
+#[derive(Clone, Eq, Message, PartialEq)]
+pub struct ProtocolMetadataProto {
+    #[prost(uint64, tag = "1")]
+    pub max_epoch_chunk_size: u64,
+    #[prost(uint64, tag = "2")]
+    pub max_state_chunk_size: u64,
+    #[prost(uint64, tag = "3")]
+    pub max_transaction_chunk_size: u64,
+    #[prost(uint64, tag = "4")]
+    pub max_transaction_output_chunk_size: u64,
+}
+
+/// The protobuf representation of a `DataSummary`. The synced ledger info is
+/// carried as opaque BCS bytes (it contains signatures and is only meaningful
+/// to Aptos nodes).
+#[derive(Clone, Eq, Message, PartialEq)]
+pub struct DataSummaryProto {
+    #[prost(bytes = "vec", optional, tag = "1")]
+    pub synced_ledger_info: Option<Vec<u8>>,
+    #[prost(message, optional, tag = "2")]
+    pub epoch_ending_ledger_infos: Option<CompleteDataRangeProto>,
+    #[prost(message, optional, tag = "3")]
+    pub states: Option<CompleteDataRangeProto>,
+    #[prost(message, optional, tag = "4")]
+    pub transactions: Option<CompleteDataRangeProto>,
+    #[prost(message, optional, tag = "5")]
+    pub transaction_outputs: Option<CompleteDataRangeProto>,
+}
+
+/// The protobuf representation of a `StorageServerSummary`
+#[derive(Clone, Eq, Message, PartialEq)]
+pub struct StorageServerSummaryProto {
+    #[prost(message, optional, tag = "1")]
+    pub protocol_metadata: Option<ProtocolMetadataProto>,
+    #[prost(message, optional, tag = "2")]
+    pub data_summary: Option<DataSummaryProto>,
+}
+
+impl CompleteDataRange<u64> {
+    /// Converts the data range into its protobuf representation
+    pub fn to_proto(&self) -> CompleteDataRangeProto {
+        CompleteDataRangeProto {
+            lowest: self.lowest(),
+            highest: self.highest(),
+        }
+    }
+
+    /// Converts the protobuf representation into a data range. Returns an
+    /// error if the range is degenerate.
+    pub fn from_proto(proto: CompleteDataRangeProto) -> crate::Result<Self, Error> {
+        CompleteDataRange::new(proto.lowest, proto.highest)
+    }
+}
+
+impl ProtocolMetadata {
+    /// Converts the protocol metadata into its protobuf representation
+    pub fn to_proto(&self) -> ProtocolMetadataProto {
+        ProtocolMetadataProto {
+            max_epoch_chunk_size: self.max_epoch_chunk_size,
+            max_state_chunk_size: self.max_state_chunk_size,
+            max_transaction_chunk_size: self.max_transaction_chunk_size,
+            max_transaction_output_chunk_size: self.max_transaction_output_chunk_size,
+        }
+    }
+
+    /// Converts the protobuf representation into protocol metadata
+    pub fn from_proto(proto: ProtocolMetadataProto) -> crate::Result<Self, Error> {
+        Ok(ProtocolMetadata {
+            max_epoch_chunk_size: proto.max_epoch_chunk_size,
+            max_state_chunk_size: proto.max_state_chunk_size,
+            max_transaction_chunk_size: proto.max_transaction_chunk_size,
+            max_transaction_output_chunk_size: proto.max_transaction_output_chunk_size,
+        })
+    }
+}
+
+impl DataSummary {
+    /// Converts the data summary into its protobuf representation
+    pub fn to_proto(&self) -> crate::Result<DataSummaryProto, Error> {
+        let synced_ledger_info = self
+            .synced_ledger_info
+            .as_ref()
+            .map(bcs::to_bytes)
+            .transpose()
+            .map_err(|error| Error::UnexpectedErrorEncountered(error.to_string()))?;
+        Ok(DataSummaryProto {
+            synced_ledger_info,
+            epoch_ending_ledger_infos: self.epoch_ending_ledger_infos.map(|range| range.to_proto()),
+            states: self.states.map(|range| range.to_proto()),
+            transactions: self.transactions.map(|range| range.to_proto()),
+            transaction_outputs: self.transaction_outputs.map(|range| range.to_proto()),
+        })
+    }
+
+    /// Converts the protobuf representation into a data summary. Returns an
+    /// error if any of the data ranges are degenerate, if the synced ledger
+    /// info cannot be deserialized, or if the summary is inconsistent (i.e.,
+    /// it fails the same validation as a BCS encoded summary).
+    pub fn from_proto(proto: DataSummaryProto) -> crate::Result<Self, Error> {
+        let synced_ledger_info = proto
+            .synced_ledger_info
+            .map(|bytes| bcs::from_bytes::<LedgerInfoWithSignatures>(&bytes))
+            .transpose()
+            .map_err(|error| Error::UnexpectedResponseError(error.to_string()))?;
+        let from_proto_range = |range: Option<CompleteDataRangeProto>| {
+            range.map(CompleteDataRange::from_proto).transpose()
+        };
+        DataSummary::try_from(UncheckedDataSummary {
+            synced_ledger_info,
+            epoch_ending_ledger_infos: from_proto_range(proto.epoch_ending_ledger_infos)?,
+            states: from_proto_range(proto.states)?,
+            transactions: from_proto_range(proto.transactions)?,
+            transaction_outputs: from_proto_range(proto.transaction_outputs)?,
+        })
+    }
+}
+
+impl StorageServerSummary {
+    /// Converts the storage server summary into its protobuf representation
+    pub fn to_proto(&self) -> crate::Result<StorageServerSummaryProto, Error> {
+        Ok(StorageServerSummaryProto {
+            protocol_metadata: Some(self.protocol_metadata.to_proto()),
+            data_summary: Some(self.data_summary.to_proto()?),
+        })
+    }
+
+    /// Converts the protobuf representation into a storage server summary.
+    /// Returns an error if the protocol metadata or data summary is missing,
+    /// or if either fails to convert.
+    pub fn from_proto(proto: StorageServerSummaryProto) -> crate::Result<Self, Error> {
+        let protocol_metadata = proto.protocol_metadata.ok_or_else(|| {
+            Error::UnexpectedResponseError("The protocol metadata is missing!".into())
+        })?;
+        let data_summary = proto
+            .data_summary
+            .ok_or_else(|| Error::UnexpectedResponseError("The data summary is missing!".into()))?;
+        Ok(StorageServerSummary {
+            protocol_metadata: ProtocolMetadata::from_proto(protocol_metadata)?,
+            data_summary: DataSummary::from_proto(data_summary)?,
+        })
+    }
+}
@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::COMPRESSION_SUFFIX_LABEL;
+use aptos_crypto::HashValue;
 use aptos_types::transaction::Version;
 use serde::{Deserialize, Serialize};
 
@@ -30,6 +31,19 @@ impl StorageServiceRequest {
     }
 }
 
+/// The priority of a storage service request. Servers under load may shed
+/// low-priority requests (e.g., background backfills), while high-priority
+/// requests (e.g., for the tip of the chain) are always processed. Note: the
+/// priority is sent alongside the request (see `StorageServiceMessage`), and
+/// not as a part of it, so it never affects the request's wire format or
+/// how the server caches the response.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum RequestPriority {
+    #[default]
+    High, // The request is never shed (this is the default)
+    Low, // The request may be shed if the server is overloaded
+}
+
 /// A single data request.
 #[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub enum DataRequest {
@@ -44,6 +58,11 @@ pub enum DataRequest {
     GetTransactionsWithProof(TransactionsWithProofRequest), // Fetches a list of transactions with a proof
     GetNewTransactionsOrOutputsWithProof(NewTransactionsOrOutputsWithProofRequest), // Optimistically fetches new transactions or outputs
     GetTransactionsOrOutputsWithProof(TransactionsOrOutputsWithProofRequest), // Fetches a list of transactions or outputs with a proof
+    GetTransactionRangeProof(TransactionRangeProofRequest), // Fetches a proof for a range of transactions (without the data)
+    GetTransactionByHashWithProof(TransactionByHashWithProofRequest), // Fetches a transaction (by hash) with a proof
+    GetLatestLedgerInfo, // Fetches the latest ledger info (without the rest of the summary)
+    GetEpochChangeProofBetween(EpochChangeProofBetweenRequest), // Fetches the minimal epoch change proof between two epochs
+    GetBatch(Vec<DataRequest>), // Fetches a batch of (non-batch) requests in a single response
 }
 
 impl DataRequest {
@@ -63,6 +82,11 @@ impl DataRequest {
                 "get_new_transactions_or_outputs_with_proof"
             },
             Self::GetTransactionsOrOutputsWithProof(_) => "get_transactions_or_outputs_with_proof",
+            Self::GetTransactionRangeProof(_) => "get_transaction_range_proof",
+            Self::GetTransactionByHashWithProof(_) => "get_transaction_by_hash_with_proof",
+            Self::GetLatestLedgerInfo => "get_latest_ledger_info",
+            Self::GetEpochChangeProofBetween(_) => "get_epoch_change_proof_between",
+            Self::GetBatch(_) => "get_batch",
         }
     }
 
@@ -79,6 +103,10 @@ impl DataRequest {
     pub fn is_protocol_version_request(&self) -> bool {
         matches!(self, &Self::GetServerProtocolVersion)
     }
+
+    pub fn is_batch_request(&self) -> bool {
+        matches!(self, &Self::GetBatch(_))
+    }
 }
 
 /// A storage service request for fetching a list of epoch ending ledger infos.
@@ -88,6 +116,17 @@ pub struct EpochEndingLedgerInfoRequest {
     pub expected_end_epoch: u64, // The epoch to finish at
 }
 
+/// A storage service request for fetching the minimal epoch change proof
+/// linking `start_epoch` to `end_epoch`, i.e., the epoch ending ledger infos
+/// for epochs `start_epoch` through `end_epoch - 1` (inclusive). This allows
+/// a client that trusts the validator set of `start_epoch` to verify
+/// ledger infos in `end_epoch`.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct EpochChangeProofBetweenRequest {
+    pub start_epoch: u64, // The epoch the client already trusts
+    pub end_epoch: u64,   // The epoch the client wishes to verify (must be > start_epoch)
+}
+
 /// A storage service request for fetching a new transaction output list
 /// beyond the already known version and epoch.
 #[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
@@ -153,3 +192,25 @@ pub struct TransactionsOrOutputsWithProofRequest {
     pub include_events: bool, // Whether or not to include events (if transactions are returned)
     pub max_num_output_reductions: u64, // The max num of output reductions before transactions are returned
 }
+
+/// A storage service request for fetching only the accumulator range proof
+/// for a list of transactions (e.g., if the client already holds the data).
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct TransactionRangeProofRequest {
+    pub proof_version: u64, // The version the proof should be relative to
+    pub start_version: u64, // The starting version of the transaction range
+    pub end_version: u64,   // The ending version of the transaction range (inclusive)
+}
+
+/// A storage service request for fetching a single transaction (identified
+/// by hash) with a corresponding proof. Given that the version of the
+/// transaction is unknown, the request also specifies the version range
+/// that could contain the transaction (e.g., to help identify peers).
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct TransactionByHashWithProofRequest {
+    pub transaction_hash: HashValue, // The hash of the transaction to fetch
+    pub proof_version: u64,          // The version the proof should be relative to
+    pub start_version: u64,          // The lowest version that could contain the transaction
+    pub end_version: u64, // The highest version that could contain the transaction (inclusive)
+    pub include_events: bool, // Whether or not to include events in the response
+}
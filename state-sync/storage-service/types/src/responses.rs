@@ -2,39 +2,110 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    requests::DataRequest::{
-        GetEpochEndingLedgerInfos, GetNewTransactionOutputsWithProof,
-        GetNewTransactionsOrOutputsWithProof, GetNewTransactionsWithProof,
-        GetNumberOfStatesAtVersion, GetServerProtocolVersion, GetStateValuesWithProof,
-        GetStorageServerSummary, GetTransactionOutputsWithProof, GetTransactionsOrOutputsWithProof,
-        GetTransactionsWithProof,
+    requests::{
+        DataRequest::{
+            GetBatch, GetEpochChangeProofBetween, GetEpochEndingLedgerInfos, GetLatestLedgerInfo,
+            GetNewTransactionOutputsWithProof, GetNewTransactionsOrOutputsWithProof,
+            GetNewTransactionsWithProof, GetNumberOfStatesAtVersion, GetServerProtocolVersion,
+            GetStateValuesWithProof, GetStorageServerSummary, GetTransactionByHashWithProof,
+            GetTransactionOutputsWithProof, GetTransactionRangeProof,
+            GetTransactionsOrOutputsWithProof, GetTransactionsWithProof,
+        },
+        TransactionOutputsWithProofRequest, TransactionsWithProofRequest,
     },
     responses::Error::DegenerateRangeError,
-    Epoch, StorageServiceRequest, COMPRESSION_SUFFIX_LABEL,
+    Epoch, StorageServiceRequest, CHECKSUMMED_COMPRESSION_SUFFIX_LABEL, COMPRESSION_SUFFIX_LABEL,
 };
 use aptos_compression::{metrics::CompressionClient, CompressedData, CompressionError};
 use aptos_config::config::{StorageServiceConfig, MAX_APPLICATION_MESSAGE_SIZE};
+use aptos_crypto::{hash::CryptoHash, HashValue};
+use aptos_infallible::Mutex;
 use aptos_types::{
     epoch_change::EpochChangeProof,
     ledger_info::LedgerInfoWithSignatures,
+    proof::{
+        TransactionAccumulatorInternalNode, TransactionAccumulatorRangeProof,
+        TransactionInfoListWithProof,
+    },
     state_store::state_value::StateValueChunkWithProof,
-    transaction::{TransactionListWithProof, TransactionOutputListWithProof, Version},
+    transaction::{
+        TransactionListWithProof, TransactionOutputListWithProof, TransactionWithProof, Version,
+    },
 };
-use num_traits::{PrimInt, Zero};
+use num_traits::PrimInt;
 #[cfg(test)]
 use proptest::prelude::{any, Arbitrary, BoxedStrategy, Strategy};
+use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
 use std::{
+    borrow::Cow,
+    cmp::Ordering,
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
     convert::TryFrom,
     fmt::{Display, Formatter},
+    ops::Range,
+    str::FromStr,
+    time::Duration,
 };
 use thiserror::Error;
 
 /// The version delta we'll tolerate when considering if a peer is eligible
 /// to handle an optimistic fetch for new data. This value is set assuming
 /// 5k TPS for a 5 second delay, which should be more than enough.
+/// See `OptimisticFetchWindow` for a typed representation of the window.
 pub const OPTIMISTIC_FETCH_VERSION_DELTA: u64 = 25000;
 
+/// The window (in versions) ahead of a peer's synced version within which
+/// the peer is eligible to handle an optimistic fetch for new data, i.e.,
+/// the peer can service an optimistic fetch if the known version is less
+/// than the synced version plus the window. The default window is
+/// `OPTIMISTIC_FETCH_VERSION_DELTA`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct OptimisticFetchWindow(u64);
+
+impl OptimisticFetchWindow {
+    /// Creates a window that tolerates the given version delta
+    pub const fn from_version_delta(version_delta: u64) -> Self {
+        Self(version_delta)
+    }
+
+    /// Creates a window that tolerates the versions committed over the given
+    /// number of seconds at the given throughput (in transactions per second)
+    pub fn from_tps_and_seconds(tps: u64, secs: u64) -> Self {
+        Self(tps.saturating_mul(secs))
+    }
+
+    /// Returns the version delta tolerated by the window
+    pub fn version_delta(&self) -> u64 {
+        self.0
+    }
+
+    /// Returns true iff the known version is within the window ahead of
+    /// the synced version (i.e., an optimistic fetch can be serviced)
+    pub fn is_within(&self, synced: Version, known: Version) -> bool {
+        synced.saturating_add(self.0) > known
+    }
+}
+
+impl Default for OptimisticFetchWindow {
+    fn default() -> Self {
+        Self::from_version_delta(OPTIMISTIC_FETCH_VERSION_DELTA)
+    }
+}
+
+/// The estimated serialized size (in bytes) of a single data item (e.g., a
+/// transaction or state value) in a response. This estimate is deliberately
+/// coarse, and is only used to plan requests (see `plan_within_byte_budget`).
+pub const ESTIMATED_RESPONSE_ITEM_BYTES: usize = 1024;
+
+/// The estimated serialized size (in bytes) of the proof (and any other
+/// metadata) in a response, regardless of the number of data items.
+pub const ESTIMATED_RESPONSE_OVERHEAD_BYTES: usize = 2048;
+
+/// The number of bytes in the checksum trailing checksummed compressed data
+/// (i.e., a truncated SHA3-256 hash of the compressed bytes).
+pub const COMPRESSED_DATA_CHECKSUM_NUM_BYTES: usize = 8;
+
 #[derive(Clone, Debug, Deserialize, Error, PartialEq, Eq, Serialize)]
 pub enum Error {
     #[error("Data range cannot be degenerate!")]
@@ -43,6 +114,50 @@ pub enum Error {
     UnexpectedErrorEncountered(String),
     #[error("Unexpected response error: {0}")]
     UnexpectedResponseError(String),
+    #[error("Unsupported request: {label}")]
+    UnsupportedRequest { label: String },
+    #[error("{error}")]
+    RetriedError { attempt: u32, error: Box<Error> }, // The error along with the number of failed attempts
+    #[error("Unsupported compression scheme: {scheme}")]
+    UnsupportedCompressionScheme { scheme: String },
+    #[error("Checksum mismatch for the compressed response: {label}")]
+    ChecksumMismatch { label: String },
+}
+
+impl Error {
+    /// Wraps the error with the number of times the request has already
+    /// failed. The original error message is preserved. If the error
+    /// already carries an attempt count, it is replaced.
+    pub fn with_attempt(self, attempt: u32) -> Error {
+        Error::RetriedError {
+            attempt,
+            error: Box::new(self.into_unwrapped()),
+        }
+    }
+
+    /// Returns the number of failed attempts (if the error carries one)
+    pub fn attempt_count(&self) -> Option<u32> {
+        match self {
+            Error::RetriedError { attempt, .. } => Some(*attempt),
+            _ => None,
+        }
+    }
+
+    /// Returns a reference to the underlying error (i.e., without the attempt count)
+    fn unwrapped(&self) -> &Error {
+        match self {
+            Error::RetriedError { error, .. } => error.unwrapped(),
+            error => error,
+        }
+    }
+
+    /// Returns the underlying error (i.e., without the attempt count)
+    fn into_unwrapped(self) -> Error {
+        match self {
+            Error::RetriedError { error, .. } => error.into_unwrapped(),
+            error => error,
+        }
+    }
 }
 
 impl From<CompressionError> for Error {
@@ -51,6 +166,196 @@ impl From<CompressionError> for Error {
     }
 }
 
+/// Returns the compression scheme of the given compressed response label, i.e.,
+/// the suffix that follows the data response label (e.g., the only currently
+/// supported scheme is `COMPRESSION_SUFFIX_LABEL`). If the label doesn't start
+/// with a known data response label, the entire label is returned.
+fn get_compression_scheme(label: &str) -> &str {
+    DataResponse::all_labels()
+        .iter()
+        .filter_map(|data_label| label.strip_prefix(data_label))
+        .min_by_key(|scheme| scheme.len()) // Use the longest matching data label
+        .unwrap_or(label)
+}
+
+/// Serializes and compresses the given data response
+fn compress_data_response(data_response: &DataResponse) -> Result<CompressedData, Error> {
+    let raw_data = bcs::to_bytes(data_response)
+        .map_err(|error| Error::UnexpectedErrorEncountered(error.to_string()))?;
+    let compressed_data = aptos_compression::compress(
+        raw_data,
+        CompressionClient::StateSync,
+        MAX_APPLICATION_MESSAGE_SIZE,
+    )?;
+    Ok(compressed_data)
+}
+
+/// Returns the checksum of the given compressed data
+fn compute_checksum(compressed_data: &[u8]) -> [u8; COMPRESSED_DATA_CHECKSUM_NUM_BYTES] {
+    let mut checksum = [0; COMPRESSED_DATA_CHECKSUM_NUM_BYTES];
+    checksum.copy_from_slice(
+        &HashValue::sha3_256_of(compressed_data).as_ref()[..COMPRESSED_DATA_CHECKSUM_NUM_BYTES],
+    );
+    checksum
+}
+
+/// Verifies the checksum trailing the given compressed data, and returns the
+/// compressed data without the checksum. Returns a `ChecksumMismatch` error
+/// if the checksum is missing or doesn't match the compressed data.
+fn verify_and_strip_checksum(
+    label: &str,
+    checksummed_data: &[u8],
+) -> Result<CompressedData, Error> {
+    let checksum_mismatch = || Error::ChecksumMismatch {
+        label: label.into(),
+    };
+    let data_length = checksummed_data
+        .len()
+        .checked_sub(COMPRESSED_DATA_CHECKSUM_NUM_BYTES)
+        .ok_or_else(checksum_mismatch)?;
+    let (compressed_data, checksum) = checksummed_data.split_at(data_length);
+    if checksum != compute_checksum(compressed_data) {
+        return Err(checksum_mismatch());
+    }
+    Ok(compressed_data.to_vec())
+}
+
+/// Verifies the compression scheme of the given label is supported (and
+/// verifies the checksum, if one is expected), and decompresses the data
+fn decompress_response_data(label: &str, compressed_data: &[u8]) -> Result<Vec<u8>, Error> {
+    let scheme = get_compression_scheme(label);
+    let verified_data;
+    let compressed_data = if scheme == COMPRESSION_SUFFIX_LABEL {
+        compressed_data
+    } else if scheme == CHECKSUMMED_COMPRESSION_SUFFIX_LABEL {
+        verified_data = verify_and_strip_checksum(label, compressed_data)?;
+        &verified_data
+    } else {
+        return Err(Error::UnsupportedCompressionScheme {
+            scheme: scheme.trim_start_matches('_').into(),
+        });
+    };
+
+    aptos_compression::decompress(
+        compressed_data,
+        CompressionClient::StateSync,
+        MAX_APPLICATION_MESSAGE_SIZE,
+    )
+    .map_err(|error| {
+        Error::UnexpectedResponseError(format!(
+            "Failed to decompress the response (label: {}) using the supported \
+            compression scheme! Error: {}",
+            label, error
+        ))
+    })
+}
+
+/// Reads the item count from the leading bytes of the given BCS encoded data
+/// response, without deserializing the items (see `try_item_count`). This
+/// relies on the BCS variant indices (i.e., the declaration order) of
+/// `DataResponse`, and the field order of the inner list types.
+fn read_item_count(label: &str, raw_data: &[u8]) -> Result<Option<usize>, Error> {
+    let truncated = || {
+        Error::UnexpectedResponseError(format!(
+            "The response (label: {}) is truncated or malformed!",
+            label
+        ))
+    };
+    let mut bytes = raw_data;
+    let read_uleb128 = |bytes: &mut &[u8]| -> Result<u64, Error> {
+        let mut value: u64 = 0;
+        for shift in (0..u64::BITS).step_by(7) {
+            let (byte, remaining) = bytes.split_first().ok_or_else(truncated)?;
+            *bytes = remaining;
+            value |= u64::from(byte & 0x7F) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(truncated())
+    };
+
+    // Identify the response variant (using the same table as the labels)
+    let variant_index = read_uleb128(&mut bytes)?;
+    let variant_label = usize::try_from(variant_index)
+        .ok()
+        .and_then(|variant_index| DATA_RESPONSE_LABELS.get(variant_index))
+        .ok_or_else(|| {
+            Error::UnexpectedResponseError(format!(
+                "The response (label: {}) has an unknown variant index: {}",
+                label, variant_index
+            ))
+        })?;
+
+    let item_count = match *variant_label {
+        // The list is the first field of the response (i.e., epoch ending
+        // ledger infos, transactions, outputs and epoch change proofs).
+        "epoch_ending_ledger_infos"
+        | "new_transaction_outputs_with_proof"
+        | "new_transactions_with_proof"
+        | "transaction_outputs_with_proof"
+        | "transactions_with_proof"
+        | "epoch_change_proof_between" => read_uleb128(&mut bytes)?,
+        // The state values are preceded by the first and last indices and keys
+        "state_value_chunk_with_proof" => {
+            let prefix_length = 2 * std::mem::size_of::<u64>() + 2 * HashValue::LENGTH;
+            bytes = bytes.get(prefix_length..).ok_or_else(truncated)?;
+            read_uleb128(&mut bytes)?
+        },
+        // The list is held by the first option that is set (if any)
+        "new_transactions_or_outputs_with_proof" | "transactions_or_outputs_with_proof" => {
+            let mut item_count = 0;
+            for _ in 0..2 {
+                let (option_tag, remaining) = bytes.split_first().ok_or_else(truncated)?;
+                bytes = remaining;
+                if *option_tag == 1 {
+                    item_count = read_uleb128(&mut bytes)?;
+                    break;
+                }
+            }
+            item_count
+        },
+        "number_of_states_at_version"
+        | "server_protocol_version"
+        | "storage_server_summary"
+        | "transaction_range_proof"
+        | "transaction_by_hash_with_proof"
+        | "latest_ledger_info"
+        | "batch" => return Ok(None),
+        variant_label => {
+            return Err(Error::UnexpectedResponseError(format!(
+                "The response (label: {}) has an unsupported variant: {}",
+                label, variant_label
+            )))
+        },
+    };
+    usize::try_from(item_count)
+        .map(Some)
+        .map_err(|_| truncated())
+}
+
+/// The (canonical) labels of the responses that clients accept raw, even if
+/// compression was requested. These responses are trivially small, so servers
+/// may eventually stop compressing them. Note: servers still honor the
+/// compression flag, as older clients reject raw responses to requests
+/// that asked for compression.
+pub const RAW_TOLERATED_RESPONSE_LABELS: &[&str] =
+    &["number_of_states_at_version", "server_protocol_version"];
+
+/// Returns true iff responses with the given (canonical) label must be
+/// compressed when compression is requested (see `RAW_TOLERATED_RESPONSE_LABELS`)
+pub fn requires_compression(label: &str) -> bool {
+    !RAW_TOLERATED_RESPONSE_LABELS.contains(&label)
+}
+
+/// The compression schemes with which a response can be sent
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum CompressionScheme {
+    None,                   // The response is sent raw (i.e., uncompressed)
+    Compressed,             // The response is compressed
+    CompressedWithChecksum, // The response is compressed and carries a checksum
+}
+
 /// A storage service response.
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 #[allow(clippy::large_enum_variant)]
@@ -63,14 +368,8 @@ impl StorageServiceResponse {
     /// Creates a new response and performs compression if required
     pub fn new(data_response: DataResponse, perform_compression: bool) -> Result<Self, Error> {
         if perform_compression {
-            let raw_data = bcs::to_bytes(&data_response)
-                .map_err(|error| Error::UnexpectedErrorEncountered(error.to_string()))?;
-            let compressed_data = aptos_compression::compress(
-                raw_data,
-                CompressionClient::StateSync,
-                MAX_APPLICATION_MESSAGE_SIZE,
-            )?;
-            let label = data_response.get_label().to_string() + COMPRESSION_SUFFIX_LABEL;
+            let compressed_data = compress_data_response(&data_response)?;
+            let label = data_response.canonical_label().to_string() + COMPRESSION_SUFFIX_LABEL;
             Ok(StorageServiceResponse::CompressedResponse(
                 label,
                 compressed_data,
@@ -80,15 +379,48 @@ impl StorageServiceResponse {
         }
     }
 
+    /// Creates a new compressed response with a checksum (computed over the
+    /// compressed bytes) appended to the compressed data. The checksum is
+    /// verified by `get_data_response` to detect corruption in transit. This
+    /// is opt-in (via the compression scheme in the label), as older peers
+    /// will reject the scheme as unsupported.
+    pub fn new_with_checksum(data_response: DataResponse) -> Result<Self, Error> {
+        let mut compressed_data = compress_data_response(&data_response)?;
+        compressed_data.extend_from_slice(&compute_checksum(&compressed_data));
+        let label =
+            data_response.canonical_label().to_string() + CHECKSUMMED_COMPRESSION_SUFFIX_LABEL;
+        Ok(StorageServiceResponse::CompressedResponse(
+            label,
+            compressed_data,
+        ))
+    }
+
+    /// Decodes the response and re-encodes it under the given compression
+    /// scheme (e.g., to serve a canonical cached response to older peers that
+    /// don't support the cached scheme). The data response is unchanged.
+    pub fn recompress_as(&self, scheme: CompressionScheme) -> Result<Self, Error> {
+        let data_response = self.get_data_response()?;
+        match scheme {
+            CompressionScheme::None => Ok(StorageServiceResponse::RawResponse(data_response)),
+            CompressionScheme::Compressed => StorageServiceResponse::new(data_response, true),
+            CompressionScheme::CompressedWithChecksum => {
+                StorageServiceResponse::new_with_checksum(data_response)
+            },
+        }
+    }
+
+    /// Returns true iff both responses hold the same data response, regardless
+    /// of the inner format (i.e., the compression scheme). Returns an error if
+    /// either response fails to decode.
+    pub fn same_data(&self, other: &StorageServiceResponse) -> Result<bool, Error> {
+        Ok(self.get_data_response()? == other.get_data_response()?)
+    }
+
     /// Returns the data response regardless of the inner format
     pub fn get_data_response(&self) -> Result<DataResponse, Error> {
         match self {
-            StorageServiceResponse::CompressedResponse(_, compressed_data) => {
-                let raw_data = aptos_compression::decompress(
-                    compressed_data,
-                    CompressionClient::StateSync,
-                    MAX_APPLICATION_MESSAGE_SIZE,
-                )?;
+            StorageServiceResponse::CompressedResponse(label, compressed_data) => {
+                let raw_data = decompress_response_data(label, compressed_data)?;
                 let data_response = bcs::from_bytes::<DataResponse>(&raw_data)
                     .map_err(|error| Error::UnexpectedErrorEncountered(error.to_string()))?;
                 Ok(data_response)
@@ -97,12 +429,42 @@ impl StorageServiceResponse {
         }
     }
 
+    /// Returns the number of items in the list held by the response (e.g.,
+    /// the number of transactions or states), or `None` if the response
+    /// doesn't hold a list. For compressed responses, only the leading BCS
+    /// prefix (i.e., the variant index and list length) is decoded, so the
+    /// items themselves are never deserialized. Note: the data is still
+    /// decompressed in full, as the compression scheme doesn't support
+    /// partial (streaming) decompression.
+    pub fn try_item_count(&self) -> Result<Option<usize>, Error> {
+        match self {
+            StorageServiceResponse::CompressedResponse(label, compressed_data) => {
+                let raw_data = decompress_response_data(label, compressed_data)?;
+                read_item_count(label, &raw_data)
+            },
+            StorageServiceResponse::RawResponse(data_response) => {
+                Ok(data_response.get_item_count())
+            },
+        }
+    }
+
+    /// Decodes the response (i.e., decompressing it if required) into a
+    /// `DecodedResponse`. This consumes the response, so raw responses are
+    /// never cloned.
+    pub fn decode(self) -> Result<DecodedResponse, Error> {
+        let data_response = match self {
+            StorageServiceResponse::CompressedResponse(..) => self.get_data_response()?,
+            StorageServiceResponse::RawResponse(data_response) => data_response,
+        };
+        DecodedResponse::try_from(data_response)
+    }
+
     /// Returns a summary label for the response
     pub fn get_label(&self) -> String {
         match self {
             StorageServiceResponse::CompressedResponse(label, _) => label.clone(),
             StorageServiceResponse::RawResponse(data_response) => {
-                data_response.get_label().to_string()
+                data_response.canonical_label().to_string()
             },
         }
     }
@@ -111,6 +473,61 @@ impl StorageServiceResponse {
     pub fn is_compressed(&self) -> bool {
         matches!(self, Self::CompressedResponse(_, _))
     }
+
+    /// Returns the storage server summary held by the response. For raw
+    /// responses, the summary is borrowed (to avoid cloning it). For
+    /// compressed responses, the summary is decoded and owned.
+    pub fn storage_server_summary_ref(&self) -> Result<Cow<StorageServerSummary>, Error> {
+        match self {
+            StorageServiceResponse::RawResponse(DataResponse::StorageServerSummary(summary)) => {
+                Ok(Cow::Borrowed(summary))
+            },
+            StorageServiceResponse::RawResponse(data_response) => {
+                Err(Error::UnexpectedResponseError(format!(
+                    "expected storage_server_summary, found {}",
+                    data_response.get_label()
+                )))
+            },
+            StorageServiceResponse::CompressedResponse(..) => match self.get_data_response()? {
+                DataResponse::StorageServerSummary(summary) => Ok(Cow::Owned(summary)),
+                data_response => Err(Error::UnexpectedResponseError(format!(
+                    "expected storage_server_summary, found {}",
+                    data_response.get_label()
+                ))),
+            },
+        }
+    }
+
+    /// Returns the data summary held by the response (see `storage_server_summary_ref`)
+    pub fn data_summary_ref(&self) -> Result<Cow<DataSummary>, Error> {
+        let data_summary = match self.storage_server_summary_ref()? {
+            Cow::Borrowed(summary) => Cow::Borrowed(&summary.data_summary),
+            Cow::Owned(summary) => Cow::Owned(summary.data_summary),
+        };
+        Ok(data_summary)
+    }
+
+    /// Returns the protocol metadata held by the response (see `storage_server_summary_ref`)
+    pub fn protocol_metadata_ref(&self) -> Result<Cow<ProtocolMetadata>, Error> {
+        let protocol_metadata = match self.storage_server_summary_ref()? {
+            Cow::Borrowed(summary) => Cow::Borrowed(&summary.protocol_metadata),
+            Cow::Owned(summary) => Cow::Owned(summary.protocol_metadata),
+        };
+        Ok(protocol_metadata)
+    }
+}
+
+/// Decodes the given responses (i.e., decompressing them if required) in
+/// parallel. The results are returned in the same order as the responses,
+/// and a response that fails to decode doesn't affect the others.
+pub fn decode_many(responses: Vec<StorageServiceResponse>) -> Vec<Result<DataResponse, Error>> {
+    responses
+        .into_par_iter()
+        .map(|response| match response {
+            StorageServiceResponse::RawResponse(data_response) => Ok(data_response),
+            response => response.get_data_response(),
+        })
+        .collect()
 }
 
 /// A useful type to hold optional transaction data
@@ -119,6 +536,64 @@ pub type TransactionOrOutputListWithProof = (
     Option<TransactionOutputListWithProof>,
 );
 
+/// A transaction list or a transaction output list (but never both)
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum TxnsOrOutputs {
+    Transactions(TransactionListWithProof),
+    Outputs(TransactionOutputListWithProof),
+}
+
+/// A simple trait to convert optional transaction data into a uniform enum
+pub trait IntoTxnsOrOutputs {
+    /// Converts the data into either a transaction list or an output list.
+    /// Returns an error if both (or neither) of the lists are present.
+    fn into_either(self) -> Result<TxnsOrOutputs, Error>;
+}
+
+impl IntoTxnsOrOutputs for TransactionOrOutputListWithProof {
+    fn into_either(self) -> Result<TxnsOrOutputs, Error> {
+        match self {
+            (Some(transaction_list), None) => Ok(TxnsOrOutputs::Transactions(transaction_list)),
+            (None, Some(output_list)) => Ok(TxnsOrOutputs::Outputs(output_list)),
+            (Some(_), Some(_)) => Err(Error::UnexpectedResponseError(
+                "Found both transactions and outputs! Expected exactly one.".into(),
+            )),
+            (None, None) => Err(Error::UnexpectedResponseError(
+                "Found neither transactions nor outputs! Expected exactly one.".into(),
+            )),
+        }
+    }
+}
+
+/// The minimal epoch change proof between two epochs (i.e., the response to
+/// a `GetEpochChangeProofBetween` request). This is kept distinct from the
+/// epoch ending ledger infos (which are also an `EpochChangeProof`), so that
+/// each response can only be converted into its own type. Note: the wrapper
+/// doesn't change the wire layout of the proof.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct MinimalEpochChangeProof(pub EpochChangeProof);
+
+/// The canonical labels of the data response variants (indexed by the variant
+/// index, see `DataResponse::variant_index`). New labels must be appended.
+const DATA_RESPONSE_LABELS: [&str; 16] = [
+    "epoch_ending_ledger_infos",
+    "new_transaction_outputs_with_proof",
+    "new_transactions_with_proof",
+    "number_of_states_at_version",
+    "server_protocol_version",
+    "state_value_chunk_with_proof",
+    "storage_server_summary",
+    "transaction_outputs_with_proof",
+    "transactions_with_proof",
+    "new_transactions_or_outputs_with_proof",
+    "transactions_or_outputs_with_proof",
+    "transaction_range_proof",
+    "transaction_by_hash_with_proof",
+    "latest_ledger_info",
+    "epoch_change_proof_between",
+    "batch",
+];
+
 /// A single data response.
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 #[allow(clippy::large_enum_variant)]
@@ -134,27 +609,863 @@ pub enum DataResponse {
     TransactionsWithProof(TransactionListWithProof),
     NewTransactionsOrOutputsWithProof((TransactionOrOutputListWithProof, LedgerInfoWithSignatures)),
     TransactionsOrOutputsWithProof(TransactionOrOutputListWithProof),
+    TransactionRangeProof(TransactionAccumulatorRangeProof),
+    TransactionByHashWithProof(Option<TransactionWithProof>),
+    LatestLedgerInfo(LedgerInfoWithSignatures),
+    EpochChangeProofBetween(MinimalEpochChangeProof),
+    Batch(#[serde(deserialize_with = "deserialize_batch")] Vec<DataResponse>), // A batch of (non-batch) responses (e.g., for pipelining)
 }
 
 impl DataResponse {
-    /// Returns a summary label for the response
+    /// Returns the canonical label for the response. Canonical labels are
+    /// used for metrics and compressed response labels, so they are stable:
+    /// once released, a variant's canonical label must never change, and new
+    /// variants must be given a label that is not already in use.
+    pub fn canonical_label(&self) -> &'static str {
+        DATA_RESPONSE_LABELS[self.variant_index()]
+    }
+
+    /// Returns a human readable label for the response (e.g., for logs and
+    /// error messages). Unlike `canonical_label`, this label is not stable and
+    /// may change between releases, so it should never feed metrics. For now,
+    /// it is identical to the canonical label.
+    pub fn display_label(&self) -> &'static str {
+        self.canonical_label()
+    }
+
+    /// Returns a summary label for the response (see `display_label`)
     pub fn get_label(&self) -> &'static str {
+        self.display_label()
+    }
+
+    /// Returns the canonical labels of all response variants (in variant order)
+    pub fn all_labels() -> &'static [&'static str] {
+        &DATA_RESPONSE_LABELS
+    }
+
+    /// Returns the index of the response variant. This is identical to the
+    /// variant index used by BCS, so variants must only ever be appended.
+    pub(crate) fn variant_index(&self) -> usize {
+        match self {
+            Self::EpochEndingLedgerInfos(_) => 0,
+            Self::NewTransactionOutputsWithProof(_) => 1,
+            Self::NewTransactionsWithProof(_) => 2,
+            Self::NumberOfStatesAtVersion(_) => 3,
+            Self::ServerProtocolVersion(_) => 4,
+            Self::StateValueChunkWithProof(_) => 5,
+            Self::StorageServerSummary(_) => 6,
+            Self::TransactionOutputsWithProof(_) => 7,
+            Self::TransactionsWithProof(_) => 8,
+            Self::NewTransactionsOrOutputsWithProof(_) => 9,
+            Self::TransactionsOrOutputsWithProof(_) => 10,
+            Self::TransactionRangeProof(_) => 11,
+            Self::TransactionByHashWithProof(_) => 12,
+            Self::LatestLedgerInfo(_) => 13,
+            Self::EpochChangeProofBetween(_) => 14,
+            Self::Batch(_) => 15,
+        }
+    }
+
+    /// Returns the response as structured (key-value) fields for logging.
+    /// The label is always included, and the version range and item count
+    /// are included for responses that contain data lists.
+    pub fn to_log_fields(&self) -> Vec<(&'static str, String)> {
+        let mut log_fields = vec![("label", self.get_label().to_string())];
+        match self {
+            Self::EpochEndingLedgerInfos(epoch_change_proof)
+            | Self::EpochChangeProofBetween(MinimalEpochChangeProof(epoch_change_proof)) => {
+                log_fields.push((
+                    "item_count",
+                    epoch_change_proof.ledger_info_with_sigs.len().to_string(),
+                ))
+            },
+            Self::NewTransactionOutputsWithProof((output_list, _)) => {
+                add_output_list_log_fields(&mut log_fields, output_list)
+            },
+            Self::NewTransactionsWithProof((transaction_list, _)) => {
+                add_transaction_list_log_fields(&mut log_fields, transaction_list)
+            },
+            Self::StateValueChunkWithProof(state_value_chunk) => {
+                log_fields.push(("item_count", state_value_chunk.raw_values.len().to_string()))
+            },
+            Self::TransactionOutputsWithProof(output_list) => {
+                add_output_list_log_fields(&mut log_fields, output_list)
+            },
+            Self::TransactionsWithProof(transaction_list) => {
+                add_transaction_list_log_fields(&mut log_fields, transaction_list)
+            },
+            Self::NewTransactionsOrOutputsWithProof((transaction_or_output_list, _))
+            | Self::TransactionsOrOutputsWithProof(transaction_or_output_list) => {
+                if let Some(transaction_list) = &transaction_or_output_list.0 {
+                    add_transaction_list_log_fields(&mut log_fields, transaction_list);
+                }
+                if let Some(output_list) = &transaction_or_output_list.1 {
+                    add_output_list_log_fields(&mut log_fields, output_list);
+                }
+            },
+            Self::TransactionByHashWithProof(transaction_with_proof) => {
+                if let Some(transaction_with_proof) = transaction_with_proof {
+                    add_version_range_log_fields(
+                        &mut log_fields,
+                        Some(transaction_with_proof.version),
+                        1,
+                    );
+                }
+                log_fields.push((
+                    "item_count",
+                    (transaction_with_proof.is_some() as usize).to_string(),
+                ));
+            },
+            Self::Batch(data_responses) => {
+                log_fields.push(("batch_size", data_responses.len().to_string()))
+            },
+            Self::LatestLedgerInfo(_)
+            | Self::NumberOfStatesAtVersion(_)
+            | Self::ServerProtocolVersion(_)
+            | Self::StorageServerSummary(_)
+            | Self::TransactionRangeProof(_) => {},
+        }
+        log_fields
+    }
+
+    /// Returns the number of items in the list held by the response, or
+    /// `None` if the response doesn't hold a list (see `try_item_count`).
+    fn get_item_count(&self) -> Option<usize> {
+        match self {
+            Self::EpochEndingLedgerInfos(epoch_change_proof)
+            | Self::EpochChangeProofBetween(MinimalEpochChangeProof(epoch_change_proof)) => {
+                Some(epoch_change_proof.ledger_info_with_sigs.len())
+            },
+            Self::NewTransactionOutputsWithProof((output_list, _))
+            | Self::TransactionOutputsWithProof(output_list) => {
+                Some(output_list.transactions_and_outputs.len())
+            },
+            Self::NewTransactionsWithProof((transaction_list, _))
+            | Self::TransactionsWithProof(transaction_list) => {
+                Some(transaction_list.transactions.len())
+            },
+            Self::StateValueChunkWithProof(state_value_chunk) => {
+                Some(state_value_chunk.raw_values.len())
+            },
+            Self::NewTransactionsOrOutputsWithProof((transaction_or_output_list, _))
+            | Self::TransactionsOrOutputsWithProof(transaction_or_output_list) => {
+                match transaction_or_output_list {
+                    (Some(transaction_list), _) => Some(transaction_list.transactions.len()),
+                    (None, Some(output_list)) => Some(output_list.transactions_and_outputs.len()),
+                    (None, None) => Some(0),
+                }
+            },
+            Self::LatestLedgerInfo(_)
+            | Self::NumberOfStatesAtVersion(_)
+            | Self::ServerProtocolVersion(_)
+            | Self::StorageServerSummary(_)
+            | Self::TransactionByHashWithProof(_)
+            | Self::TransactionRangeProof(_)
+            | Self::Batch(_) => None,
+        }
+    }
+
+    /// Creates a batch response holding the given responses, so that several
+    /// (small) responses can be sent in a single message. Returns an error if
+    /// any of the responses is itself a batch (i.e., batches are limited to a
+    /// depth of one), or if the batch is estimated to exceed the max message
+    /// size (see `estimated_serialized_size`).
+    pub fn new_batch(data_responses: Vec<DataResponse>) -> Result<Self, Error> {
+        check_batch_depth(&data_responses)?;
+        let batch = Self::Batch(data_responses);
+        let estimated_size = batch.estimated_serialized_size()?;
+        if estimated_size > MAX_APPLICATION_MESSAGE_SIZE {
+            return Err(Error::UnexpectedErrorEncountered(format!(
+                "The batch response is too large! Estimated size: {}, max size: {}",
+                estimated_size, MAX_APPLICATION_MESSAGE_SIZE
+            )));
+        }
+        Ok(batch)
+    }
+
+    /// Returns the estimated serialized size (in bytes) of the response. For
+    /// batch responses, this includes every response in the batch (as well
+    /// as the batch framing, i.e., the variant index and length prefix).
+    pub fn estimated_serialized_size(&self) -> Result<usize, Error> {
+        get_serialized_size(self)
+    }
+
+    /// Returns the range of versions actually served by this response (or
+    /// `None` if the response doesn't contain a (non-empty) version list).
+    pub fn get_served_range(&self) -> Option<CompleteDataRange<Version>> {
+        let (first_version, num_items) = match self {
+            Self::NewTransactionOutputsWithProof((output_list, _))
+            | Self::TransactionOutputsWithProof(output_list) => (
+                output_list.first_transaction_output_version,
+                output_list.transactions_and_outputs.len(),
+            ),
+            Self::NewTransactionsWithProof((transaction_list, _))
+            | Self::TransactionsWithProof(transaction_list) => (
+                transaction_list.first_transaction_version,
+                transaction_list.transactions.len(),
+            ),
+            Self::NewTransactionsOrOutputsWithProof(((Some(transaction_list), _), _))
+            | Self::TransactionsOrOutputsWithProof((Some(transaction_list), _)) => (
+                transaction_list.first_transaction_version,
+                transaction_list.transactions.len(),
+            ),
+            Self::NewTransactionsOrOutputsWithProof(((None, Some(output_list)), _))
+            | Self::TransactionsOrOutputsWithProof((None, Some(output_list))) => (
+                output_list.first_transaction_output_version,
+                output_list.transactions_and_outputs.len(),
+            ),
+            _ => return None,
+        };
+
+        let first_version = first_version?;
+        if num_items == 0 {
+            return None;
+        }
+        CompleteDataRange::from_len(first_version, num_items as u64).ok()
+    }
+
+    /// Returns true iff the range served by this response ends before the
+    /// end version of the given request (i.e., the server truncated the
+    /// response). This allows clients to compute the next request without
+    /// guessing. Requests without an end version are never truncated.
+    pub fn is_truncated(&self, request: &StorageServiceRequest) -> bool {
+        let requested_end_version = match &request.data_request {
+            GetTransactionOutputsWithProof(request) => request.end_version,
+            GetTransactionsWithProof(request) => request.end_version,
+            GetTransactionsOrOutputsWithProof(request) => request.end_version,
+            _ => return false,
+        };
+        self.get_served_range()
+            .map(|served_range| served_range.highest() < requested_end_version)
+            .unwrap_or(false)
+    }
+
+    /// Merges this response with the given response, iff both responses are
+    /// the same list variant and the version ranges are adjacent (i.e., the
+    /// given list starts immediately after this list ends). The items are
+    /// concatenated and the proofs are combined. Note: both proofs must be
+    /// relative to the same ledger info, otherwise the merged proof will
+    /// fail verification.
+    pub fn try_merge_adjacent(self, other: DataResponse) -> Result<DataResponse, Error> {
+        match (self, other) {
+            (Self::TransactionsWithProof(first), Self::TransactionsWithProof(second)) => {
+                merge_transaction_lists(first, second).map(Self::TransactionsWithProof)
+            },
+            (
+                Self::TransactionOutputsWithProof(first),
+                Self::TransactionOutputsWithProof(second),
+            ) => {
+                merge_transaction_output_lists(first, second).map(Self::TransactionOutputsWithProof)
+            },
+            (first, second) => Err(Error::UnexpectedResponseError(format!(
+                "Unable to merge responses! Expected matching list variants, found: {} and {}",
+                first.get_label(),
+                second.get_label()
+            ))),
+        }
+    }
+
+    /// Splits this response into adjacent pieces that each serialize to at
+    /// most `max_bytes` (e.g., `MAX_APPLICATION_MESSAGE_SIZE`). This is the
+    /// inverse of `try_merge_adjacent`: only the list variants supported by
+    /// `try_merge_adjacent` are split, and a valid range proof is recomputed
+    /// for each piece (relative to the same ledger info as the original). All
+    /// other variants (and responses that already fit) are returned as a
+    /// single piece. Returns an error if a single item doesn't fit.
+    pub fn split_for_wire(self, max_bytes: usize) -> Result<Vec<DataResponse>, Error> {
+        if get_serialized_size(&self)? <= max_bytes {
+            return Ok(vec![self]);
+        }
+
         match self {
-            Self::EpochEndingLedgerInfos(_) => "epoch_ending_ledger_infos",
-            Self::NewTransactionOutputsWithProof(_) => "new_transaction_outputs_with_proof",
-            Self::NewTransactionsWithProof(_) => "new_transactions_with_proof",
-            Self::NumberOfStatesAtVersion(_) => "number_of_states_at_version",
-            Self::ServerProtocolVersion(_) => "server_protocol_version",
-            Self::StateValueChunkWithProof(_) => "state_value_chunk_with_proof",
-            Self::StorageServerSummary(_) => "storage_server_summary",
-            Self::TransactionOutputsWithProof(_) => "transaction_outputs_with_proof",
-            Self::TransactionsWithProof(_) => "transactions_with_proof",
-            Self::NewTransactionsOrOutputsWithProof(_) => "new_transactions_or_outputs_with_proof",
-            Self::TransactionsOrOutputsWithProof(_) => "transactions_or_outputs_with_proof",
+            Self::TransactionsWithProof(transaction_list) => {
+                let first_version = match transaction_list.first_transaction_version {
+                    Some(first_version) => first_version,
+                    None => return Ok(vec![Self::TransactionsWithProof(transaction_list)]),
+                };
+                check_transaction_infos_match(
+                    transaction_list.transactions.len(),
+                    &transaction_list.proof,
+                )?;
+                split_into_pieces(transaction_list.transactions.len(), max_bytes, |range| {
+                    get_transaction_list_piece(&transaction_list, first_version, range)
+                        .map(Self::TransactionsWithProof)
+                })
+            },
+            Self::TransactionOutputsWithProof(output_list) => {
+                let first_version = match output_list.first_transaction_output_version {
+                    Some(first_version) => first_version,
+                    None => return Ok(vec![Self::TransactionOutputsWithProof(output_list)]),
+                };
+                check_transaction_infos_match(
+                    output_list.transactions_and_outputs.len(),
+                    &output_list.proof,
+                )?;
+                split_into_pieces(
+                    output_list.transactions_and_outputs.len(),
+                    max_bytes,
+                    |range| {
+                        get_output_list_piece(&output_list, first_version, range)
+                            .map(Self::TransactionOutputsWithProof)
+                    },
+                )
+            },
+            data_response => Ok(vec![data_response]),
+        }
+    }
+
+    /// Returns a copy of this response with its list truncated to (at most)
+    /// `max_items` items (e.g., for load shedding). The retained prefix of
+    /// any transaction or output list is given a valid range proof (relative
+    /// to the same ledger info as the original). Non-list variants are cloned
+    /// unchanged. Returns an error if a state value chunk must be truncated,
+    /// as its sparse merkle range proof cannot be recomputed from the chunk.
+    pub fn truncated_to(&self, max_items: usize) -> Result<DataResponse, Error> {
+        let truncate_transactions_or_outputs =
+            |(transaction_list, output_list): &TransactionOrOutputListWithProof| {
+                let transaction_list = transaction_list
+                    .as_ref()
+                    .map(|transaction_list| truncate_transaction_list(transaction_list, max_items))
+                    .transpose()?;
+                let output_list = output_list
+                    .as_ref()
+                    .map(|output_list| truncate_output_list(output_list, max_items))
+                    .transpose()?;
+                Ok::<_, Error>((transaction_list, output_list))
+            };
+
+        let truncated_response = match self {
+            Self::EpochEndingLedgerInfos(epoch_change_proof) => Self::EpochEndingLedgerInfos(
+                truncate_epoch_change_proof(epoch_change_proof, max_items),
+            ),
+            Self::NewTransactionOutputsWithProof((output_list, ledger_info)) => {
+                Self::NewTransactionOutputsWithProof((
+                    truncate_output_list(output_list, max_items)?,
+                    ledger_info.clone(),
+                ))
+            },
+            Self::NewTransactionsWithProof((transaction_list, ledger_info)) => {
+                Self::NewTransactionsWithProof((
+                    truncate_transaction_list(transaction_list, max_items)?,
+                    ledger_info.clone(),
+                ))
+            },
+            Self::StateValueChunkWithProof(state_value_chunk) => {
+                if state_value_chunk.raw_values.len() > max_items {
+                    return Err(Error::UnexpectedResponseError(
+                        "Unable to truncate the state value chunk! The proof can't be recomputed."
+                            .into(),
+                    ));
+                }
+                self.clone()
+            },
+            Self::TransactionOutputsWithProof(output_list) => {
+                Self::TransactionOutputsWithProof(truncate_output_list(output_list, max_items)?)
+            },
+            Self::TransactionsWithProof(transaction_list) => {
+                Self::TransactionsWithProof(truncate_transaction_list(transaction_list, max_items)?)
+            },
+            Self::NewTransactionsOrOutputsWithProof((transaction_or_output_list, ledger_info)) => {
+                Self::NewTransactionsOrOutputsWithProof((
+                    truncate_transactions_or_outputs(transaction_or_output_list)?,
+                    ledger_info.clone(),
+                ))
+            },
+            Self::TransactionsOrOutputsWithProof(transaction_or_output_list) => {
+                Self::TransactionsOrOutputsWithProof(truncate_transactions_or_outputs(
+                    transaction_or_output_list,
+                )?)
+            },
+            Self::EpochChangeProofBetween(MinimalEpochChangeProof(epoch_change_proof)) => {
+                Self::EpochChangeProofBetween(MinimalEpochChangeProof(truncate_epoch_change_proof(
+                    epoch_change_proof,
+                    max_items,
+                )))
+            },
+            data_response => data_response.clone(),
+        };
+        Ok(truncated_response)
+    }
+}
+
+/// Returns a copy of the epoch change proof with (at most) `max_items` ledger
+/// infos. If any ledger infos are dropped, the proof is marked as having more.
+fn truncate_epoch_change_proof(
+    epoch_change_proof: &EpochChangeProof,
+    max_items: usize,
+) -> EpochChangeProof {
+    let ledger_infos = &epoch_change_proof.ledger_info_with_sigs;
+    if ledger_infos.len() <= max_items {
+        return epoch_change_proof.clone();
+    }
+    EpochChangeProof::new(ledger_infos[..max_items].to_vec(), true)
+}
+
+/// Returns a copy of the transaction list with (at most) `max_items` items
+fn truncate_transaction_list(
+    transaction_list: &TransactionListWithProof,
+    max_items: usize,
+) -> Result<TransactionListWithProof, Error> {
+    let num_transactions = transaction_list.transactions.len();
+    if num_transactions <= max_items {
+        return Ok(transaction_list.clone());
+    } else if max_items == 0 {
+        return Ok(TransactionListWithProof::new_empty());
+    }
+
+    let first_version = get_first_version_to_truncate(transaction_list.first_transaction_version)?;
+    check_transaction_infos_match(num_transactions, &transaction_list.proof)?;
+    get_transaction_list_piece(transaction_list, first_version, 0..max_items)
+}
+
+/// Returns a copy of the output list with (at most) `max_items` items
+fn truncate_output_list(
+    output_list: &TransactionOutputListWithProof,
+    max_items: usize,
+) -> Result<TransactionOutputListWithProof, Error> {
+    let num_outputs = output_list.transactions_and_outputs.len();
+    if num_outputs <= max_items {
+        return Ok(output_list.clone());
+    } else if max_items == 0 {
+        return Ok(TransactionOutputListWithProof::new_empty());
+    }
+
+    let first_version =
+        get_first_version_to_truncate(output_list.first_transaction_output_version)?;
+    check_transaction_infos_match(num_outputs, &output_list.proof)?;
+    get_output_list_piece(output_list, first_version, 0..max_items)
+}
+
+/// Returns the first version of a (non-empty) list that is being truncated
+fn get_first_version_to_truncate(first_version: Option<Version>) -> Result<Version, Error> {
+    first_version.ok_or_else(|| {
+        Error::UnexpectedResponseError(
+            "Unable to truncate the list! The first version is missing.".into(),
+        )
+    })
+}
+
+/// Returns the given sub-range of the transaction list (where the first
+/// transaction is at `first_version`), with a valid range proof
+fn get_transaction_list_piece(
+    transaction_list: &TransactionListWithProof,
+    first_version: Version,
+    range: Range<usize>,
+) -> Result<TransactionListWithProof, Error> {
+    let proof = get_transaction_info_sub_proof(&transaction_list.proof, first_version, &range)?;
+    let events = transaction_list
+        .events
+        .as_ref()
+        .map(|events| events[range.clone()].to_vec());
+    Ok(TransactionListWithProof::new(
+        transaction_list.transactions[range.clone()].to_vec(),
+        events,
+        Some(first_version + range.start as u64),
+        proof,
+    ))
+}
+
+/// Returns the given sub-range of the output list (where the first output
+/// is at `first_version`), with a valid range proof
+fn get_output_list_piece(
+    output_list: &TransactionOutputListWithProof,
+    first_version: Version,
+    range: Range<usize>,
+) -> Result<TransactionOutputListWithProof, Error> {
+    let proof = get_transaction_info_sub_proof(&output_list.proof, first_version, &range)?;
+    Ok(TransactionOutputListWithProof::new(
+        output_list.transactions_and_outputs[range.clone()].to_vec(),
+        Some(first_version + range.start as u64),
+        proof,
+    ))
+}
+
+/// Verifies that none of the given (batched) responses is itself a batch,
+/// i.e., that batches are limited to a depth of one.
+fn check_batch_depth(data_responses: &[DataResponse]) -> Result<(), Error> {
+    if data_responses
+        .iter()
+        .any(|data_response| matches!(data_response, DataResponse::Batch(_)))
+    {
+        return Err(Error::UnexpectedResponseError(
+            "Nested batch responses are not supported!".into(),
+        ));
+    }
+    Ok(())
+}
+
+/// Deserializes the responses held by a batch response, and verifies the batch
+/// depth (see `check_batch_depth`). This ensures that nested batches are also
+/// rejected on the wire, and not only when the batch is created locally.
+fn deserialize_batch<'de, D>(deserializer: D) -> Result<Vec<DataResponse>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let data_responses = Vec::<DataResponse>::deserialize(deserializer)?;
+    check_batch_depth(&data_responses).map_err(serde::de::Error::custom)?;
+    Ok(data_responses)
+}
+
+/// Returns the serialized size (in bytes) of the given data response
+fn get_serialized_size(data_response: &DataResponse) -> Result<usize, Error> {
+    bcs::serialized_size(data_response)
+        .map_err(|error| Error::UnexpectedErrorEncountered(error.to_string()))
+}
+
+/// Splits a list of `num_items` items into adjacent pieces (created using
+/// `create_piece`) that each serialize to at most `max_bytes`. Each piece is
+/// grown as large as possible, using a binary search over the piece length.
+fn split_into_pieces<F>(
+    num_items: usize,
+    max_bytes: usize,
+    create_piece: F,
+) -> Result<Vec<DataResponse>, Error>
+where
+    F: Fn(Range<usize>) -> Result<DataResponse, Error>,
+{
+    let mut pieces = vec![];
+    let mut piece_start = 0;
+    while piece_start < num_items {
+        // Find the largest piece (starting at the current item) that fits
+        let mut largest_piece = None;
+        let (mut lowest_end, mut highest_end) = (piece_start + 1, num_items);
+        while lowest_end <= highest_end {
+            let piece_end = lowest_end + (highest_end - lowest_end) / 2;
+            let piece = create_piece(piece_start..piece_end)?;
+            if get_serialized_size(&piece)? <= max_bytes {
+                largest_piece = Some((piece_end, piece));
+                lowest_end = piece_end + 1;
+            } else {
+                highest_end = piece_end - 1;
+            }
+        }
+
+        let (piece_end, piece) = largest_piece.ok_or_else(|| {
+            Error::UnexpectedErrorEncountered(format!(
+                "Unable to split the response! The item at index {} exceeds the max bytes: {}",
+                piece_start, max_bytes
+            ))
+        })?;
+        pieces.push(piece);
+        piece_start = piece_end;
+    }
+    Ok(pieces)
+}
+
+/// Verifies that the proof holds a transaction info for each of the items
+fn check_transaction_infos_match(
+    num_items: usize,
+    proof: &TransactionInfoListWithProof,
+) -> Result<(), Error> {
+    if proof.transaction_infos.len() == num_items {
+        Ok(())
+    } else {
+        Err(Error::UnexpectedResponseError(format!(
+            "The number of transaction infos doesn't match the number of items! \
+            Transaction infos: {}, items: {}",
+            proof.transaction_infos.len(),
+            num_items
+        )))
+    }
+}
+
+/// Adds the version range and item count of the transaction list to the log fields
+fn add_transaction_list_log_fields(
+    log_fields: &mut Vec<(&'static str, String)>,
+    transaction_list: &TransactionListWithProof,
+) {
+    add_version_range_log_fields(
+        log_fields,
+        transaction_list.first_transaction_version,
+        transaction_list.transactions.len(),
+    );
+    log_fields.push((
+        "item_count",
+        transaction_list.transactions.len().to_string(),
+    ));
+}
+
+/// Adds the version range and item count of the output list to the log fields
+fn add_output_list_log_fields(
+    log_fields: &mut Vec<(&'static str, String)>,
+    output_list: &TransactionOutputListWithProof,
+) {
+    let num_outputs = output_list.transactions_and_outputs.len();
+    add_version_range_log_fields(
+        log_fields,
+        output_list.first_transaction_output_version,
+        num_outputs,
+    );
+    log_fields.push(("item_count", num_outputs.to_string()));
+}
+
+/// Adds the (inclusive) version range of the list to the log fields (if the
+/// list is non-empty and the first version is known).
+fn add_version_range_log_fields(
+    log_fields: &mut Vec<(&'static str, String)>,
+    first_version: Option<Version>,
+    num_items: usize,
+) {
+    if let Some(first_version) = first_version {
+        if num_items > 0 {
+            let last_version = first_version.saturating_add(num_items as u64 - 1);
+            log_fields.push((
+                "version_range",
+                format!("[{}, {}]", first_version, last_version),
+            ));
         }
     }
 }
 
+/// Verifies that the given epoch change proof spans exactly the requested
+/// epoch range, i.e., that the first and last ledger infos in the proof end
+/// the lowest and highest requested epochs, respectively. Note: this should
+/// be checked before the proof is trusted (e.g., a short proof may have been
+/// truncated by the server, in which case the remainder must be requested).
+pub fn validate_epoch_proof_range(
+    proof: &EpochChangeProof,
+    requested: &CompleteDataRange<Epoch>,
+) -> Result<(), Error> {
+    let get_epoch = |ledger_info: Option<&LedgerInfoWithSignatures>| {
+        ledger_info.map(|ledger_info| ledger_info.ledger_info().epoch())
+    };
+    let first_epoch = get_epoch(proof.ledger_info_with_sigs.first());
+    let last_epoch = get_epoch(proof.ledger_info_with_sigs.last());
+    if first_epoch == Some(requested.lowest()) && last_epoch == Some(requested.highest()) {
+        Ok(())
+    } else {
+        Err(Error::UnexpectedResponseError(format!(
+            "The epoch change proof doesn't span the requested epochs! Requested: {:?}, \
+            found first epoch: {:?}, last epoch: {:?}",
+            requested, first_epoch, last_epoch
+        )))
+    }
+}
+
+/// Decodes the given responses (each of which must hold a transaction list)
+/// and concatenates the lists into a single list with a combined proof. The
+/// lists must be ordered, and each list must start immediately after the
+/// previous list ends (i.e., there can be no gaps or overlaps).
+pub fn try_concat_transactions(
+    responses: Vec<StorageServiceResponse>,
+) -> Result<TransactionListWithProof, Error> {
+    let mut concatenated_list = TransactionListWithProof::new_empty();
+    for response in responses {
+        let transaction_list = TransactionListWithProof::try_from(response)?;
+        concatenated_list = merge_transaction_lists(concatenated_list, transaction_list)?;
+    }
+    Ok(concatenated_list)
+}
+
+/// Merges the two adjacent transaction lists into a single list
+fn merge_transaction_lists(
+    first: TransactionListWithProof,
+    second: TransactionListWithProof,
+) -> Result<TransactionListWithProof, Error> {
+    // If either list is empty, there's nothing to merge
+    if first.transactions.is_empty() {
+        return Ok(second);
+    } else if second.transactions.is_empty() {
+        return Ok(first);
+    }
+
+    // Verify the lists are adjacent
+    check_lists_adjacent(
+        first.first_transaction_version,
+        first.transactions.len(),
+        second.first_transaction_version,
+    )?;
+
+    // Merge the events
+    let events = match (first.events, second.events) {
+        (Some(mut first_events), Some(second_events)) => {
+            first_events.extend(second_events);
+            Some(first_events)
+        },
+        (None, None) => None,
+        _ => {
+            return Err(Error::UnexpectedResponseError(
+                "Unable to merge transaction lists with and without events!".into(),
+            ))
+        },
+    };
+
+    // Merge the transactions and proofs
+    let mut transactions = first.transactions;
+    transactions.extend(second.transactions);
+    let proof = merge_transaction_info_proofs(first.proof, second.proof);
+
+    Ok(TransactionListWithProof::new(
+        transactions,
+        events,
+        first.first_transaction_version,
+        proof,
+    ))
+}
+
+/// Merges the two adjacent transaction output lists into a single list
+fn merge_transaction_output_lists(
+    first: TransactionOutputListWithProof,
+    second: TransactionOutputListWithProof,
+) -> Result<TransactionOutputListWithProof, Error> {
+    // If either list is empty, there's nothing to merge
+    if first.transactions_and_outputs.is_empty() {
+        return Ok(second);
+    } else if second.transactions_and_outputs.is_empty() {
+        return Ok(first);
+    }
+
+    // Verify the lists are adjacent
+    check_lists_adjacent(
+        first.first_transaction_output_version,
+        first.transactions_and_outputs.len(),
+        second.first_transaction_output_version,
+    )?;
+
+    // Merge the transactions, outputs and proofs
+    let mut transactions_and_outputs = first.transactions_and_outputs;
+    transactions_and_outputs.extend(second.transactions_and_outputs);
+    let proof = merge_transaction_info_proofs(first.proof, second.proof);
+
+    Ok(TransactionOutputListWithProof::new(
+        transactions_and_outputs,
+        first.first_transaction_output_version,
+        proof,
+    ))
+}
+
+/// Returns `Ok(())` iff the second list starts immediately after the first list ends
+fn check_lists_adjacent(
+    first_version: Option<Version>,
+    first_list_length: usize,
+    second_version: Option<Version>,
+) -> Result<(), Error> {
+    let expected_second_version =
+        first_version.and_then(|version| version.checked_add(first_list_length as u64));
+    if second_version.is_some() && second_version == expected_second_version {
+        Ok(())
+    } else {
+        Err(Error::UnexpectedResponseError(format!(
+            "Unable to merge non-adjacent lists! Expected the second list to start at: {:?}, found: {:?}",
+            expected_second_version, second_version
+        )))
+    }
+}
+
+/// Combines the proofs of two adjacent transaction info lists. The merged
+/// range proof uses the left siblings of the first proof and the right
+/// siblings of the second proof, as the inner siblings are now covered
+/// by the merged range itself.
+fn merge_transaction_info_proofs(
+    first: TransactionInfoListWithProof,
+    second: TransactionInfoListWithProof,
+) -> TransactionInfoListWithProof {
+    let ledger_info_to_transaction_infos_proof = TransactionAccumulatorRangeProof::new(
+        first
+            .ledger_info_to_transaction_infos_proof
+            .left_siblings()
+            .clone(),
+        second
+            .ledger_info_to_transaction_infos_proof
+            .right_siblings()
+            .clone(),
+    );
+    let mut transaction_infos = first.transaction_infos;
+    transaction_infos.extend(second.transaction_infos);
+
+    TransactionInfoListWithProof::new(ledger_info_to_transaction_infos_proof, transaction_infos)
+}
+
+/// Returns the proof for the given sub-range of the transaction infos in the
+/// given proof (where the first transaction info is at `first_version`). The
+/// range proof is recomputed by walking up the accumulator from the leaves
+/// (as done by the proof verification). At each level, the siblings of the
+/// sub-range are either nodes of the full range, or siblings of the full range.
+fn get_transaction_info_sub_proof(
+    proof: &TransactionInfoListWithProof,
+    first_version: Version,
+    sub_range: &Range<usize>,
+) -> Result<TransactionInfoListWithProof, Error> {
+    let missing_sibling_error = || {
+        Error::UnexpectedResponseError(
+            "Unable to compute the sub-range proof! The range proof is missing siblings.".into(),
+        )
+    };
+
+    let range_proof = &proof.ledger_info_to_transaction_infos_proof;
+    let mut left_siblings = range_proof.left_siblings().iter().peekable();
+    let mut right_siblings = range_proof.right_siblings().iter().peekable();
+    let mut sub_left_siblings = vec![];
+    let mut sub_right_siblings = vec![];
+
+    // Walk up the accumulator (one level at a time) until we reach the root
+    let mut first_position = first_version;
+    let mut current_hashes: Vec<HashValue> = proof
+        .transaction_infos
+        .iter()
+        .map(CryptoHash::hash)
+        .collect();
+    let mut sub_first_position = first_version + sub_range.start as u64;
+    let mut sub_last_position = first_version + sub_range.end as u64 - 1;
+    while current_hashes.len() > 1
+        || left_siblings.peek().is_some()
+        || right_siblings.peek().is_some()
+    {
+        // Identify the siblings of the full range at this level
+        let last_position = first_position + current_hashes.len() as u64 - 1;
+        let left_sibling = if first_position % 2 == 1 {
+            Some(*left_siblings.next().ok_or_else(missing_sibling_error)?)
+        } else {
+            None
+        };
+        let right_sibling = if last_position % 2 == 0 {
+            Some(*right_siblings.next().ok_or_else(missing_sibling_error)?)
+        } else {
+            None
+        };
+
+        // Identify the siblings of the sub-range at this level
+        let get_node = |position: u64| {
+            if position < first_position {
+                left_sibling
+            } else if position > last_position {
+                right_sibling
+            } else {
+                Some(current_hashes[(position - first_position) as usize])
+            }
+        };
+        if sub_first_position % 2 == 1 {
+            sub_left_siblings
+                .push(get_node(sub_first_position - 1).ok_or_else(missing_sibling_error)?);
+        }
+        if sub_last_position % 2 == 0 {
+            sub_right_siblings
+                .push(get_node(sub_last_position + 1).ok_or_else(missing_sibling_error)?);
+        }
+
+        // Compute the parent hashes (for the next level)
+        let level_hashes: Vec<_> = left_sibling
+            .into_iter()
+            .chain(current_hashes)
+            .chain(right_sibling)
+            .collect();
+        current_hashes = level_hashes
+            .chunks_exact(2)
+            .map(|children| {
+                TransactionAccumulatorInternalNode::new(children[0], children[1]).hash()
+            })
+            .collect();
+        first_position /= 2;
+        sub_first_position /= 2;
+        sub_last_position /= 2;
+    }
+
+    Ok(TransactionInfoListWithProof::new(
+        TransactionAccumulatorRangeProof::new(sub_left_siblings, sub_right_siblings),
+        proof.transaction_infos[sub_range.clone()].to_vec(),
+    ))
+}
+
 impl Display for DataResponse {
     fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
         // To prevent log spamming, we only display storage response data for summaries
@@ -162,14 +1473,123 @@ impl Display for DataResponse {
             DataResponse::StorageServerSummary(storage_summary) => {
                 format!("{:?}", storage_summary)
             },
-            _ => "...".into(),
+            _ => "...".into(),
+        };
+        write!(
+            f,
+            "Storage service response: {}, data: {}",
+            self.get_label(),
+            data
+        )
+    }
+}
+
+/// A decoded data response (see `StorageServiceResponse::decode`). This
+/// mirrors `DataResponse`, but unpacks the tuple payloads into named fields
+/// (and transaction or output lists into `TxnsOrOutputs`), so downstream
+/// code can match on the response once.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[allow(clippy::large_enum_variant)]
+pub enum DecodedResponse {
+    EpochEndingLedgerInfos(EpochChangeProof),
+    NewTransactionOutputsWithProof {
+        output_list: TransactionOutputListWithProof,
+        target_ledger_info: LedgerInfoWithSignatures,
+    },
+    NewTransactionsWithProof {
+        transaction_list: TransactionListWithProof,
+        target_ledger_info: LedgerInfoWithSignatures,
+    },
+    NumberOfStatesAtVersion(u64),
+    ServerProtocolVersion(ServerProtocolVersion),
+    StateValueChunkWithProof(StateValueChunkWithProof),
+    StorageServerSummary(StorageServerSummary),
+    TransactionOutputsWithProof(TransactionOutputListWithProof),
+    TransactionsWithProof(TransactionListWithProof),
+    NewTransactionsOrOutputsWithProof {
+        transactions_or_outputs: TxnsOrOutputs,
+        target_ledger_info: LedgerInfoWithSignatures,
+    },
+    TransactionsOrOutputsWithProof(TxnsOrOutputs),
+    TransactionRangeProof(TransactionAccumulatorRangeProof),
+    TransactionByHashWithProof(Option<TransactionWithProof>),
+    LatestLedgerInfo(LedgerInfoWithSignatures),
+    EpochChangeProofBetween(MinimalEpochChangeProof),
+    Batch(Vec<DecodedResponse>),
+}
+
+impl TryFrom<DataResponse> for DecodedResponse {
+    type Error = crate::responses::Error;
+
+    fn try_from(data_response: DataResponse) -> crate::Result<Self, Self::Error> {
+        let decoded_response = match data_response {
+            DataResponse::EpochEndingLedgerInfos(epoch_change_proof) => {
+                DecodedResponse::EpochEndingLedgerInfos(epoch_change_proof)
+            },
+            DataResponse::NewTransactionOutputsWithProof((output_list, target_ledger_info)) => {
+                DecodedResponse::NewTransactionOutputsWithProof {
+                    output_list,
+                    target_ledger_info,
+                }
+            },
+            DataResponse::NewTransactionsWithProof((transaction_list, target_ledger_info)) => {
+                DecodedResponse::NewTransactionsWithProof {
+                    transaction_list,
+                    target_ledger_info,
+                }
+            },
+            DataResponse::NumberOfStatesAtVersion(number_of_states) => {
+                DecodedResponse::NumberOfStatesAtVersion(number_of_states)
+            },
+            DataResponse::ServerProtocolVersion(server_protocol_version) => {
+                DecodedResponse::ServerProtocolVersion(server_protocol_version)
+            },
+            DataResponse::StateValueChunkWithProof(state_value_chunk) => {
+                DecodedResponse::StateValueChunkWithProof(state_value_chunk)
+            },
+            DataResponse::StorageServerSummary(storage_server_summary) => {
+                DecodedResponse::StorageServerSummary(storage_server_summary)
+            },
+            DataResponse::TransactionOutputsWithProof(output_list) => {
+                DecodedResponse::TransactionOutputsWithProof(output_list)
+            },
+            DataResponse::TransactionsWithProof(transaction_list) => {
+                DecodedResponse::TransactionsWithProof(transaction_list)
+            },
+            DataResponse::NewTransactionsOrOutputsWithProof((
+                transaction_or_output_list,
+                target_ledger_info,
+            )) => DecodedResponse::NewTransactionsOrOutputsWithProof {
+                transactions_or_outputs: transaction_or_output_list.into_either()?,
+                target_ledger_info,
+            },
+            DataResponse::TransactionsOrOutputsWithProof(transaction_or_output_list) => {
+                DecodedResponse::TransactionsOrOutputsWithProof(
+                    transaction_or_output_list.into_either()?,
+                )
+            },
+            DataResponse::TransactionRangeProof(range_proof) => {
+                DecodedResponse::TransactionRangeProof(range_proof)
+            },
+            DataResponse::TransactionByHashWithProof(transaction_with_proof) => {
+                DecodedResponse::TransactionByHashWithProof(transaction_with_proof)
+            },
+            DataResponse::LatestLedgerInfo(ledger_info) => {
+                DecodedResponse::LatestLedgerInfo(ledger_info)
+            },
+            DataResponse::EpochChangeProofBetween(epoch_change_proof) => {
+                DecodedResponse::EpochChangeProofBetween(epoch_change_proof)
+            },
+            DataResponse::Batch(data_responses) => {
+                check_batch_depth(&data_responses)?;
+                let decoded_responses = data_responses
+                    .into_iter()
+                    .map(DecodedResponse::try_from)
+                    .collect::<Result<_, _>>()?;
+                DecodedResponse::Batch(decoded_responses)
+            },
         };
-        write!(
-            f,
-            "Storage service response: {}, data: {}",
-            self.get_label(),
-            data
-        )
+        Ok(decoded_response)
     }
 }
 
@@ -203,6 +1623,21 @@ impl TryFrom<StorageServiceResponse> for EpochChangeProof {
     }
 }
 
+impl TryFrom<StorageServiceResponse> for MinimalEpochChangeProof {
+    type Error = crate::responses::Error;
+
+    fn try_from(response: StorageServiceResponse) -> crate::Result<Self, Self::Error> {
+        let data_response = response.get_data_response()?;
+        match data_response {
+            DataResponse::EpochChangeProofBetween(inner) => Ok(inner),
+            _ => Err(Error::UnexpectedResponseError(format!(
+                "expected epoch_change_proof_between, found {}",
+                data_response.get_label()
+            ))),
+        }
+    }
+}
+
 impl TryFrom<StorageServiceResponse>
     for (TransactionOutputListWithProof, LedgerInfoWithSignatures)
 {
@@ -342,6 +1777,69 @@ impl TryFrom<StorageServiceResponse> for TransactionOrOutputListWithProof {
     }
 }
 
+impl TryFrom<StorageServiceResponse> for TransactionAccumulatorRangeProof {
+    type Error = crate::responses::Error;
+
+    fn try_from(response: StorageServiceResponse) -> crate::Result<Self, Self::Error> {
+        let data_response = response.get_data_response()?;
+        match data_response {
+            DataResponse::TransactionRangeProof(inner) => Ok(inner),
+            _ => Err(Error::UnexpectedResponseError(format!(
+                "expected transaction_range_proof, found {}",
+                data_response.get_label()
+            ))),
+        }
+    }
+}
+
+impl TryFrom<StorageServiceResponse> for Option<TransactionWithProof> {
+    type Error = crate::responses::Error;
+
+    fn try_from(response: StorageServiceResponse) -> crate::Result<Self, Self::Error> {
+        let data_response = response.get_data_response()?;
+        match data_response {
+            DataResponse::TransactionByHashWithProof(inner) => Ok(inner),
+            _ => Err(Error::UnexpectedResponseError(format!(
+                "expected transaction_by_hash_with_proof, found {}",
+                data_response.get_label()
+            ))),
+        }
+    }
+}
+
+impl TryFrom<StorageServiceResponse> for Vec<DataResponse> {
+    type Error = crate::responses::Error;
+
+    fn try_from(response: StorageServiceResponse) -> crate::Result<Self, Self::Error> {
+        let data_response = response.get_data_response()?;
+        match data_response {
+            DataResponse::Batch(inner) => {
+                check_batch_depth(&inner)?;
+                Ok(inner)
+            },
+            _ => Err(Error::UnexpectedResponseError(format!(
+                "expected batch, found {}",
+                data_response.get_label()
+            ))),
+        }
+    }
+}
+
+impl TryFrom<StorageServiceResponse> for LedgerInfoWithSignatures {
+    type Error = crate::responses::Error;
+
+    fn try_from(response: StorageServiceResponse) -> crate::Result<Self, Self::Error> {
+        let data_response = response.get_data_response()?;
+        match data_response {
+            DataResponse::LatestLedgerInfo(inner) => Ok(inner),
+            _ => Err(Error::UnexpectedResponseError(format!(
+                "expected latest_ledger_info, found {}",
+                data_response.get_label()
+            ))),
+        }
+    }
+}
+
 /// The protocol version run by this server. Clients request this first to
 /// identify what API calls and data requests the server supports.
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
@@ -362,6 +1860,391 @@ impl StorageServerSummary {
     pub fn can_service(&self, request: &StorageServiceRequest) -> bool {
         self.protocol_metadata.can_service(request) && self.data_summary.can_service(request)
     }
+
+    /// Returns true iff at least one of the given requests can be serviced.
+    /// This short-circuits on the first serviceable request, and returns
+    /// false if no requests are given.
+    pub fn can_service_any(&self, requests: &[StorageServiceRequest]) -> bool {
+        requests.iter().any(|request| self.can_service(request))
+    }
+
+    /// Returns true iff all of the given requests can be serviced. This
+    /// short-circuits on the first unserviceable request, and returns
+    /// true if no requests are given.
+    pub fn can_service_all(&self, requests: &[StorageServiceRequest]) -> bool {
+        requests.iter().all(|request| self.can_service(request))
+    }
+
+    /// Returns true iff this summary and the other summary are interchangeable
+    /// for the given request, i.e., both can service the request, or neither
+    /// can. This is useful for bucketing peers (e.g., for connection pooling).
+    pub fn serves_same_as(
+        &self,
+        other: &StorageServerSummary,
+        request: &StorageServiceRequest,
+    ) -> bool {
+        self.can_service(request) == other.can_service(request)
+    }
+
+    /// Returns true iff the synced ledger info in the summary was created
+    /// within `max_skew_secs` of the given time (in either direction). As the
+    /// ledger info timestamp is signed by the validators (and not stamped by
+    /// the peer), this guards against peers that replay old summaries. Note:
+    /// the timestamp should only be trusted once the ledger info signatures
+    /// have been verified. Summaries without a synced ledger info are never
+    /// considered fresh.
+    pub fn is_fresh(&self, now_unix_secs: u64, max_skew_secs: u64) -> bool {
+        match &self.data_summary.synced_ledger_info {
+            Some(synced_ledger_info) => {
+                let synced_at_unix_secs =
+                    Duration::from_micros(synced_ledger_info.ledger_info().timestamp_usecs())
+                        .as_secs();
+                now_unix_secs.abs_diff(synced_at_unix_secs) <= max_skew_secs
+            },
+            None => false,
+        }
+    }
+
+    /// Advances the summary in place to account for the newly synced ledger
+    /// info and the newly stored data (i.e., without recomputing the entire
+    /// summary). Each held range is extended to the highest value of the
+    /// corresponding newly stored range. Note: the newly stored ranges must
+    /// be contiguous with the ranges already held (otherwise the summary
+    /// will no longer describe complete data).
+    pub fn advance(
+        &mut self,
+        new_synced: LedgerInfoWithSignatures,
+        newly_stored: DataSummaryDelta,
+    ) {
+        let data_summary = &mut self.data_summary;
+        data_summary.synced_ledger_info = Some(new_synced);
+        extend_range(
+            &mut data_summary.epoch_ending_ledger_infos,
+            newly_stored.epoch_ending_ledger_infos,
+        );
+        extend_range(&mut data_summary.states, newly_stored.states);
+        extend_range(&mut data_summary.transactions, newly_stored.transactions);
+        extend_range(
+            &mut data_summary.transaction_outputs,
+            newly_stored.transaction_outputs,
+        );
+    }
+
+    /// Returns a compact form of the summary that drops the (large) synced
+    /// ledger info. This is useful for tracking the summaries of many peers.
+    pub fn compacted(&self) -> CompactStorageServerSummary {
+        CompactStorageServerSummary {
+            protocol_metadata: self.protocol_metadata.clone(),
+            data_summary: self.data_summary.compacted(),
+        }
+    }
+}
+
+/// Storage server summaries are ordered by freshness, i.e., by the version of
+/// the synced ledger info (where summaries without a synced ledger info are
+/// the least fresh). This allows peers to be ranked by sorting their summaries.
+/// Summaries at the same synced version are ordered by their protocol metadata
+/// and data ranges, which is arbitrary, but cheap. Only if all of these are
+/// equal (but the synced ledger infos differ, e.g., in their signatures) are
+/// the summaries ordered by the BCS encoding of their synced ledger infos. This
+/// keeps the order total and consistent with `Eq`.
+impl Ord for StorageServerSummary {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let range_key = |range: &Option<CompleteDataRange<u64>>| {
+            range.map(|range| (range.lowest(), range.highest()))
+        };
+        let cheap_key = |summary: &Self| {
+            let protocol_metadata = &summary.protocol_metadata;
+            let data_summary = &summary.data_summary;
+            (
+                data_summary.get_synced_ledger_info_version(),
+                protocol_metadata.max_epoch_chunk_size,
+                protocol_metadata.max_state_chunk_size,
+                protocol_metadata.max_transaction_chunk_size,
+                protocol_metadata.max_transaction_output_chunk_size,
+                range_key(&data_summary.epoch_ending_ledger_infos),
+                range_key(&data_summary.states),
+                range_key(&data_summary.transactions),
+                range_key(&data_summary.transaction_outputs),
+            )
+        };
+        cheap_key(self).cmp(&cheap_key(other)).then_with(|| {
+            let synced_ledger_info = &self.data_summary.synced_ledger_info;
+            let other_synced_ledger_info = &other.data_summary.synced_ledger_info;
+            if synced_ledger_info == other_synced_ledger_info {
+                Ordering::Equal
+            } else {
+                bcs::to_bytes(synced_ledger_info)
+                    .ok()
+                    .cmp(&bcs::to_bytes(other_synced_ledger_info).ok())
+            }
+        })
+    }
+}
+
+impl PartialOrd for StorageServerSummary {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// A compact form of the storage server summary (see `StorageServerSummary`).
+/// It answers `can_service` identically, but uses much less memory.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct CompactStorageServerSummary {
+    pub protocol_metadata: ProtocolMetadata,
+    pub data_summary: CompactDataSummary,
+}
+
+impl CompactStorageServerSummary {
+    pub fn can_service(&self, request: &StorageServiceRequest) -> bool {
+        self.protocol_metadata.can_service(request) && self.data_summary.can_service(request)
+    }
+}
+
+/// A policy for selecting the peer (i.e., the storage server summary) to
+/// send a request to, from the summaries of all candidate peers.
+pub trait PeerSelector {
+    /// Returns the selected summary, or `None` if no candidate is suitable
+    fn select<'a>(
+        &mut self,
+        candidates: &'a [StorageServerSummary],
+        request: &StorageServiceRequest,
+    ) -> Option<&'a StorageServerSummary>;
+}
+
+/// A peer selector that picks the freshest peer (i.e., the peer with the
+/// highest synced version) that can service the request. Ties are broken
+/// in favour of the earliest candidate.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct FreshestCapable;
+
+impl PeerSelector for FreshestCapable {
+    fn select<'a>(
+        &mut self,
+        candidates: &'a [StorageServerSummary],
+        request: &StorageServiceRequest,
+    ) -> Option<&'a StorageServerSummary> {
+        let mut selected_summary: Option<&'a StorageServerSummary> = None;
+        for summary in candidates
+            .iter()
+            .filter(|summary| summary.can_service(request))
+        {
+            let is_fresher = match selected_summary {
+                Some(selected_summary) => {
+                    summary.data_summary.get_synced_ledger_info_version()
+                        > selected_summary
+                            .data_summary
+                            .get_synced_ledger_info_version()
+                },
+                None => true,
+            };
+            if is_fresher {
+                selected_summary = Some(summary);
+            }
+        }
+        selected_summary
+    }
+}
+
+/// Returns the (maximal) sub-ranges of the desired range that are covered by
+/// the transaction range of exactly one of the given summaries, i.e., the
+/// ranges for which a single peer is the only source. The sub-ranges are
+/// returned in ascending order.
+pub fn single_source_ranges(
+    summaries: &[StorageServerSummary],
+    desired: CompleteDataRange<Version>,
+) -> Vec<CompleteDataRange<Version>> {
+    // Identify the points at which the number of covering peers changes. Note:
+    // we use u128 so that the end of a range (i.e., highest + 1) can't overflow.
+    let mut coverage_changes: BTreeMap<u128, i64> = BTreeMap::new();
+    for summary in summaries {
+        if let Some(transactions) = &summary.data_summary.transactions {
+            let lowest = transactions.lowest().max(desired.lowest());
+            let highest = transactions.highest().min(desired.highest());
+            if lowest <= highest {
+                *coverage_changes.entry(lowest as u128).or_default() += 1;
+                *coverage_changes.entry(highest as u128 + 1).or_default() -= 1;
+            }
+        }
+    }
+
+    // Sweep over the change points and collect the ranges covered exactly once
+    let mut single_source_ranges = vec![];
+    let mut num_covering_peers = 0;
+    let mut single_source_start = None;
+    for (point, change) in coverage_changes {
+        num_covering_peers += change;
+        match (single_source_start, num_covering_peers == 1) {
+            (None, true) => single_source_start = Some(point),
+            (Some(start), false) => {
+                single_source_ranges.push(CompleteDataRange {
+                    lowest: start as Version,
+                    highest: (point - 1) as Version,
+                });
+                single_source_start = None;
+            },
+            _ => {},
+        }
+    }
+    single_source_ranges
+}
+
+/// Returns the smallest set of summaries whose transaction ranges (merged)
+/// cover the desired range, e.g., to download the range from several peers.
+/// The set is computed greedily: starting at the lowest desired version, we
+/// repeatedly select the peer covering the next missing version that reaches
+/// the highest (preferring earlier summaries on ties). The summaries are
+/// returned in ascending order of coverage. If the desired range can't be
+/// fully covered, `None` is returned.
+pub fn min_cover<'a>(
+    summaries: &'a [StorageServerSummary],
+    desired: CompleteDataRange<Version>,
+) -> Option<Vec<&'a StorageServerSummary>> {
+    // Note: we use u128 so that the next missing version can't overflow
+    let mut cover = vec![];
+    let mut next_missing_version = desired.lowest() as u128;
+    while next_missing_version <= desired.highest() as u128 {
+        let mut best_summary: Option<(&StorageServerSummary, Version)> = None;
+        for summary in summaries {
+            if let Some(transactions) = &summary.data_summary.transactions {
+                let covers_next_version = transactions.lowest() as u128 <= next_missing_version
+                    && next_missing_version <= transactions.highest() as u128;
+                let reaches_further = best_summary
+                    .map(|(_, highest)| transactions.highest() > highest)
+                    .unwrap_or(true);
+                if covers_next_version && reaches_further {
+                    best_summary = Some((summary, transactions.highest()));
+                }
+            }
+        }
+
+        // If no peer covers the next missing version, the range can't be covered
+        let (summary, highest) = best_summary?;
+        cover.push(summary);
+        next_missing_version = highest as u128 + 1;
+    }
+    Some(cover)
+}
+
+/// Returns the number of summaries (i.e., peers) that can service the given
+/// request, e.g., to decide whether to request the data from multiple peers
+/// (for verification).
+pub fn redundancy_for(
+    summaries: &[StorageServerSummary],
+    request: &StorageServiceRequest,
+) -> usize {
+    summaries
+        .iter()
+        .filter(|summary| summary.can_service(request))
+        .count()
+}
+
+/// Rewrites the given fused request (i.e., a `GetTransactionsOrOutputsWithProof`
+/// request) into a request for only the side that the summary can service, so
+/// that the response is unambiguous. Outputs are preferred if both sides can be
+/// serviced (as the server prefers to return outputs for fused requests). All
+/// other properties of the request (e.g., compression) are preserved. Returns
+/// `None` if the request isn't fused, or if neither side can be serviced.
+pub fn specialize_fused_request(
+    request: &StorageServiceRequest,
+    summary: &DataSummary,
+) -> Option<StorageServiceRequest> {
+    let fused_request = match &request.data_request {
+        GetTransactionsOrOutputsWithProof(fused_request) => fused_request,
+        _ => return None,
+    };
+
+    // Check if the outputs can be serviced
+    let outputs_request = StorageServiceRequest {
+        data_request: GetTransactionOutputsWithProof(TransactionOutputsWithProofRequest {
+            proof_version: fused_request.proof_version,
+            start_version: fused_request.start_version,
+            end_version: fused_request.end_version,
+        }),
+        ..request.clone()
+    };
+    if summary.can_service(&outputs_request) {
+        return Some(outputs_request);
+    }
+
+    // Otherwise, check if the transactions can be serviced
+    let transactions_request = StorageServiceRequest {
+        data_request: GetTransactionsWithProof(TransactionsWithProofRequest {
+            proof_version: fused_request.proof_version,
+            start_version: fused_request.start_version,
+            end_version: fused_request.end_version,
+            include_events: fused_request.include_events,
+        }),
+        ..request.clone()
+    };
+    summary
+        .can_service(&transactions_request)
+        .then_some(transactions_request)
+}
+
+/// The change in the merged data coverage advertised by a fleet of peers
+/// between two snapshots (see `fleet_progress`). Each delta is the change in
+/// the number of items covered by at least one peer (negative if the fleet
+/// regressed, e.g., due to pruning or peers leaving).
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct FleetProgress {
+    pub epoch_ending_ledger_infos_delta: i128, // The change in merged epoch ending ledger info coverage
+    pub states_delta: i128,                    // The change in merged state coverage
+    pub transactions_delta: i128,              // The change in merged transaction coverage
+}
+
+/// Returns the progress of the fleet between the given summary snapshots.
+/// Summaries don't carry a stable peer key, so peers can't be matched across
+/// snapshots. Instead, the merged (i.e., union) coverage of each snapshot is
+/// compared, which also accounts for peers joining or leaving the fleet.
+pub fn fleet_progress(
+    before: &[StorageServerSummary],
+    after: &[StorageServerSummary],
+) -> FleetProgress {
+    let coverage_delta = |get_range: fn(&DataSummary) -> Option<CompleteDataRange<u64>>| {
+        let merged_coverage = |summaries: &[StorageServerSummary]| {
+            merged_coverage_len(
+                summaries
+                    .iter()
+                    .filter_map(|summary| get_range(&summary.data_summary))
+                    .collect(),
+            ) as i128
+        };
+        merged_coverage(after) - merged_coverage(before)
+    };
+    FleetProgress {
+        epoch_ending_ledger_infos_delta: coverage_delta(|summary| {
+            summary.epoch_ending_ledger_infos
+        }),
+        states_delta: coverage_delta(|summary| summary.states),
+        transactions_delta: coverage_delta(|summary| summary.transactions),
+    }
+}
+
+/// Returns the number of items covered by at least one of the given ranges.
+/// Note: we use u128 so that the coverage of the full u64 range can't overflow.
+fn merged_coverage_len(mut ranges: Vec<CompleteDataRange<u64>>) -> u128 {
+    ranges.sort_by_key(|range| range.lowest());
+
+    let mut coverage_len = 0;
+    let mut merged_range: Option<(u64, u64)> = None;
+    for range in ranges {
+        merged_range = match merged_range {
+            Some((lowest, highest)) if range.lowest() <= highest.saturating_add(1) => {
+                Some((lowest, highest.max(range.highest())))
+            },
+            Some((lowest, highest)) => {
+                coverage_len += u128::from(highest - lowest) + 1;
+                Some((range.lowest(), range.highest()))
+            },
+            None => Some((range.lowest(), range.highest())),
+        };
+    }
+    if let Some((lowest, highest)) = merged_range {
+        coverage_len += u128::from(highest - lowest) + 1;
+    }
+    coverage_len
 }
 
 /// A summary of the protocol metadata for the storage service instance, such as
@@ -381,6 +2264,45 @@ impl ProtocolMetadata {
     pub fn can_service(&self, _request: &StorageServiceRequest) -> bool {
         true // TODO: figure out if should eventually remove this
     }
+
+    /// Returns the max chunk size the server will return for the given request
+    /// (based on the request kind). Requests that don't fetch a chunk of data
+    /// (e.g., the storage server summary) are not bounded, so `u64::MAX` is
+    /// returned for them.
+    pub fn max_chunk_size_for(&self, request: &StorageServiceRequest) -> u64 {
+        match &request.data_request {
+            GetEpochEndingLedgerInfos(_) | GetEpochChangeProofBetween(_) => {
+                self.max_epoch_chunk_size
+            },
+            GetStateValuesWithProof(_) => self.max_state_chunk_size,
+            GetNewTransactionsWithProof(_) | GetTransactionsWithProof(_) => {
+                self.max_transaction_chunk_size
+            },
+            // Transactions or outputs requests are bounded by the output chunk size
+            GetNewTransactionOutputsWithProof(_)
+            | GetTransactionOutputsWithProof(_)
+            | GetNewTransactionsOrOutputsWithProof(_)
+            | GetTransactionsOrOutputsWithProof(_) => self.max_transaction_output_chunk_size,
+            GetLatestLedgerInfo
+            | GetNumberOfStatesAtVersion(_)
+            | GetServerProtocolVersion
+            | GetStorageServerSummary
+            | GetTransactionRangeProof(_)
+            | GetTransactionByHashWithProof(_)
+            | GetBatch(_) => u64::MAX,
+        }
+    }
+
+    /// Returns true iff any of the advertised max chunk sizes decreased since
+    /// the previous metadata (e.g., the server reduced its chunk sizes under
+    /// load). If so, pending requests planned against the previous metadata
+    /// should be re-planned, as they may now be over-sized.
+    pub fn chunk_sizes_shrank_since(&self, previous: &ProtocolMetadata) -> bool {
+        self.max_epoch_chunk_size < previous.max_epoch_chunk_size
+            || self.max_state_chunk_size < previous.max_state_chunk_size
+            || self.max_transaction_chunk_size < previous.max_transaction_chunk_size
+            || self.max_transaction_output_chunk_size < previous.max_transaction_output_chunk_size
+    }
 }
 
 impl Default for ProtocolMetadata {
@@ -395,8 +2317,58 @@ impl Default for ProtocolMetadata {
     }
 }
 
+/// The labels of the pre-fusion transaction and transaction output requests
+/// (i.e., those superseded by the transactions or outputs requests)
+const PRE_FUSION_REQUEST_LABELS: [&str; 4] = [
+    "get_new_transaction_outputs_with_proof",
+    "get_new_transactions_with_proof",
+    "get_transaction_outputs_with_proof",
+    "get_transactions_with_proof",
+];
+
+/// The request kinds (identified by their labels) that a storage service
+/// instance has deprecated, and will reject with an `UnsupportedRequest` error.
+/// Note: the set is derived from the local config of each server, and is never
+/// sent over the wire (peers learn of it via the errors they receive).
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct DeprecatedRequests {
+    labels: BTreeSet<&'static str>,
+}
+
+impl DeprecatedRequests {
+    pub fn new(labels: BTreeSet<&'static str>) -> Self {
+        Self { labels }
+    }
+
+    /// Returns the request kinds deprecated by the given storage service config
+    pub fn from_config(config: &StorageServiceConfig) -> Self {
+        let labels = if config.deprecate_pre_fusion_requests {
+            PRE_FUSION_REQUEST_LABELS.into_iter().collect()
+        } else {
+            BTreeSet::new()
+        };
+        Self::new(labels)
+    }
+
+    /// Returns an `UnsupportedRequest` error iff the kind of the given
+    /// request has been deprecated (and is no longer supported)
+    pub fn check_request_supported(&self, request: &StorageServiceRequest) -> Result<(), Error> {
+        let label = request.data_request.get_label();
+        if self.labels.contains(label) {
+            Err(Error::UnsupportedRequest {
+                label: label.into(),
+            })
+        } else {
+            Ok(())
+        }
+    }
+}
+
 /// A summary of the data actually held by the storage service instance.
+/// Note: the summary is validated when it is deserialized (e.g., to reject
+/// malformed summaries sent by peers).
 #[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(try_from = "UncheckedDataSummary")]
 pub struct DataSummary {
     /// The ledger info corresponding to the highest synced version in storage.
     /// This indicates the highest version and epoch that storage can prove.
@@ -405,33 +2377,507 @@ pub struct DataSummary {
     /// is [(X,Y)], it means all epoch ending ledger infos for epochs X->Y
     /// (inclusive) are held.
     pub epoch_ending_ledger_infos: Option<CompleteDataRange<Epoch>>,
-    /// The range of states held in storage, e.g., if the range is
-    /// [(X,Y)], it means all states are held for every version X->Y
-    /// (inclusive).
+    /// The range of states held in storage, e.g., if the range is
+    /// [(X,Y)], it means all states are held for every version X->Y
+    /// (inclusive).
+    pub states: Option<CompleteDataRange<Version>>,
+    /// The range of transactions held in storage, e.g., if the range is
+    /// [(X,Y)], it means all transactions for versions X->Y (inclusive) are held.
+    pub transactions: Option<CompleteDataRange<Version>>,
+    /// The range of transaction outputs held in storage, e.g., if the range
+    /// is [(X,Y)], it means all transaction outputs for versions X->Y
+    /// (inclusive) are held.
+    pub transaction_outputs: Option<CompleteDataRange<Version>>,
+}
+
+/// An unvalidated data summary (as received over the wire). This is only
+/// used to deserialize (and then validate) a `DataSummary`, either from BCS
+/// or from its protobuf representation.
+#[derive(Deserialize)]
+#[serde(rename = "DataSummary")]
+pub(crate) struct UncheckedDataSummary {
+    pub(crate) synced_ledger_info: Option<LedgerInfoWithSignatures>,
+    pub(crate) epoch_ending_ledger_infos: Option<CompleteDataRange<Epoch>>,
+    pub(crate) states: Option<CompleteDataRange<Version>>,
+    pub(crate) transactions: Option<CompleteDataRange<Version>>,
+    pub(crate) transaction_outputs: Option<CompleteDataRange<Version>>,
+}
+
+impl TryFrom<UncheckedDataSummary> for DataSummary {
+    type Error = Error;
+
+    fn try_from(unchecked_summary: UncheckedDataSummary) -> crate::Result<Self, Self::Error> {
+        let data_summary = DataSummary {
+            synced_ledger_info: unchecked_summary.synced_ledger_info,
+            epoch_ending_ledger_infos: unchecked_summary.epoch_ending_ledger_infos,
+            states: unchecked_summary.states,
+            transactions: unchecked_summary.transactions,
+            transaction_outputs: unchecked_summary.transaction_outputs,
+        };
+
+        // Verify the transactions don't extend beyond the synced version
+        if let (Some(synced_version), Some(transactions)) = (
+            data_summary.get_synced_ledger_info_version(),
+            data_summary.transactions,
+        ) {
+            if transactions.highest() > synced_version {
+                return Err(Error::UnexpectedResponseError(format!(
+                    "Inconsistent data summary! The highest transaction version: {} \
+                    is greater than the synced version: {}",
+                    transactions.highest(),
+                    synced_version
+                )));
+            }
+        }
+
+        Ok(data_summary)
+    }
+}
+
+impl DataSummary {
+    /// Returns true iff the request can be serviced
+    pub fn can_service(&self, request: &StorageServiceRequest) -> bool {
+        self.can_service_with_reason(request).is_ok()
+    }
+
+    /// Returns true iff the request can be serviced, and records the
+    /// outcome (and the reason for any rejection) in the given metrics.
+    pub fn can_service_observed(
+        &self,
+        request: &StorageServiceRequest,
+        metrics: &ServiceMetrics,
+    ) -> bool {
+        let request_label = request.data_request.get_label();
+        match self.can_service_with_reason(request) {
+            Ok(()) => {
+                metrics.increment_accepted(request_label);
+                true
+            },
+            Err(reason) => {
+                metrics.increment_rejected(request_label, reason);
+                false
+            },
+        }
+    }
+
+    /// Returns `Ok(())` iff the request can be serviced. Otherwise,
+    /// returns the reason the request cannot be serviced.
+    pub fn can_service_with_reason(
+        &self,
+        request: &StorageServiceRequest,
+    ) -> crate::Result<(), UnserviceableReason> {
+        self.compacted().can_service_with_reason(request)
+    }
+
+    /// Buckets the given requests by whether they can be fully serviced,
+    /// partially serviced or not serviced at all by this data summary.
+    pub fn batch_coverage(&self, requests: &[StorageServiceRequest]) -> CoverageReport {
+        let mut coverage_report = CoverageReport::default();
+        for (index, request) in requests.iter().enumerate() {
+            match self.can_service_with_reason(request) {
+                Ok(()) => coverage_report.serviceable.push(index),
+                Err(UnserviceableReason::DataUnavailable) if self.holds_partial_data(request) => {
+                    coverage_report.partially_serviceable.push(index)
+                },
+                Err(_) => coverage_report.unserviceable.push(index),
+            }
+        }
+        coverage_report
+    }
+
+    /// Returns true iff some (but not necessarily all) of the data range
+    /// requested by the given request is held. Requests that don't ask
+    /// for a data range are never considered partially held.
+    fn holds_partial_data(&self, request: &StorageServiceRequest) -> bool {
+        let (held_range, start, end) = match &request.data_request {
+            GetEpochEndingLedgerInfos(request) => (
+                &self.epoch_ending_ledger_infos,
+                request.start_epoch,
+                request.expected_end_epoch,
+            ),
+            GetTransactionOutputsWithProof(request) => (
+                &self.transaction_outputs,
+                request.start_version,
+                request.end_version,
+            ),
+            GetTransactionsWithProof(request) => (
+                &self.transactions,
+                request.start_version,
+                request.end_version,
+            ),
+            GetTransactionsOrOutputsWithProof(request) => (
+                &self.transactions,
+                request.start_version,
+                request.end_version,
+            ),
+            GetTransactionRangeProof(request) => (
+                &self.transactions,
+                request.start_version,
+                request.end_version,
+            ),
+            GetTransactionByHashWithProof(request) => (
+                &self.transactions,
+                request.start_version,
+                request.end_version,
+            ),
+            _ => return false,
+        };
+
+        match (held_range, CompleteDataRange::new(start, end)) {
+            (Some(held_range), Ok(desired_range)) => {
+                held_range.lowest() <= desired_range.highest()
+                    && desired_range.lowest() <= held_range.highest()
+            },
+            _ => false,
+        }
+    }
+
+    /// Returns the lowest advertised transaction version that is greater than
+    /// or equal to the given version (or `None` if no such version exists).
+    /// This helps clients realign (e.g., after the peer prunes its data).
+    pub fn first_servable_version_at_or_after(&self, version: Version) -> Option<Version> {
+        self.transactions.and_then(|transactions| {
+            if version > transactions.highest() {
+                None
+            } else {
+                Some(version.max(transactions.lowest()))
+            }
+        })
+    }
+
+    /// Returns the range of transactions that both this peer and the other
+    /// peer can serve with valid proofs, i.e., the intersection of both
+    /// transaction ranges, clamped to both synced versions. If no such
+    /// transactions exist, `None` is returned.
+    pub fn jointly_serviceable_transactions(
+        &self,
+        other: &DataSummary,
+    ) -> Option<CompleteDataRange<Version>> {
+        let transactions = self.transactions?;
+        let other_transactions = other.transactions?;
+        let synced_version = self.get_synced_ledger_info_version()?;
+        let other_synced_version = other.get_synced_ledger_info_version()?;
+
+        // Calculate the intersection (clamped to the highest provable version)
+        let lowest = transactions.lowest().max(other_transactions.lowest());
+        let highest = transactions
+            .highest()
+            .min(other_transactions.highest())
+            .min(synced_version)
+            .min(other_synced_version);
+        CompleteDataRange::new(lowest, highest).ok()
+    }
+
+    /// Returns the window of versions for which this peer can serve all data
+    /// categories, i.e., the intersection of the transaction, transaction
+    /// output and state ranges, clamped to the synced version. If any of the
+    /// categories (or the synced ledger info) is missing, or the window is
+    /// empty, `None` is returned.
+    pub fn common_serviceable_window(&self) -> Option<CompleteDataRange<Version>> {
+        let transactions = self.transactions?;
+        let transaction_outputs = self.transaction_outputs?;
+        let states = self.states?;
+        let synced_version = self.get_synced_ledger_info_version()?;
+
+        // Calculate the intersection (clamped to the synced version)
+        let lowest = transactions
+            .lowest()
+            .max(transaction_outputs.lowest())
+            .max(states.lowest());
+        let highest = transactions
+            .highest()
+            .min(transaction_outputs.highest())
+            .min(states.highest())
+            .min(synced_version);
+        CompleteDataRange::new(lowest, highest).ok()
+    }
+
+    /// Returns the known version to use when sending an optimistic fetch
+    /// request to the peer (i.e., the given version, iff the peer is
+    /// eligible to service the request). Otherwise, `None` is returned.
+    pub fn optimistic_known_version(&self, my_version: Version) -> Option<Version> {
+        self.compacted()
+            .can_service_optimistic_request(my_version)
+            .ok()
+            .map(|_| my_version)
+    }
+
+    /// Returns the given known version clamped to the window in which the
+    /// peer will service an optimistic fetch request, i.e., the desired
+    /// version if it is within `OPTIMISTIC_FETCH_VERSION_DELTA` of the synced
+    /// version, otherwise the highest version still within the window. If the
+    /// peer has no synced ledger info, `None` is returned.
+    pub fn clamp_known_version(&self, desired: Version) -> Option<Version> {
+        let synced_version = self.get_synced_ledger_info_version()?;
+        let highest_known_version = synced_version
+            .saturating_add(OPTIMISTIC_FETCH_VERSION_DELTA)
+            .saturating_sub(1);
+        Some(desired.min(highest_known_version))
+    }
+
+    /// Returns a suggested interval (in milliseconds) at which to poll the
+    /// peer for new data, given a previous summary of the same peer that was
+    /// received `elapsed_ms` ago. The interval is estimated from the rate at
+    /// which the peer's synced version is advancing, such that a client that
+    /// polls at this interval never falls further behind the peer than
+    /// `OPTIMISTIC_FETCH_VERSION_DELTA` versions. If the rate cannot be
+    /// estimated (e.g., the peer has stalled, or a synced ledger info is
+    /// missing), `None` is returned.
+    pub fn suggested_poll_interval_ms(
+        &self,
+        previous: &DataSummary,
+        elapsed_ms: u64,
+    ) -> Option<u64> {
+        let synced_version = self.get_synced_ledger_info_version()?;
+        let previous_synced_version = previous.get_synced_ledger_info_version()?;
+
+        // Calculate the number of versions the peer advanced by
+        let version_delta = synced_version.checked_sub(previous_synced_version)?;
+        if version_delta == 0 || elapsed_ms == 0 {
+            return None; // We can't estimate the rate of progress
+        }
+
+        // Calculate the time it takes the peer to advance by the fetch delta.
+        // We use u128 to ensure the intermediate product doesn't overflow.
+        let poll_interval_ms =
+            (OPTIMISTIC_FETCH_VERSION_DELTA as u128 * elapsed_ms as u128) / version_delta as u128;
+        Some(u64::try_from(poll_interval_ms).unwrap_or(u64::MAX))
+    }
+
+    /// Returns the version of the synced ledger info (if one exists)
+    pub fn get_synced_ledger_info_version(&self) -> Option<u64> {
+        self.synced_ledger_info
+            .as_ref()
+            .map(|ledger_info| ledger_info.ledger_info().version())
+    }
+
+    /// Returns the epoch of the synced ledger info (if one exists), i.e.,
+    /// the current epoch of the peer.
+    pub fn synced_epoch(&self) -> Option<Epoch> {
+        self.synced_ledger_info
+            .as_ref()
+            .map(|ledger_info| ledger_info.ledger_info().epoch())
+    }
+
+    /// Returns the range of versions at which state values (with proofs) can
+    /// be serviced, i.e., the states range clamped to the synced ledger info
+    /// version (as proofs can't be created beyond it). Returns `None` if no
+    /// states are held, if there's no synced ledger info, or if the synced
+    /// version predates all held states.
+    pub fn serviceable_state_version_range(&self) -> Option<CompleteDataRange<Version>> {
+        let states = self.states?;
+        let synced_version = self.get_synced_ledger_info_version()?;
+        let highest = states.highest().min(synced_version);
+        CompleteDataRange::new(states.lowest(), highest).ok()
+    }
+
+    /// Returns true iff state values at the given state version can be
+    /// serviced with a proof relative to the given proof version, i.e., the
+    /// state version is held and the synced ledger info is at (or beyond)
+    /// the proof version. This allows request builders to validate state
+    /// value requests before sending them.
+    pub fn can_serve_state_values(&self, state_version: Version, proof_version: Version) -> bool {
+        let state_version_held = self
+            .states
+            .map(|states| states.contains(state_version))
+            .unwrap_or(false);
+        let can_create_proof = self
+            .get_synced_ledger_info_version()
+            .map(|synced_version| synced_version >= proof_version)
+            .unwrap_or(false);
+        state_version_held && can_create_proof
+    }
+
+    /// Returns the highest end version that a transactions request starting
+    /// at the given version should ask for, i.e., the end that is both held
+    /// and fits in a single chunk (as the peer will truncate larger requests).
+    /// Returns `None` if the start version isn't held, or if the max chunk
+    /// size is zero.
+    pub fn safe_request_end(&self, start: Version, metadata: &ProtocolMetadata) -> Option<Version> {
+        let transactions = self
+            .transactions
+            .filter(|transactions| transactions.contains(start))?;
+        let max_chunk_end =
+            start.saturating_add(metadata.max_transaction_chunk_size.checked_sub(1)?);
+        Some(transactions.highest().min(max_chunk_end))
+    }
+
+    /// Returns the highest version V such that the transactions, transaction
+    /// outputs and states held all cover `[0, V]`, i.e., the peer holds an
+    /// unbroken history from genesis up to V (e.g., as an archival node).
+    /// Returns `None` if any of these categories doesn't start at genesis.
+    pub fn contiguous_from_genesis_version(&self) -> Option<Version> {
+        [self.transactions, self.transaction_outputs, self.states]
+            .into_iter()
+            .map(|range| range.filter(|range| range.lowest() == 0))
+            .map(|range| range.map(|range| range.highest()))
+            .collect::<Option<Vec<_>>>()?
+            .into_iter()
+            .min()
+    }
+
+    /// Returns (best-effort) the range of epochs spanned by the given version
+    /// range. The summary doesn't record the version at which each epoch ended,
+    /// so the only version to epoch mappings known are the synced ledger info
+    /// (i.e., the synced version is in the synced epoch) and genesis (i.e.,
+    /// version 0 is in epoch 0). As such, the returned range is conservative:
+    /// it ends at the synced epoch, and starts at the synced epoch iff the
+    /// version range starts at the synced version (otherwise, at epoch 0).
+    /// Returns `None` if there's no synced ledger info, if the version range
+    /// extends beyond the synced version (i.e., the later epochs are unknown),
+    /// or if the advertised epoch ending ledger infos are inconsistent with
+    /// the synced ledger info.
+    pub fn epochs_for_version_range(
+        &self,
+        versions: &CompleteDataRange<Version>,
+    ) -> Option<CompleteDataRange<Epoch>> {
+        let synced_version = self.get_synced_ledger_info_version()?;
+        let synced_epoch = self.synced_epoch()?;
+        if versions.highest() > synced_version {
+            return None; // We can't know the epochs of versions beyond the synced version
+        }
+        if let Some(epoch_ending_ledger_infos) = &self.epoch_ending_ledger_infos {
+            if epoch_ending_ledger_infos.highest() > synced_epoch {
+                return None; // The epoch data is inconsistent with the synced ledger info
+            }
+        }
+
+        let lowest_epoch = if versions.lowest() == synced_version {
+            synced_epoch
+        } else {
+            0
+        };
+        CompleteDataRange::new(lowest_epoch, synced_epoch).ok()
+    }
+
+    /// Returns true iff the data summary has regressed since the previous
+    /// summary (e.g., the peer pruned or rolled back its data). This is the
+    /// case if any held range shrank on either end (or is no longer held),
+    /// or if the synced ledger info version decreased.
+    pub fn regressed_since(&self, previous: &DataSummary) -> bool {
+        let synced_version_regressed = match (
+            self.get_synced_ledger_info_version(),
+            previous.get_synced_ledger_info_version(),
+        ) {
+            (Some(synced_version), Some(previous_synced_version)) => {
+                synced_version < previous_synced_version
+            },
+            (None, Some(_)) => true,
+            _ => false,
+        };
+
+        synced_version_regressed
+            || range_regressed(
+                &self.epoch_ending_ledger_infos,
+                &previous.epoch_ending_ledger_infos,
+            )
+            || range_regressed(&self.states, &previous.states)
+            || range_regressed(&self.transactions, &previous.transactions)
+            || range_regressed(&self.transaction_outputs, &previous.transaction_outputs)
+    }
+
+    /// Returns a copy of the data summary with only the ranges of the given
+    /// data categories populated (all other ranges are `None`). The synced
+    /// ledger info is always retained, so that proofs can still be created.
+    pub fn project(&self, categories: &[DataCategory]) -> DataSummary {
+        let project_range = |range: &Option<CompleteDataRange<u64>>, category: DataCategory| {
+            if categories.contains(&category) {
+                *range
+            } else {
+                None
+            }
+        };
+
+        DataSummary {
+            synced_ledger_info: self.synced_ledger_info.clone(),
+            epoch_ending_ledger_infos: project_range(
+                &self.epoch_ending_ledger_infos,
+                DataCategory::EpochEndingLedgerInfos,
+            ),
+            states: project_range(&self.states, DataCategory::States),
+            transactions: project_range(&self.transactions, DataCategory::Transactions),
+            transaction_outputs: project_range(
+                &self.transaction_outputs,
+                DataCategory::TransactionOutputs,
+            ),
+        }
+    }
+
+    /// Returns a compact form of the data summary that drops the synced
+    /// ledger info (and its signatures), keeping only the synced version
+    /// and epoch. The synced epoch is used to cross-check the serviceability
+    /// of epoch ending ledger info requests.
+    pub fn compacted(&self) -> CompactDataSummary {
+        CompactDataSummary {
+            synced_version: self.get_synced_ledger_info_version(),
+            synced_epoch: self.synced_epoch(),
+            epoch_ending_ledger_infos: self.epoch_ending_ledger_infos,
+            states: self.states,
+            transactions: self.transactions,
+            transaction_outputs: self.transaction_outputs,
+        }
+    }
+}
+
+/// A compact form of the data summary (see `DataSummary`) that holds only
+/// the synced version and epoch (instead of the entire synced ledger info).
+/// This still suffices to check if requests can be serviced.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct CompactDataSummary {
+    /// The highest synced version in storage
+    pub synced_version: Option<Version>,
+    /// The epoch of the highest synced version in storage
+    pub synced_epoch: Option<Epoch>,
+    /// The range of epoch ending ledger infos in storage
+    pub epoch_ending_ledger_infos: Option<CompleteDataRange<Epoch>>,
+    /// The range of states held in storage
     pub states: Option<CompleteDataRange<Version>>,
-    /// The range of transactions held in storage, e.g., if the range is
-    /// [(X,Y)], it means all transactions for versions X->Y (inclusive) are held.
+    /// The range of transactions held in storage
     pub transactions: Option<CompleteDataRange<Version>>,
-    /// The range of transaction outputs held in storage, e.g., if the range
-    /// is [(X,Y)], it means all transaction outputs for versions X->Y
-    /// (inclusive) are held.
+    /// The range of transaction outputs held in storage
     pub transaction_outputs: Option<CompleteDataRange<Version>>,
 }
 
-impl DataSummary {
+impl CompactDataSummary {
     /// Returns true iff the request can be serviced
     pub fn can_service(&self, request: &StorageServiceRequest) -> bool {
+        self.can_service_with_reason(request).is_ok()
+    }
+
+    /// Returns `Ok(())` iff the request can be serviced. Otherwise,
+    /// returns the reason the request cannot be serviced.
+    pub fn can_service_with_reason(
+        &self,
+        request: &StorageServiceRequest,
+    ) -> crate::Result<(), UnserviceableReason> {
         match &request.data_request {
-            GetServerProtocolVersion | GetStorageServerSummary => true,
+            GetServerProtocolVersion | GetStorageServerSummary => Ok(()),
+            GetLatestLedgerInfo => {
+                // Any synced ledger info suffices (i.e., the peer simply returns its tip)
+                if self.synced_version.is_some() {
+                    Ok(())
+                } else {
+                    Err(UnserviceableReason::DataUnavailable)
+                }
+            },
             GetEpochEndingLedgerInfos(request) => {
+                // Single epoch requests (i.e., start == end) are valid, but requests
+                // with start > end are degenerate and are cleanly rejected here.
                 let desired_range =
-                    match CompleteDataRange::new(request.start_epoch, request.expected_end_epoch) {
-                        Ok(desired_range) => desired_range,
-                        Err(_) => return false,
-                    };
-                self.epoch_ending_ledger_infos
-                    .map(|range| range.superset_of(&desired_range))
-                    .unwrap_or(false)
+                    desired_request_range(request.start_epoch, request.expected_end_epoch)?;
+                check_range_held(&self.epoch_ending_ledger_infos, &desired_range)?;
+                self.check_synced_epoch(request.expected_end_epoch)
+            },
+            GetEpochChangeProofBetween(request) => {
+                // The proof requires the epoch ending ledger infos for all epochs in
+                // [start_epoch, end_epoch), so requests with start >= end are degenerate.
+                let last_epoch = request
+                    .end_epoch
+                    .checked_sub(1)
+                    .ok_or(UnserviceableReason::DegenerateRange)?;
+                let desired_range = desired_request_range(request.start_epoch, last_epoch)?;
+                check_range_held(&self.epoch_ending_ledger_infos, &desired_range)?;
+                self.check_synced_epoch(request.end_epoch)
             },
             GetNewTransactionOutputsWithProof(request) => {
                 self.can_service_optimistic_request(request.known_version)
@@ -439,110 +2885,282 @@ impl DataSummary {
             GetNewTransactionsWithProof(request) => {
                 self.can_service_optimistic_request(request.known_version)
             },
-            GetNumberOfStatesAtVersion(version) => self
-                .states
-                .map(|range| range.contains(*version))
-                .unwrap_or(false),
+            GetNumberOfStatesAtVersion(version) => check_item_held(&self.states, *version),
             GetStateValuesWithProof(request) => {
-                let proof_version = request.version;
-
-                let can_serve_states = self
-                    .states
-                    .map(|range| range.contains(request.version))
-                    .unwrap_or(false);
-
-                let can_create_proof = self
-                    .synced_ledger_info
-                    .as_ref()
-                    .map(|li| li.ledger_info().version() >= proof_version)
-                    .unwrap_or(false);
-
-                can_serve_states && can_create_proof
+                check_item_held(&self.states, request.version)?;
+                self.can_create_proof(request.version)
             },
             GetTransactionOutputsWithProof(request) => {
                 let desired_range =
-                    match CompleteDataRange::new(request.start_version, request.end_version) {
-                        Ok(desired_range) => desired_range,
-                        Err(_) => return false,
-                    };
-
-                let can_serve_outputs = self
-                    .transaction_outputs
-                    .map(|range| range.superset_of(&desired_range))
-                    .unwrap_or(false);
-
-                let can_create_proof = self
-                    .synced_ledger_info
-                    .as_ref()
-                    .map(|li| li.ledger_info().version() >= request.proof_version)
-                    .unwrap_or(false);
-
-                can_serve_outputs && can_create_proof
+                    desired_request_range(request.start_version, request.end_version)?;
+                check_range_held(&self.transaction_outputs, &desired_range)?;
+                self.can_create_proof(request.proof_version)
             },
             GetTransactionsWithProof(request) => {
                 let desired_range =
-                    match CompleteDataRange::new(request.start_version, request.end_version) {
-                        Ok(desired_range) => desired_range,
-                        Err(_) => return false,
-                    };
-
-                let can_serve_txns = self
-                    .transactions
-                    .map(|range| range.superset_of(&desired_range))
-                    .unwrap_or(false);
-
-                let can_create_proof = self
-                    .synced_ledger_info
-                    .as_ref()
-                    .map(|li| li.ledger_info().version() >= request.proof_version)
-                    .unwrap_or(false);
-
-                can_serve_txns && can_create_proof
+                    desired_request_range(request.start_version, request.end_version)?;
+                check_range_held(&self.transactions, &desired_range)?;
+                self.can_create_proof(request.proof_version)
             },
             GetNewTransactionsOrOutputsWithProof(request) => {
                 self.can_service_optimistic_request(request.known_version)
             },
             GetTransactionsOrOutputsWithProof(request) => {
                 let desired_range =
-                    match CompleteDataRange::new(request.start_version, request.end_version) {
-                        Ok(desired_range) => desired_range,
-                        Err(_) => return false,
-                    };
-
-                let can_serve_txns = self
-                    .transactions
-                    .map(|range| range.superset_of(&desired_range))
-                    .unwrap_or(false);
-
-                let can_serve_outputs = self
-                    .transaction_outputs
-                    .map(|range| range.superset_of(&desired_range))
-                    .unwrap_or(false);
-
-                let can_create_proof = self
-                    .synced_ledger_info
-                    .as_ref()
-                    .map(|li| li.ledger_info().version() >= request.proof_version)
-                    .unwrap_or(false);
+                    desired_request_range(request.start_version, request.end_version)?;
+                check_range_held(&self.transactions, &desired_range)?;
+                check_range_held(&self.transaction_outputs, &desired_range)?;
+                self.can_create_proof(request.proof_version)
+            },
+            GetTransactionRangeProof(request) => {
+                let desired_range =
+                    desired_request_range(request.start_version, request.end_version)?;
+                check_range_held(&self.transactions, &desired_range)?;
+                self.can_create_proof(request.proof_version)
+            },
+            GetTransactionByHashWithProof(request) => {
+                // This is best-effort: we can't map the hash to a version without
+                // a lookup, so we check that the candidate version range is held.
+                let desired_range =
+                    desired_request_range(request.start_version, request.end_version)?;
+                check_range_held(&self.transactions, &desired_range)?;
+                self.can_create_proof(request.proof_version)
+            },
+            GetBatch(data_requests) => {
+                // Every request in the batch must be serviceable (and batches
+                // are limited to a depth of one, so nested batches are rejected).
+                for data_request in data_requests {
+                    if data_request.is_batch_request() {
+                        return Err(UnserviceableReason::DataUnavailable);
+                    }
+                    let inner_request =
+                        StorageServiceRequest::new(data_request.clone(), request.use_compression);
+                    self.can_service_with_reason(&inner_request)?;
+                }
+                Ok(())
+            },
+        }
+    }
+
+    /// Returns `Ok(())` iff a proof can be created relative to the given version
+    fn can_create_proof(&self, proof_version: Version) -> crate::Result<(), UnserviceableReason> {
+        let can_create_proof = self
+            .synced_version
+            .map(|synced_version| synced_version >= proof_version)
+            .unwrap_or(false);
+        if can_create_proof {
+            Ok(())
+        } else {
+            Err(UnserviceableReason::ProofUnavailable)
+        }
+    }
 
-                can_serve_txns && can_serve_outputs && can_create_proof
+    /// Returns `Ok(())` iff the synced ledger info doesn't predate the given
+    /// end epoch (i.e., the advertised epoch range is consistent with the
+    /// synced ledger info). If no synced ledger info exists, the advertised
+    /// epoch range is trusted.
+    fn check_synced_epoch(&self, end_epoch: Epoch) -> crate::Result<(), UnserviceableReason> {
+        match self.synced_epoch {
+            Some(synced_epoch) if synced_epoch < end_epoch => {
+                Err(UnserviceableReason::SyncedEpochTooLow)
             },
+            _ => Ok(()),
         }
     }
 
-    /// Returns true iff the optimistic data request can be serviced
-    fn can_service_optimistic_request(&self, known_version: u64) -> bool {
-        self.synced_ledger_info
-            .as_ref()
-            .map(|li| (li.ledger_info().version() + OPTIMISTIC_FETCH_VERSION_DELTA) > known_version)
-            .unwrap_or(false)
+    /// Returns `Ok(())` iff the optimistic data request can be serviced. The
+    /// request can't be serviced if the peer has no synced ledger info, or if
+    /// the known version is outside the optimistic fetch window.
+    fn can_service_optimistic_request(
+        &self,
+        known_version: u64,
+    ) -> crate::Result<(), UnserviceableReason> {
+        let synced_version = self
+            .synced_version
+            .ok_or(UnserviceableReason::NoSyncedLedgerInfo)?;
+        if OptimisticFetchWindow::default().is_within(synced_version, known_version) {
+            Ok(())
+        } else {
+            Err(UnserviceableReason::OptimisticOutsideWindow)
+        }
     }
+}
 
-    /// Returns the version of the synced ledger info (if one exists)
-    pub fn get_synced_ledger_info_version(&self) -> Option<u64> {
-        self.synced_ledger_info
-            .as_ref()
-            .map(|ledger_info| ledger_info.ledger_info().version())
+/// The data newly stored by a storage server instance (e.g., since its
+/// summary was last computed). This is used to advance a summary in place.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct DataSummaryDelta {
+    /// The range of newly stored epoch ending ledger infos
+    pub epoch_ending_ledger_infos: Option<CompleteDataRange<Epoch>>,
+    /// The range of newly stored states
+    pub states: Option<CompleteDataRange<Version>>,
+    /// The range of newly stored transactions
+    pub transactions: Option<CompleteDataRange<Version>>,
+    /// The range of newly stored transaction outputs
+    pub transaction_outputs: Option<CompleteDataRange<Version>>,
+}
+
+/// The categories of data ranges held in a data summary
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum DataCategory {
+    EpochEndingLedgerInfos, // The epoch ending ledger info range
+    States,                 // The state range
+    Transactions,           // The transaction range
+    TransactionOutputs,     // The transaction output range
+}
+
+/// Extends the highest value of the given range to cover the newly stored
+/// range. If no range is held yet, the newly stored range is used directly.
+fn extend_range<T: PrimInt>(
+    range: &mut Option<CompleteDataRange<T>>,
+    newly_stored: Option<CompleteDataRange<T>>,
+) {
+    if let Some(newly_stored) = newly_stored {
+        match range {
+            Some(held_range) => {
+                if newly_stored.highest() > held_range.highest() {
+                    if let Ok(extended_range) =
+                        CompleteDataRange::new(held_range.lowest(), newly_stored.highest())
+                    {
+                        *held_range = extended_range;
+                    }
+                }
+            },
+            None => *range = Some(newly_stored),
+        }
+    }
+}
+
+/// Returns true iff the given range shrank on either end (or is no
+/// longer held) when compared to the previous range.
+fn range_regressed<T: PrimInt>(
+    range: &Option<CompleteDataRange<T>>,
+    previous_range: &Option<CompleteDataRange<T>>,
+) -> bool {
+    match (range, previous_range) {
+        (Some(range), Some(previous_range)) => {
+            range.lowest() > previous_range.lowest() || range.highest() < previous_range.highest()
+        },
+        (None, Some(_)) => true,
+        _ => false,
+    }
+}
+
+/// Returns `Ok(())` iff the given range is held and is a superset of the desired range
+fn check_range_held<T: PrimInt>(
+    range: &Option<CompleteDataRange<T>>,
+    desired_range: &CompleteDataRange<T>,
+) -> crate::Result<(), UnserviceableReason> {
+    match range {
+        Some(range) if range.superset_of(desired_range) => Ok(()),
+        _ => Err(UnserviceableReason::DataUnavailable),
+    }
+}
+
+/// Returns `Ok(())` iff the given range is held and contains the desired item
+fn check_item_held<T: PrimInt>(
+    range: &Option<CompleteDataRange<T>>,
+    item: T,
+) -> crate::Result<(), UnserviceableReason> {
+    match range {
+        Some(range) if range.contains(item) => Ok(()),
+        _ => Err(UnserviceableReason::DataUnavailable),
+    }
+}
+
+/// The reason a data summary is unable to service a request
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum UnserviceableReason {
+    DataUnavailable,         // The requested data is not held
+    DegenerateRange,         // The requested range is degenerate (i.e., start > end)
+    NoSyncedLedgerInfo,      // The peer has no synced ledger info
+    OptimisticOutsideWindow, // The known version is too far beyond the synced version
+    ProofUnavailable,        // A proof cannot be created relative to the requested version
+    SyncedEpochTooLow,       // The synced ledger info predates the requested end epoch
+}
+
+/// A report of how well a single data summary covers a batch of requests.
+/// Each bucket contains the indices (in the batch) of the requests in it.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct CoverageReport {
+    pub serviceable: Vec<usize>, // The requests that can be fully serviced
+    pub partially_serviceable: Vec<usize>, // The requests for which only some of the data is held
+    pub unserviceable: Vec<usize>, // The requests that cannot be serviced at all
+}
+
+impl CoverageReport {
+    /// Returns the number of fully serviceable requests
+    pub fn num_serviceable(&self) -> usize {
+        self.serviceable.len()
+    }
+
+    /// Returns the number of partially serviceable requests
+    pub fn num_partially_serviceable(&self) -> usize {
+        self.partially_serviceable.len()
+    }
+
+    /// Returns the number of unserviceable requests
+    pub fn num_unserviceable(&self) -> usize {
+        self.unserviceable.len()
+    }
+}
+
+/// Simple counters that track the outcome of serviceability checks for each
+/// request kind (e.g., to understand how often requests are rejected and why).
+#[derive(Debug)]
+pub struct ServiceMetrics {
+    accepted_requests: Mutex<HashMap<&'static str, u64>>,
+    rejected_requests: Mutex<HashMap<(&'static str, UnserviceableReason), u64>>,
+}
+
+impl ServiceMetrics {
+    pub fn new() -> Self {
+        Self {
+            accepted_requests: Mutex::new(HashMap::new()),
+            rejected_requests: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Returns the number of accepted requests with the given label
+    pub fn num_accepted(&self, request_label: &'static str) -> u64 {
+        self.accepted_requests
+            .lock()
+            .get(request_label)
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Returns the number of rejected requests with the given label and reason
+    pub fn num_rejected(&self, request_label: &'static str, reason: UnserviceableReason) -> u64 {
+        self.rejected_requests
+            .lock()
+            .get(&(request_label, reason))
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Increments the accepted counter for the given request label
+    fn increment_accepted(&self, request_label: &'static str) {
+        *self
+            .accepted_requests
+            .lock()
+            .entry(request_label)
+            .or_insert(0) += 1;
+    }
+
+    /// Increments the rejected counter for the given request label and reason
+    fn increment_rejected(&self, request_label: &'static str, reason: UnserviceableReason) {
+        *self
+            .rejected_requests
+            .lock()
+            .entry((request_label, reason))
+            .or_insert(0) += 1;
+    }
+}
+
+impl Default for ServiceMetrics {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -561,6 +3179,17 @@ pub struct CompleteDataRange<T> {
     highest: T,
 }
 
+/// Returns the data range requested by a storage service request. Pathological
+/// requests (i.e., those with `start > end`, or those whose length overflows,
+/// such as `[0, u64::MAX]`) are rejected as degenerate, and are never serviced.
+fn desired_request_range(
+    start: u64,
+    end: u64,
+) -> crate::Result<CompleteDataRange<u64>, UnserviceableReason> {
+    range_length_checked(start, end).map_err(|_| UnserviceableReason::DegenerateRange)?;
+    CompleteDataRange::new(start, end).map_err(|_| UnserviceableReason::DegenerateRange)
+}
+
 fn range_length_checked<T: PrimInt>(lowest: T, highest: T) -> crate::Result<T, Error> {
     // len = highest - lowest + 1
     // Note: the order of operations here is important; we need to subtract first
@@ -591,6 +3220,12 @@ impl<T: PrimInt> CompleteDataRange<T> {
         Self::new(lowest, highest)
     }
 
+    /// Create a data range given the lower and upper bounds (inclusive). This
+    /// is an alias of `new` (e.g., for ranges parsed at runtime).
+    pub fn try_from_bounds(lowest: T, highest: T) -> crate::Result<Self, Error> {
+        Self::new(lowest, highest)
+    }
+
     #[inline]
     pub fn lowest(&self) -> T {
         self.lowest
@@ -619,15 +3254,361 @@ impl<T: PrimInt> CompleteDataRange<T> {
     pub fn superset_of(&self, other: &Self) -> bool {
         self.lowest <= other.lowest && other.highest <= self.highest
     }
-}
 
-impl<T: Zero> CompleteDataRange<T> {
+    /// Returns a new data range that is expanded (if required) to include
+    /// the given item. Returns an error if the range length would overflow.
+    pub fn expand_to_include(&self, item: T) -> crate::Result<Self, Error> {
+        Self::new(self.lowest.min(item), self.highest.max(item))
+    }
+
+    /// Returns a new data range with the given highest value (and the same
+    /// lowest value). Returns an error if the new range is degenerate.
+    pub fn with_highest(&self, highest: T) -> crate::Result<Self, Error> {
+        Self::new(self.lowest, highest)
+    }
+
+    /// Returns a new data range with the given lowest value (and the same
+    /// highest value). Returns an error if the new range is degenerate.
+    pub fn with_lowest(&self, lowest: T) -> crate::Result<Self, Error> {
+        Self::new(lowest, self.highest)
+    }
+
+    /// Creates a data range from genesis (i.e., zero) to the given highest
+    /// value. Note: the caller must ensure the range length doesn't overflow
+    /// (i.e., `highest != T::max_value()`), otherwise use `from_genesis_checked`.
     pub fn from_genesis(highest: T) -> Self {
+        debug_assert!(
+            range_length_checked(T::zero(), highest).is_ok(),
+            "The range length overflows!"
+        );
         Self {
             lowest: T::zero(),
             highest,
         }
     }
+
+    /// Creates a data range from genesis (i.e., zero) to the given highest
+    /// value. Returns an error if the range length would overflow.
+    pub fn from_genesis_checked(highest: T) -> crate::Result<Self, Error> {
+        Self::new(T::zero(), highest)
+    }
+
+    /// Returns an iterator over consecutive sub-ranges of this range, from
+    /// highest to lowest (e.g., to fetch the newest data first). Each chunk
+    /// holds at most `chunk_size` items, and the final (i.e., lowest) chunk
+    /// may be shorter. If `chunk_size` is zero, no chunks are returned.
+    pub fn chunks_rev(&self, chunk_size: T) -> impl Iterator<Item = Self> {
+        let lowest = self.lowest;
+        let mut next_chunk_highest = (chunk_size > T::zero()).then_some(self.highest);
+        std::iter::from_fn(move || {
+            let chunk_highest = next_chunk_highest?;
+
+            // Calculate the lowest value of the chunk (without underflowing)
+            let chunk_lowest = chunk_highest
+                .checked_sub(&(chunk_size - T::one()))
+                .map_or(lowest, |chunk_lowest| chunk_lowest.max(lowest));
+
+            // Identify the highest value of the next chunk (if one exists)
+            next_chunk_highest = if chunk_lowest == lowest {
+                None
+            } else {
+                Some(chunk_lowest - T::one())
+            };
+
+            Some(CompleteDataRange {
+                lowest: chunk_lowest,
+                highest: chunk_highest,
+            })
+        })
+    }
+
+    /// Returns an iterator over every `step`-th item in the range, i.e.,
+    /// `lowest`, `lowest + step`, and so on (up to and including `highest`,
+    /// if it is reached exactly). If `step` is zero, no items are returned.
+    pub fn step_by(&self, step: T) -> impl Iterator<Item = T> {
+        let highest = self.highest;
+        let mut next_item = (step > T::zero()).then_some(self.lowest);
+        std::iter::from_fn(move || {
+            let item = next_item?;
+
+            // Identify the next item (if it doesn't overflow or overshoot the range)
+            next_item = item
+                .checked_add(&step)
+                .filter(|next_item| *next_item <= highest);
+
+            Some(item)
+        })
+    }
+
+    /// Returns the fraction (between 0.0 and 1.0) of the desired range that
+    /// is covered by this range, i.e., the length of the intersection divided
+    /// by the length of the desired range. This is useful for reporting
+    /// progress (e.g., how much of a target range a peer can serve).
+    pub fn coverage_fraction(&self, desired: &CompleteDataRange<T>) -> f64 {
+        let lowest = self.lowest.max(desired.lowest);
+        let highest = self.highest.min(desired.highest);
+        if lowest > highest {
+            return 0.0; // The ranges don't overlap
+        }
+
+        // Compute the lengths as floats to avoid overflowing for large ranges
+        let range_length = |lowest: T, highest: T| {
+            highest.to_f64().unwrap_or_default() - lowest.to_f64().unwrap_or_default() + 1.0
+        };
+        let covered_length = range_length(lowest, highest);
+        let desired_length = range_length(desired.lowest, desired.highest);
+        (covered_length / desired_length).clamp(0.0, 1.0)
+    }
+
+    /// Returns the number of requests required to fetch the entire range,
+    /// where each request fetches at most `max_chunk` items (i.e., the length
+    /// of the range divided by `max_chunk`, rounded up). Returns an error if
+    /// `max_chunk` is zero, or if the request count would overflow.
+    pub fn request_count_for(&self, max_chunk: T) -> crate::Result<T, Error> {
+        if max_chunk == T::zero() {
+            return Err(Error::UnexpectedErrorEncountered(
+                "The max chunk size must be non-zero!".into(),
+            ));
+        }
+
+        // Note: ceil(len / max_chunk) == floor((len - 1) / max_chunk) + 1, and
+        // (len - 1) never overflows, even for ranges spanning the entire domain.
+        let span = self.highest - self.lowest;
+        (span / max_chunk)
+            .checked_add(&T::one())
+            .ok_or(DegenerateRangeError)
+    }
+
+    /// Returns the item at the given (zero-based) index in the range, i.e.,
+    /// `lowest + index`, without iterating. If the index is out of bounds,
+    /// `None` is returned.
+    pub fn nth(&self, index: T) -> Option<T> {
+        // Note: we compare against (highest - lowest) rather than the length,
+        // as the length may overflow for ranges spanning the entire domain.
+        if index <= self.highest - self.lowest {
+            Some(self.lowest + index)
+        } else {
+            None
+        }
+    }
+
+    /// Partitions the range around the given pivot, returning the part of the
+    /// range strictly below the pivot, whether the pivot is contained in the
+    /// range, and the part of the range strictly above the pivot. Empty parts
+    /// (e.g., if the pivot is at the bounds of the range) are `None`.
+    pub fn partition_around(&self, pivot: T) -> (Option<Self>, bool, Option<Self>) {
+        if pivot < self.lowest {
+            return (None, false, Some(*self));
+        }
+        if pivot > self.highest {
+            return (Some(*self), false, None);
+        }
+
+        // Note: sub-ranges of a valid range are always valid
+        let before = (pivot > self.lowest).then(|| CompleteDataRange {
+            lowest: self.lowest,
+            highest: pivot - T::one(),
+        });
+        let after = (pivot < self.highest).then(|| CompleteDataRange {
+            lowest: pivot + T::one(),
+            highest: self.highest,
+        });
+        (before, true, after)
+    }
+
+    /// Returns the sub-ranges of the given universe that are not covered by
+    /// this range (e.g., the data missing within `[0, synced]`), in ascending
+    /// order. There are at most two sub-ranges: below and above this range.
+    pub fn complement_within(&self, universe: &Self) -> Vec<Self> {
+        let mut complement = vec![];
+
+        // Note: sub-ranges of a valid range are always valid, and the bounds
+        // are only stepped when they are strictly inside the universe.
+        if self.lowest > universe.lowest {
+            complement.push(CompleteDataRange {
+                lowest: universe.lowest,
+                highest: (self.lowest - T::one()).min(universe.highest),
+            });
+        }
+        if self.highest < universe.highest {
+            complement.push(CompleteDataRange {
+                lowest: (self.highest + T::one()).max(universe.lowest),
+                highest: universe.highest,
+            });
+        }
+        complement
+    }
+
+    /// Returns the number of items held by both ranges (zero if the ranges
+    /// are disjoint), without constructing the intersection. If the number
+    /// of items overflows `T` (i.e., both ranges span the entire domain), the
+    /// length saturates at the max value of `T`.
+    pub fn intersection_len(&self, other: &Self) -> T {
+        let lowest = self.lowest.max(other.lowest);
+        let highest = self.highest.min(other.highest);
+        if lowest > highest {
+            return T::zero(); // The ranges don't overlap
+        }
+        (highest - lowest)
+            .checked_add(&T::one())
+            .unwrap_or_else(T::max_value)
+    }
+}
+
+impl<T: PrimInt + Into<u128>> CompleteDataRange<T> {
+    /// Returns the length of the range widened to a `u128`. This never
+    /// fails, and allows the lengths of many ranges to be summed without
+    /// overflowing.
+    pub fn len_u128(&self) -> u128 {
+        self.highest.into() - self.lowest.into() + 1
+    }
+}
+
+impl CompleteDataRange<Version> {
+    /// Splits the version range into contiguous chunks, such that the estimated
+    /// serialized size of each chunk (i.e., the number of items in the chunk
+    /// multiplied by `per_item_bytes`, plus `proof_overhead`) never exceeds
+    /// `max_bytes`. If not even a single item fits within `max_bytes`, no
+    /// chunks are returned.
+    pub fn chunks_by_estimated_bytes(
+        &self,
+        per_item_bytes: usize,
+        proof_overhead: usize,
+        max_bytes: usize,
+    ) -> Vec<CompleteDataRange<Version>> {
+        // Calculate the maximum number of items that fit in a single chunk
+        let max_items_per_chunk = match max_bytes.checked_sub(proof_overhead) {
+            Some(max_item_bytes) => max_item_bytes
+                .checked_div(per_item_bytes)
+                .map(|max_items| max_items as u64)
+                .unwrap_or(u64::MAX), // Items are free, so everything fits
+            None => 0, // Not even the proof fits
+        };
+        self.chunks_by_max_items(max_items_per_chunk)
+    }
+
+    /// Splits the version range into the ordered list of transaction requests
+    /// (one `GetTransactionsWithProof` request per chunk), such that each
+    /// request fetches at most `max_chunk` transactions. All requests share
+    /// the given proof version, and fetch transactions without events or
+    /// compression. If `max_chunk` is zero, no requests are returned.
+    pub fn to_transaction_requests(
+        &self,
+        proof_version: Version,
+        max_chunk: u64,
+    ) -> Vec<StorageServiceRequest> {
+        self.chunks_by_max_items(max_chunk)
+            .into_iter()
+            .map(|chunk| {
+                let data_request = GetTransactionsWithProof(TransactionsWithProofRequest {
+                    proof_version,
+                    start_version: chunk.lowest,
+                    end_version: chunk.highest,
+                    include_events: false,
+                });
+                StorageServiceRequest::new(data_request, false)
+            })
+            .collect()
+    }
+
+    /// Splits the version range into contiguous chunks of at most
+    /// `max_items_per_chunk` items. If `max_items_per_chunk` is zero,
+    /// no chunks are returned.
+    fn chunks_by_max_items(&self, max_items_per_chunk: u64) -> Vec<CompleteDataRange<Version>> {
+        if max_items_per_chunk == 0 {
+            return vec![];
+        }
+
+        // Split the range into chunks
+        let mut chunks = vec![];
+        let mut chunk_start = self.lowest;
+        loop {
+            let chunk_end = chunk_start
+                .saturating_add(max_items_per_chunk - 1)
+                .min(self.highest);
+            chunks.push(CompleteDataRange {
+                lowest: chunk_start,
+                highest: chunk_end,
+            });
+            if chunk_end == self.highest {
+                return chunks;
+            }
+            chunk_start = chunk_end + 1;
+        }
+    }
+}
+
+/// A struct representing a contiguous, non-empty range (lowest to highest,
+/// inclusive) over any ordered type (e.g., a domain specific version newtype).
+///
+/// Unlike `CompleteDataRange`, this type does not require the bounds to be
+/// primitive integers, so it does not offer any length based operations.
+///
+/// Note: `OrderedRanges` are never degenerate (lowest > highest). Constructing
+/// a degenerate range via `new` will return an `Err`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct OrderedRange<T> {
+    lowest: T,
+    highest: T,
+}
+
+impl<T: Ord + Clone> OrderedRange<T> {
+    pub fn new(lowest: T, highest: T) -> crate::Result<Self, Error> {
+        if lowest > highest {
+            Err(DegenerateRangeError)
+        } else {
+            Ok(Self { lowest, highest })
+        }
+    }
+
+    #[inline]
+    pub fn lowest(&self) -> &T {
+        &self.lowest
+    }
+
+    #[inline]
+    pub fn highest(&self) -> &T {
+        &self.highest
+    }
+
+    /// Returns true iff the given item is within this range
+    pub fn contains(&self, item: &T) -> bool {
+        &self.lowest <= item && item <= &self.highest
+    }
+
+    /// Returns true iff this range is a superset of the other range.
+    pub fn superset_of(&self, other: &Self) -> bool {
+        self.lowest <= other.lowest && other.highest <= self.highest
+    }
+
+    /// Returns true iff this range shares at least one item with the other range.
+    pub fn overlaps(&self, other: &Self) -> bool {
+        self.lowest <= other.highest && other.lowest <= self.highest
+    }
+
+    /// Returns the range of items held by both ranges (if the ranges overlap)
+    pub fn intersection(&self, other: &Self) -> Option<Self> {
+        if !self.overlaps(other) {
+            return None;
+        }
+
+        let lowest = std::cmp::max(&self.lowest, &other.lowest).clone();
+        let highest = std::cmp::min(&self.highest, &other.highest).clone();
+        Some(Self { lowest, highest })
+    }
+}
+
+impl FromStr for CompleteDataRange<u64> {
+    type Err = Error;
+
+    /// Parses a data range from a string of the form "lowest-highest" (e.g.,
+    /// "100-200"). Malformed or reversed ranges are rejected as degenerate.
+    fn from_str(range: &str) -> crate::Result<Self, Self::Err> {
+        let (lowest, highest) = range.split_once('-').ok_or(DegenerateRangeError)?;
+        let lowest = lowest.trim().parse().map_err(|_| DegenerateRangeError)?;
+        let highest = highest.trim().parse().map_err(|_| DegenerateRangeError)?;
+        Self::try_from_bounds(lowest, highest)
+    }
 }
 
 impl<'de, T> serde::Deserialize<'de> for CompleteDataRange<T>
@@ -652,15 +3633,361 @@ where
     }
 }
 
+/// Alternative serde representations of `CompleteDataRange`
+pub mod complete_data_range {
+    /// (De)serializes a `CompleteDataRange` as an inclusive `[lowest, highest]`
+    /// tuple (e.g., for compact configs), using `#[serde(with = "...")]`.
+    /// Degenerate ranges are rejected on deserialization (as with the
+    /// default struct form).
+    pub mod as_tuple {
+        use crate::responses::CompleteDataRange;
+        use num_traits::PrimInt;
+        use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+        pub fn serialize<T, S>(
+            range: &CompleteDataRange<T>,
+            serializer: S,
+        ) -> Result<S::Ok, S::Error>
+        where
+            T: PrimInt + Serialize,
+            S: Serializer,
+        {
+            (range.lowest(), range.highest()).serialize(serializer)
+        }
+
+        pub fn deserialize<'de, T, D>(deserializer: D) -> Result<CompleteDataRange<T>, D::Error>
+        where
+            T: PrimInt + Deserialize<'de>,
+            D: Deserializer<'de>,
+        {
+            let (lowest, highest) = <(T, T)>::deserialize(deserializer)?;
+            CompleteDataRange::new(lowest, highest).map_err(serde::de::Error::custom)
+        }
+    }
+}
+
+/// Coalesces adjacent transaction requests (i.e., `GetTransactionsWithProof`
+/// requests with contiguous version ranges and identical proof versions) into
+/// fewer, larger requests. Requests are only merged if the merged request does
+/// not exceed `max_chunk` transactions. All other requests are left untouched
+/// and the relative order of the requests is preserved.
+pub fn coalesce_transaction_requests(
+    requests: &[StorageServiceRequest],
+    max_chunk: u64,
+) -> Vec<StorageServiceRequest> {
+    let mut coalesced_requests: Vec<StorageServiceRequest> = vec![];
+    for request in requests {
+        if let Some(previous_request) = coalesced_requests.last_mut() {
+            if let Some(merged_request) =
+                merge_transaction_requests(previous_request, request, max_chunk)
+            {
+                *previous_request = merged_request;
+                continue;
+            }
+        }
+        coalesced_requests.push(request.clone());
+    }
+    coalesced_requests
+}
+
+/// Merges the two transaction requests into a single request (if they
+/// are adjacent, compatible and the merged request fits in `max_chunk`).
+fn merge_transaction_requests(
+    first_request: &StorageServiceRequest,
+    second_request: &StorageServiceRequest,
+    max_chunk: u64,
+) -> Option<StorageServiceRequest> {
+    if first_request.use_compression != second_request.use_compression {
+        return None;
+    }
+
+    match (&first_request.data_request, &second_request.data_request) {
+        (GetTransactionsWithProof(first), GetTransactionsWithProof(second)) => {
+            // Verify the requests are compatible
+            if first.proof_version != second.proof_version
+                || first.include_events != second.include_events
+            {
+                return None;
+            }
+
+            // Verify the requests are valid and adjacent
+            let first_range =
+                CompleteDataRange::new(first.start_version, first.end_version).ok()?;
+            let second_range =
+                CompleteDataRange::new(second.start_version, second.end_version).ok()?;
+            if first_range.highest().checked_add(1) != Some(second_range.lowest()) {
+                return None;
+            }
+
+            // Verify the merged request respects the max chunk size
+            let merged_range =
+                CompleteDataRange::new(first_range.lowest(), second_range.highest()).ok()?;
+            if merged_range.len().ok()? > max_chunk {
+                return None;
+            }
+
+            let data_request = GetTransactionsWithProof(TransactionsWithProofRequest {
+                proof_version: first.proof_version,
+                start_version: merged_range.lowest(),
+                end_version: merged_range.highest(),
+                include_events: first.include_events,
+            });
+            Some(StorageServiceRequest::new(
+                data_request,
+                first_request.use_compression,
+            ))
+        },
+        _ => None,
+    }
+}
+
+/// Returns true iff the given error contradicts the given storage server
+/// summary, i.e., the peer advertised that it could service the request,
+/// but responded with an unexpected response. This can be used to mark
+/// the cached summary of the peer as stale.
+pub fn contradicts(
+    summary: &StorageServerSummary,
+    request: &StorageServiceRequest,
+    error: &Error,
+) -> bool {
+    matches!(error.unwrapped(), Error::UnexpectedResponseError(_)) && summary.can_service(request)
+}
+
+/// Normalizes the given requests into a canonical list by removing exact
+/// duplicates and sorting the remaining requests by kind and then by
+/// version range. The sort is stable (i.e., requests with the same kind
+/// and range retain their relative order) and normalization is idempotent.
+pub fn normalize_requests(requests: Vec<StorageServiceRequest>) -> Vec<StorageServiceRequest> {
+    // Remove all exact duplicates (keeping the first occurrence)
+    let mut seen_requests = HashSet::new();
+    let mut normalized_requests: Vec<_> = requests
+        .into_iter()
+        .filter(|request| seen_requests.insert(request.clone()))
+        .collect();
+
+    // Sort the requests by kind and then by range
+    normalized_requests.sort_by_key(get_request_sort_key);
+    normalized_requests
+}
+
+/// Returns the sort key (i.e., the request kind, followed by the start
+/// and end of the requested range) for the given request. Requests that
+/// don't specify a range use a single version (or zero, if none exists).
+fn get_request_sort_key(request: &StorageServiceRequest) -> (&'static str, u64, u64) {
+    let (start, end) = match &request.data_request {
+        GetEpochEndingLedgerInfos(request) => (request.start_epoch, request.expected_end_epoch),
+        GetEpochChangeProofBetween(request) => (request.start_epoch, request.end_epoch),
+        GetNewTransactionOutputsWithProof(request) => {
+            (request.known_version, request.known_version)
+        },
+        GetNewTransactionsWithProof(request) => (request.known_version, request.known_version),
+        GetNumberOfStatesAtVersion(version) => (*version, *version),
+        GetServerProtocolVersion | GetStorageServerSummary | GetLatestLedgerInfo | GetBatch(_) => {
+            (0, 0)
+        },
+        GetStateValuesWithProof(request) => (request.version, request.version),
+        GetTransactionOutputsWithProof(request) => (request.start_version, request.end_version),
+        GetTransactionsWithProof(request) => (request.start_version, request.end_version),
+        GetNewTransactionsOrOutputsWithProof(request) => {
+            (request.known_version, request.known_version)
+        },
+        GetTransactionsOrOutputsWithProof(request) => (request.start_version, request.end_version),
+        GetTransactionRangeProof(request) => (request.start_version, request.end_version),
+        GetTransactionByHashWithProof(request) => (request.start_version, request.end_version),
+    };
+    (request.data_request.get_label(), start, end)
+}
+
+/// Returns the estimated serialized size (in bytes) of the response to the
+/// given request. The number of data items is bounded by the max chunk size
+/// for the request kind (as the server will truncate larger responses). For
+/// batch requests, this is the sum of the estimates for each batched request.
+pub fn estimate_response_bytes(
+    request: &StorageServiceRequest,
+    metadata: &ProtocolMetadata,
+) -> usize {
+    if let GetBatch(data_requests) = &request.data_request {
+        return data_requests
+            .iter()
+            .map(|data_request| {
+                let inner_request =
+                    StorageServiceRequest::new(data_request.clone(), request.use_compression);
+                estimate_response_bytes(&inner_request, metadata)
+            })
+            .fold(ESTIMATED_RESPONSE_OVERHEAD_BYTES, usize::saturating_add);
+    }
+
+    let num_items = get_num_requested_items(request).min(metadata.max_chunk_size_for(request));
+    usize::try_from(num_items)
+        .unwrap_or(usize::MAX)
+        .saturating_mul(ESTIMATED_RESPONSE_ITEM_BYTES)
+        .saturating_add(ESTIMATED_RESPONSE_OVERHEAD_BYTES)
+}
+
+/// Returns the estimated number of bytes to download when syncing the given
+/// version ranges, assuming each version costs `per_version_bytes`. The
+/// estimate is widened to `u128`, so summing many ranges cannot overflow.
+pub fn estimate_sync_bytes(
+    ranges: &[CompleteDataRange<Version>],
+    per_version_bytes: usize,
+) -> u128 {
+    ranges
+        .iter()
+        .map(|range| range.len_u128() * per_version_bytes as u128)
+        .sum()
+}
+
+/// Returns true iff the served range makes sufficient progress towards the
+/// requested range, i.e., the served range is a prefix of the requested range
+/// that covers at least `min_fraction` of it. Servers may truncate responses,
+/// but a peer that repeatedly serves a tiny prefix (e.g., a single version)
+/// of a large range should be penalized, as it barely makes any progress.
+pub fn makes_sufficient_progress(
+    served: &CompleteDataRange<Version>,
+    requested: &CompleteDataRange<Version>,
+    min_fraction: f64,
+) -> bool {
+    // Verify the served range is a prefix of the requested range
+    if served.lowest() != requested.lowest() || served.highest() > requested.highest() {
+        return false;
+    }
+
+    let num_served = served.len_u128();
+    let num_requested = requested.len_u128();
+    (num_served as f64 / num_requested as f64) >= min_fraction
+}
+
+/// Plans the given requests (in order) such that the estimated total size of
+/// the responses (see `estimate_response_bytes`) doesn't exceed the byte budget.
+/// Requests that don't fit in the remaining budget are shrunk to fit (if they
+/// fetch a data range), otherwise they are dropped.
+pub fn plan_within_byte_budget(
+    requests: &[StorageServiceRequest],
+    metadata: &ProtocolMetadata,
+    budget_bytes: usize,
+) -> Vec<StorageServiceRequest> {
+    let mut remaining_bytes = budget_bytes;
+    let mut planned_requests = vec![];
+    for request in requests {
+        // If the request fits in the remaining budget, add it to the plan
+        let estimated_bytes = estimate_response_bytes(request, metadata);
+        if estimated_bytes <= remaining_bytes {
+            remaining_bytes -= estimated_bytes;
+            planned_requests.push(request.clone());
+            continue;
+        }
+
+        // Otherwise, attempt to shrink the request to fit the remaining budget
+        let max_num_items = remaining_bytes
+            .checked_sub(ESTIMATED_RESPONSE_OVERHEAD_BYTES)
+            .map(|max_item_bytes| (max_item_bytes / ESTIMATED_RESPONSE_ITEM_BYTES) as u64)
+            .unwrap_or(0);
+        if let Some(truncated_request) = truncate_request(request, max_num_items) {
+            remaining_bytes -= estimate_response_bytes(&truncated_request, metadata);
+            planned_requests.push(truncated_request);
+        }
+    }
+    planned_requests
+}
+
+/// Returns the number of data items requested by the given request. Optimistic
+/// fetch requests are unbounded, and requests for a single proof or summary
+/// fetch at most one item. Batch requests are estimated separately (see
+/// `estimate_response_bytes`).
+fn get_num_requested_items(request: &StorageServiceRequest) -> u64 {
+    let (start, end) = match &request.data_request {
+        GetEpochEndingLedgerInfos(request) => (request.start_epoch, request.expected_end_epoch),
+        GetStateValuesWithProof(request) => (request.start_index, request.end_index),
+        GetTransactionOutputsWithProof(request) => (request.start_version, request.end_version),
+        GetTransactionsWithProof(request) => (request.start_version, request.end_version),
+        GetTransactionsOrOutputsWithProof(request) => (request.start_version, request.end_version),
+        GetNewTransactionOutputsWithProof(_)
+        | GetNewTransactionsWithProof(_)
+        | GetNewTransactionsOrOutputsWithProof(_) => return u64::MAX,
+        GetTransactionRangeProof(_) => return 0, // Only the proof is returned
+        GetEpochChangeProofBetween(request) => {
+            return request.end_epoch.saturating_sub(request.start_epoch)
+        },
+        GetLatestLedgerInfo
+        | GetNumberOfStatesAtVersion(_)
+        | GetServerProtocolVersion
+        | GetStorageServerSummary
+        | GetTransactionByHashWithProof(_) => return 1,
+        GetBatch(_) => return 0,
+    };
+    CompleteDataRange::new(start, end)
+        .and_then(|range| range.len())
+        .unwrap_or(0)
+}
+
+/// Truncates the given request to fetch at most the given number of data items
+/// (starting from the beginning of the requested range). If the request doesn't
+/// fetch a data range (or no items can be fetched), `None` is returned.
+fn truncate_request(
+    request: &StorageServiceRequest,
+    max_num_items: u64,
+) -> Option<StorageServiceRequest> {
+    if max_num_items == 0 {
+        return None;
+    }
+
+    let mut data_request = request.data_request.clone();
+    let (start, end) = match &mut data_request {
+        GetEpochEndingLedgerInfos(request) => {
+            (request.start_epoch, &mut request.expected_end_epoch)
+        },
+        GetStateValuesWithProof(request) => (request.start_index, &mut request.end_index),
+        GetTransactionOutputsWithProof(request) => {
+            (request.start_version, &mut request.end_version)
+        },
+        GetTransactionsWithProof(request) => (request.start_version, &mut request.end_version),
+        GetTransactionsOrOutputsWithProof(request) => {
+            (request.start_version, &mut request.end_version)
+        },
+        _ => return None,
+    };
+    let truncated_end = start.checked_add(max_num_items - 1)?;
+    *end = (*end).min(truncated_end);
+
+    Some(StorageServiceRequest::new(
+        data_request,
+        request.use_compression,
+    ))
+}
+
+#[cfg(test)]
+impl<T> CompleteDataRange<T>
+where
+    T: PrimInt + Arbitrary + 'static,
+{
+    /// Returns a strategy that only generates ranges with a length of at
+    /// most `max_len`. Note: `max_len` must be positive.
+    pub fn arbitrary_bounded(max_len: T) -> BoxedStrategy<Self> {
+        assert!(max_len > T::zero(), "max_len must be positive!");
+        (any::<T>(), any::<T>())
+            .prop_filter_map("degenerate or oversized range", move |(lowest, len)| {
+                // Map the length into [1, max_len]
+                let len = (len % max_len).checked_add(&T::one())?;
+                CompleteDataRange::from_len(lowest, len).ok()
+            })
+            .boxed()
+    }
+}
+
 #[cfg(test)]
 impl<T> Arbitrary for CompleteDataRange<T>
 where
     T: PrimInt + Arbitrary + 'static,
 {
-    type Parameters = ();
+    /// The maximum range length to generate (if any)
+    type Parameters = Option<T>;
     type Strategy = BoxedStrategy<Self>;
 
-    fn arbitrary_with(_args: Self::Parameters) -> Self::Strategy {
+    fn arbitrary_with(max_len: Self::Parameters) -> Self::Strategy {
+        if let Some(max_len) = max_len {
+            return Self::arbitrary_bounded(max_len);
+        }
+
         (any::<T>(), any::<T>())
             .prop_filter_map("degenerate range", |(lowest, highest)| {
                 CompleteDataRange::new(lowest, highest).ok()
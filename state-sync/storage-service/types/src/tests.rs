@@ -2,23 +2,58 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
+    proto::{CompleteDataRangeProto, DataSummaryProto, StorageServerSummaryProto},
     requests::{
-        DataRequest, EpochEndingLedgerInfoRequest, StateValuesWithProofRequest,
-        TransactionOutputsWithProofRequest, TransactionsOrOutputsWithProofRequest,
+        DataRequest, EpochChangeProofBetweenRequest, EpochEndingLedgerInfoRequest,
+        NewTransactionOutputsWithProofRequest, NewTransactionsOrOutputsWithProofRequest,
+        NewTransactionsWithProofRequest, RequestPriority, StateValuesWithProofRequest,
+        TransactionByHashWithProofRequest, TransactionOutputsWithProofRequest,
+        TransactionRangeProofRequest, TransactionsOrOutputsWithProofRequest,
         TransactionsWithProofRequest,
     },
-    responses::{CompleteDataRange, DataSummary, ProtocolMetadata},
-    Epoch, StorageServiceRequest,
+    responses::{
+        coalesce_transaction_requests, complete_data_range, contradicts, decode_many,
+        estimate_response_bytes, estimate_sync_bytes, fleet_progress, makes_sufficient_progress,
+        min_cover, normalize_requests, plan_within_byte_budget, redundancy_for,
+        requires_compression, single_source_ranges, specialize_fused_request,
+        try_concat_transactions, validate_epoch_proof_range, CompactStorageServerSummary,
+        CompleteDataRange, CompressionScheme, CoverageReport, DataCategory, DataResponse,
+        DataSummary, DataSummaryDelta, DecodedResponse, DeprecatedRequests, Error, FleetProgress,
+        FreshestCapable, IntoTxnsOrOutputs, MinimalEpochChangeProof, OptimisticFetchWindow,
+        OrderedRange, PeerSelector, ProtocolMetadata, ServerProtocolVersion, ServiceMetrics,
+        StorageServerSummary, StorageServiceResponse, TransactionOrOutputListWithProof,
+        TxnsOrOutputs, UnserviceableReason, ESTIMATED_RESPONSE_ITEM_BYTES,
+        ESTIMATED_RESPONSE_OVERHEAD_BYTES, OPTIMISTIC_FETCH_VERSION_DELTA,
+        RAW_TOLERATED_RESPONSE_LABELS,
+    },
+    Epoch, StorageServiceMessage, StorageServiceRequest,
 };
-use aptos_crypto::hash::HashValue;
+use aptos_config::config::{StorageServiceConfig, MAX_APPLICATION_MESSAGE_SIZE};
+use aptos_crypto::hash::{CryptoHash, HashValue};
 use aptos_types::{
     aggregate_signature::AggregateSignature,
     block_info::BlockInfo,
+    epoch_change::EpochChangeProof,
     ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
-    transaction::Version,
+    proof::{
+        SparseMerkleRangeProof, TransactionAccumulatorInternalNode,
+        TransactionAccumulatorRangeProof, TransactionInfoListWithProof,
+    },
+    state_store::{
+        state_key::StateKey,
+        state_value::{StateValue, StateValueChunkWithProof},
+    },
+    transaction::{
+        ExecutionStatus, Transaction, TransactionInfo, TransactionListWithProof,
+        TransactionOutputListWithProof, TransactionWithProof, Version,
+    },
 };
 use claims::{assert_err, assert_ok};
+use num_traits::PrimInt;
 use proptest::{arbitrary::any, prelude::*};
+use prost::Message;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{borrow::Cow, collections::HashSet, fmt::Debug};
 
 #[test]
 fn test_complete_data_range() {
@@ -40,6 +75,575 @@ fn test_complete_data_range() {
     assert_err!(CompleteDataRange::new(0, u64::MAX));
 }
 
+#[test]
+fn test_complete_data_range_from_str() {
+    // Verify valid ranges are parsed
+    for (range, expected_range) in [
+        ("100-200", create_range(100, 200)),
+        ("0-0", create_range(0, 0)),
+        (" 5 - 10 ", create_range(5, 10)),
+        ("0-18446744073709551614", create_range(0, u64::MAX - 1)),
+    ] {
+        assert_eq!(
+            range.parse::<CompleteDataRange<u64>>().unwrap(),
+            expected_range
+        );
+    }
+    assert_eq!(
+        CompleteDataRange::try_from_bounds(100, 200).unwrap(),
+        create_range(100, 200)
+    );
+
+    // Verify reversed ranges are rejected
+    assert_eq!(
+        "200-100".parse::<CompleteDataRange<u64>>(),
+        Err(Error::DegenerateRangeError)
+    );
+    assert_err!(CompleteDataRange::try_from_bounds(200, 100));
+
+    // Verify malformed and non-numeric ranges are rejected
+    for range in [
+        "",
+        "100",
+        "100-",
+        "-200",
+        "a-b",
+        "100-abc",
+        "1.5-2",
+        "100-200-300",
+        "-1-5",
+        "0-18446744073709551616",
+    ] {
+        assert_eq!(
+            range.parse::<CompleteDataRange<u64>>(),
+            Err(Error::DegenerateRangeError)
+        );
+    }
+}
+
+#[test]
+fn test_complete_data_range_from_genesis_checked() {
+    // good ranges
+    assert_eq!(
+        CompleteDataRange::from_genesis_checked(0).unwrap(),
+        create_range(0, 0)
+    );
+    assert_eq!(
+        CompleteDataRange::from_genesis_checked(u64::MAX - 1).unwrap(),
+        create_range(0, u64::MAX - 1)
+    );
+
+    // range length overflow edge case
+    assert_err!(CompleteDataRange::from_genesis_checked(u64::MAX));
+    assert_err!(CompleteDataRange::<u8>::from_genesis_checked(u8::MAX));
+}
+
+#[test]
+fn test_complete_data_range_chunks_by_estimated_bytes() {
+    for (range, per_item_bytes, proof_overhead, max_bytes) in [
+        (create_range(0, 0), 10, 100, 110),
+        (create_range(0, 999), 10, 100, 1000),
+        (create_range(100, 1000), 7, 33, 500),
+        (create_range(100, 1000), 1000, 0, 1000),
+        (create_range(100, 1000), 0, 50, 100),
+        (create_range(u64::MAX - 10, u64::MAX), 1, 1, 4),
+    ] {
+        let chunks = range.chunks_by_estimated_bytes(per_item_bytes, proof_overhead, max_bytes);
+
+        // Verify no chunk's estimated size exceeds the limit
+        for chunk in &chunks {
+            let estimated_bytes = (chunk.len().unwrap() as usize) * per_item_bytes + proof_overhead;
+            assert!(estimated_bytes <= max_bytes);
+        }
+
+        // Verify the chunks reassemble the range
+        assert_eq!(chunks.first().unwrap().lowest(), range.lowest());
+        assert_eq!(chunks.last().unwrap().highest(), range.highest());
+        for (chunk, next_chunk) in chunks.iter().zip(chunks.iter().skip(1)) {
+            assert_eq!(chunk.highest() + 1, next_chunk.lowest());
+        }
+    }
+
+    // Verify no chunks are returned if not even a single item fits
+    let range = create_range(0, 10);
+    assert!(range.chunks_by_estimated_bytes(10, 100, 109).is_empty());
+    assert!(range.chunks_by_estimated_bytes(0, 100, 99).is_empty());
+}
+
+#[test]
+fn test_complete_data_range_to_transaction_requests() {
+    for (lowest, highest, max_chunk, expected_num_requests) in [
+        (0, 0, 1, 1),
+        (0, 99, 10, 10),
+        (0, 100, 10, 11),
+        (50, 60, 100, 1),
+        (5, 14, 1, 10),
+        (u64::MAX - 10, u64::MAX - 1, 3, 4),
+    ] {
+        let range = create_range(lowest, highest);
+        let requests = range.to_transaction_requests(1000, max_chunk);
+        assert_eq!(requests.len(), expected_num_requests);
+
+        // Verify each request respects the max chunk and shares the proof version
+        let mut next_version = lowest;
+        let mut last_end_version = None;
+        for request in requests {
+            let transactions_request = match request.data_request {
+                DataRequest::GetTransactionsWithProof(transactions_request) => transactions_request,
+                data_request => panic!("Unexpected data request: {:?}", data_request),
+            };
+            assert_eq!(transactions_request.proof_version, 1000);
+            assert!(
+                transactions_request.end_version - transactions_request.start_version < max_chunk
+            );
+
+            // Verify the requests are contiguous
+            assert_eq!(transactions_request.start_version, next_version);
+            next_version = transactions_request.end_version.saturating_add(1);
+            last_end_version = Some(transactions_request.end_version);
+        }
+
+        // Verify the requests cover the entire range
+        assert_eq!(last_end_version, Some(highest));
+    }
+
+    // Verify no requests are returned for a zero max chunk
+    assert!(create_range(0, 10)
+        .to_transaction_requests(10, 0)
+        .is_empty());
+}
+
+#[test]
+fn test_complete_data_range_expand_to_include() {
+    let range = create_range(100, 200);
+
+    // Verify expanding below, above and within the range
+    assert_eq!(range.expand_to_include(50).unwrap(), create_range(50, 200));
+    assert_eq!(range.expand_to_include(99).unwrap(), create_range(99, 200));
+    assert_eq!(
+        range.expand_to_include(201).unwrap(),
+        create_range(100, 201)
+    );
+    assert_eq!(
+        range.expand_to_include(300).unwrap(),
+        create_range(100, 300)
+    );
+    for item in [100, 150, 200] {
+        assert_eq!(range.expand_to_include(item).unwrap(), range);
+    }
+
+    // Verify the range length overflow edge case
+    assert_err!(create_range(1, u64::MAX).expand_to_include(0));
+    assert_err!(create_range(0, u64::MAX - 1).expand_to_include(u64::MAX));
+    assert_ok!(create_range(1, u64::MAX - 1).expand_to_include(0));
+    assert_ok!(create_range(1, u64::MAX - 1).expand_to_include(u64::MAX));
+}
+
+#[test]
+fn test_complete_data_range_len_u128() {
+    // Verify the widened length matches the length
+    for (lowest, highest) in [(0, 0), (10, 20), (0, u64::MAX - 1), (1, u64::MAX)] {
+        let range = create_range(lowest, highest);
+        assert_eq!(range.len_u128(), range.len().unwrap() as u128);
+    }
+
+    // Verify the lengths of several near-maximum ranges can be summed
+    let ranges = [
+        create_range(0, u64::MAX - 1),
+        create_range(1, u64::MAX),
+        create_range(5, u64::MAX - 5),
+        create_range(u64::MAX, u64::MAX),
+    ];
+    let total_length: u128 = ranges.iter().map(|range| range.len_u128()).sum();
+    let expected_length = 2 * u64::MAX as u128 + (u64::MAX as u128 - 9) + 1;
+    assert_eq!(total_length, expected_length);
+}
+
+#[test]
+fn test_complete_data_range_chunks_rev() {
+    // Verify exact divisions
+    let range = create_range(100, 199);
+    let chunks: Vec<_> = range.chunks_rev(25).collect();
+    assert_eq!(chunks, vec![
+        create_range(175, 199),
+        create_range(150, 174),
+        create_range(125, 149),
+        create_range(100, 124),
+    ]);
+    verify_reassembled_chunks(range, chunks);
+
+    // Verify non-exact divisions (the lowest chunk is shorter)
+    let chunks: Vec<_> = range.chunks_rev(30).collect();
+    assert_eq!(chunks, vec![
+        create_range(170, 199),
+        create_range(140, 169),
+        create_range(110, 139),
+        create_range(100, 109),
+    ]);
+    verify_reassembled_chunks(range, chunks);
+
+    // Verify chunk sizes that cover the entire range (or nothing at all)
+    for chunk_size in [100, 101, u64::MAX] {
+        let chunks: Vec<_> = range.chunks_rev(chunk_size).collect();
+        assert_eq!(chunks, vec![range]);
+    }
+    assert_eq!(range.chunks_rev(0).count(), 0);
+
+    // Verify the chunks don't underflow at the minimum value
+    let range = create_range(0, 9);
+    let chunks: Vec<_> = range.chunks_rev(4).collect();
+    assert_eq!(chunks, vec![
+        create_range(6, 9),
+        create_range(2, 5),
+        create_range(0, 1),
+    ]);
+    verify_reassembled_chunks(range, chunks);
+
+    let range = CompleteDataRange::new(i64::MIN, i64::MIN + 9).unwrap();
+    let chunks: Vec<_> = range.chunks_rev(4).collect();
+    assert_eq!(chunks.len(), 3);
+    assert_eq!(chunks[2].lowest(), i64::MIN);
+    verify_reassembled_chunks(range, chunks);
+
+    let range = CompleteDataRange::from_genesis(u64::MAX - 1);
+    let chunks: Vec<_> = range.chunks_rev(u64::MAX / 2).collect();
+    assert_eq!(chunks.len(), 3);
+    verify_reassembled_chunks(range, chunks);
+}
+
+#[test]
+fn test_complete_data_range_coverage_fraction() {
+    let desired_range = create_range(100, 199);
+
+    // Verify partial overlaps (on either end, or within the desired range)
+    assert_eq!(create_range(0, 149).coverage_fraction(&desired_range), 0.5);
+    assert_eq!(
+        create_range(175, 500).coverage_fraction(&desired_range),
+        0.25
+    );
+    assert_eq!(
+        create_range(110, 119).coverage_fraction(&desired_range),
+        0.1
+    );
+    assert_eq!(
+        create_range(199, 199).coverage_fraction(&desired_range),
+        0.01
+    );
+
+    // Verify no overlap
+    assert_eq!(create_range(0, 99).coverage_fraction(&desired_range), 0.0);
+    assert_eq!(
+        create_range(200, 300).coverage_fraction(&desired_range),
+        0.0
+    );
+
+    // Verify full coverage
+    assert_eq!(desired_range.coverage_fraction(&desired_range), 1.0);
+    assert_eq!(create_range(0, 1000).coverage_fraction(&desired_range), 1.0);
+    assert_eq!(
+        CompleteDataRange::from_genesis(u64::MAX - 1).coverage_fraction(&desired_range),
+        1.0
+    );
+}
+
+#[test]
+fn test_complete_data_range_request_count_for() {
+    // Verify the request count for exact multiples of the chunk size
+    let range = create_range(100, 199);
+    assert_eq!(range.request_count_for(1).unwrap(), 100);
+    assert_eq!(range.request_count_for(10).unwrap(), 10);
+    assert_eq!(range.request_count_for(100).unwrap(), 1);
+
+    // Verify the request count when there is a remainder
+    assert_eq!(range.request_count_for(3).unwrap(), 34);
+    assert_eq!(range.request_count_for(99).unwrap(), 2);
+    assert_eq!(range.request_count_for(1000).unwrap(), 1);
+    assert_eq!(create_range(5, 5).request_count_for(10).unwrap(), 1);
+
+    // Verify the request count doesn't overflow for the largest ranges
+    let range = CompleteDataRange::from_genesis(u64::MAX - 1);
+    assert_eq!(range.request_count_for(u64::MAX).unwrap(), 1);
+    assert_eq!(range.request_count_for(2).unwrap(), u64::MAX / 2 + 1);
+    assert_ok!(range.request_count_for(1)); // Exactly u64::MAX requests
+
+    // Verify that a zero chunk size returns an error
+    assert_err!(range.request_count_for(0));
+    assert_err!(create_range(100, 199).request_count_for(0));
+}
+
+#[test]
+fn test_complete_data_range_intersection_len() {
+    // Verify the length matches the length of the intersection
+    let range = create_range(100, 200);
+    for other in [
+        create_range(150, 300), // Overlapping
+        create_range(120, 180), // Contained
+        create_range(0, 1000),  // Containing
+        create_range(200, 300), // Touching (at the highest item)
+        create_range(0, 100),   // Touching (at the lowest item)
+        create_range(201, 300), // Disjoint (adjacent)
+        create_range(0, 50),    // Disjoint
+    ] {
+        let expected_len = range
+            .intersection(&other)
+            .map(|intersection| intersection.len().unwrap())
+            .unwrap_or(0);
+        assert_eq!(range.intersection_len(&other), expected_len);
+        assert_eq!(other.intersection_len(&range), expected_len);
+    }
+    assert_eq!(range.intersection_len(&create_range(200, 300)), 1);
+    assert_eq!(range.intersection_len(&create_range(201, 300)), 0);
+
+    // Verify large overlaps don't overflow
+    let full_range = create_range(0, u64::MAX);
+    assert_eq!(full_range.intersection_len(&full_range), u64::MAX);
+    assert_eq!(
+        full_range.intersection_len(&create_range(1, u64::MAX)),
+        u64::MAX
+    );
+    assert_eq!(
+        create_range(u64::MAX, u64::MAX).intersection_len(&full_range),
+        1
+    );
+}
+
+#[test]
+fn test_complete_data_range_complement_within() {
+    let universe = create_range(0, 100);
+
+    // Verify the complement of a range at the start of the universe
+    assert_eq!(create_range(0, 10).complement_within(&universe), vec![
+        create_range(11, 100)
+    ]);
+
+    // Verify the complement of a range in the middle of the universe
+    assert_eq!(create_range(20, 30).complement_within(&universe), vec![
+        create_range(0, 19),
+        create_range(31, 100)
+    ]);
+
+    // Verify the complement of a range at the end of the universe
+    assert_eq!(create_range(90, 100).complement_within(&universe), vec![
+        create_range(0, 89)
+    ]);
+
+    // Verify the complement of the universe (or a superset) is empty
+    assert!(universe.complement_within(&universe).is_empty());
+    assert!(create_range(0, 200).complement_within(&universe).is_empty());
+
+    // Verify the complement of a disjoint range is the entire universe
+    assert_eq!(create_range(150, 200).complement_within(&universe), vec![
+        universe
+    ]);
+    assert_eq!(
+        create_range(0, 10).complement_within(&create_range(50, 100)),
+        vec![create_range(50, 100)]
+    );
+
+    // Verify the complement doesn't overflow at the bounds of the domain
+    let universe = create_range(0, u64::MAX);
+    assert_eq!(
+        create_range(1, u64::MAX - 1).complement_within(&universe),
+        vec![create_range(0, 0), create_range(u64::MAX, u64::MAX)]
+    );
+}
+
+#[test]
+fn test_complete_data_range_partition_around() {
+    let range = create_range(100, 199);
+
+    // Verify a pivot below the range
+    assert_eq!(range.partition_around(50), (None, false, Some(range)));
+
+    // Verify a pivot at the lowest bound
+    assert_eq!(
+        range.partition_around(100),
+        (None, true, Some(create_range(101, 199)))
+    );
+
+    // Verify a pivot in the middle of the range
+    assert_eq!(
+        range.partition_around(150),
+        (
+            Some(create_range(100, 149)),
+            true,
+            Some(create_range(151, 199))
+        )
+    );
+
+    // Verify a pivot at the highest bound
+    assert_eq!(
+        range.partition_around(199),
+        (Some(create_range(100, 198)), true, None)
+    );
+
+    // Verify a pivot above the range
+    assert_eq!(range.partition_around(200), (Some(range), false, None));
+
+    // Verify single item ranges and the ends of the domain
+    assert_eq!(create_range(5, 5).partition_around(5), (None, true, None));
+    assert_eq!(
+        create_range(0, u64::MAX - 1).partition_around(0),
+        (None, true, Some(create_range(1, u64::MAX - 1)))
+    );
+    assert_eq!(
+        create_range(1, u64::MAX).partition_around(u64::MAX),
+        (Some(create_range(1, u64::MAX - 1)), true, None)
+    );
+}
+
+#[test]
+fn test_complete_data_range_with_bounds() {
+    let range = create_range(10, 20);
+
+    // Verify the highest value can be raised and lowered (to the lowest value)
+    assert_eq!(range.with_highest(30).unwrap(), create_range(10, 30));
+    assert_eq!(range.with_highest(10).unwrap(), create_range(10, 10));
+    assert_eq!(range.with_highest(20).unwrap(), range);
+
+    // Verify the highest value can't be lowered below the lowest value
+    assert_eq!(
+        range.with_highest(9).unwrap_err(),
+        Error::DegenerateRangeError
+    );
+    assert_err!(range.with_highest(0));
+
+    // Verify the lowest value can be lowered and raised (to the highest value)
+    assert_eq!(range.with_lowest(0).unwrap(), create_range(0, 20));
+    assert_eq!(range.with_lowest(20).unwrap(), create_range(20, 20));
+    assert_eq!(range.with_lowest(10).unwrap(), range);
+
+    // Verify the lowest value can't be raised above the highest value
+    assert_eq!(
+        range.with_lowest(21).unwrap_err(),
+        Error::DegenerateRangeError
+    );
+    assert_err!(range.with_lowest(u64::MAX));
+
+    // Verify ranges with overflowing lengths are rejected
+    let range = create_range(0, 20);
+    assert_err!(range.with_highest(u64::MAX));
+}
+
+#[test]
+fn test_complete_data_range_step_by() {
+    // Verify a step of one equals a full iteration of the range
+    let range = create_range(100, 199);
+    let items: Vec<_> = range.step_by(1).collect();
+    assert_eq!(items, (100..=199).collect::<Vec<_>>());
+
+    // Verify a step that lands exactly on the highest item
+    let items: Vec<_> = range.step_by(33).collect();
+    assert_eq!(items, vec![100, 133, 166, 199]);
+
+    // Verify a step that overshoots the highest item
+    let items: Vec<_> = range.step_by(40).collect();
+    assert_eq!(items, vec![100, 140, 180]);
+    let items: Vec<_> = range.step_by(1000).collect();
+    assert_eq!(items, vec![100]);
+
+    // Verify a step of zero returns no items
+    assert_eq!(range.step_by(0).count(), 0);
+
+    // Verify stepping doesn't overflow at the end of the domain
+    let range = create_range(u64::MAX - 10, u64::MAX);
+    let items: Vec<_> = range.step_by(5).collect();
+    assert_eq!(items, vec![u64::MAX - 10, u64::MAX - 5, u64::MAX]);
+    let items: Vec<_> = range.step_by(u64::MAX).collect();
+    assert_eq!(items, vec![u64::MAX - 10]);
+    let range = CompleteDataRange::from_genesis(u64::MAX - 1);
+    let items: Vec<_> = range.step_by(u64::MAX / 2).collect();
+    assert_eq!(items, vec![0, u64::MAX / 2, (u64::MAX / 2) * 2]);
+}
+
+#[test]
+fn test_complete_data_range_nth() {
+    // Verify the first and last indices
+    let range = create_range(100, 199);
+    assert_eq!(range.nth(0), Some(100));
+    assert_eq!(range.nth(50), Some(150));
+    assert_eq!(range.nth(99), Some(199));
+
+    // Verify out of bounds indices
+    assert_eq!(range.nth(100), None);
+    assert_eq!(range.nth(u64::MAX), None);
+
+    // Verify single item ranges
+    let range = create_range(10, 10);
+    assert_eq!(range.nth(0), Some(10));
+    assert_eq!(range.nth(1), None);
+
+    // Verify indexing doesn't overflow at the ends of the domain
+    let range = CompleteDataRange::from_genesis(u64::MAX - 1);
+    assert_eq!(range.nth(u64::MAX - 1), Some(u64::MAX - 1));
+    assert_eq!(range.nth(u64::MAX), None);
+    let range = create_range(u64::MAX, u64::MAX);
+    assert_eq!(range.nth(0), Some(u64::MAX));
+    assert_eq!(range.nth(1), None);
+}
+
+#[test]
+fn test_ordered_range() {
+    // good ranges
+    assert_ok!(OrderedRange::new(WrappedVersion(0), WrappedVersion(0)));
+    assert_ok!(OrderedRange::new(WrappedVersion(10), WrappedVersion(20)));
+    assert_ok!(OrderedRange::new(
+        WrappedVersion(u64::MAX),
+        WrappedVersion(u64::MAX)
+    ));
+
+    // degenerate ranges
+    assert_err!(OrderedRange::new(WrappedVersion(1), WrappedVersion(0)));
+    assert_err!(OrderedRange::new(WrappedVersion(20), WrappedVersion(10)));
+
+    // containment
+    let range = create_ordered_range(100, 200);
+    assert!(range.contains(&WrappedVersion(100)));
+    assert!(range.contains(&WrappedVersion(150)));
+    assert!(range.contains(&WrappedVersion(200)));
+    assert!(!range.contains(&WrappedVersion(99)));
+    assert!(!range.contains(&WrappedVersion(201)));
+
+    // supersets
+    assert!(range.superset_of(&create_ordered_range(100, 200)));
+    assert!(range.superset_of(&create_ordered_range(125, 175)));
+    assert!(!range.superset_of(&create_ordered_range(99, 200)));
+    assert!(!range.superset_of(&create_ordered_range(100, 201)));
+}
+
+#[test]
+fn test_ordered_range_intersection() {
+    let range = create_ordered_range(100, 200);
+
+    // overlapping ranges
+    for (lowest, highest, expected_lowest, expected_highest) in [
+        (100, 200, 100, 200),
+        (50, 150, 100, 150),
+        (150, 250, 150, 200),
+        (125, 175, 125, 175),
+        (0, 300, 100, 200),
+        (200, 300, 200, 200),
+        (0, 100, 100, 100),
+    ] {
+        let other = create_ordered_range(lowest, highest);
+        assert!(range.overlaps(&other));
+        assert!(other.overlaps(&range));
+        assert_eq!(
+            range.intersection(&other),
+            Some(create_ordered_range(expected_lowest, expected_highest))
+        );
+        assert_eq!(range.intersection(&other), other.intersection(&range));
+    }
+
+    // disjoint ranges
+    for (lowest, highest) in [(0, 99), (201, 300)] {
+        let other = create_ordered_range(lowest, highest);
+        assert!(!range.overlaps(&other));
+        assert!(!other.overlaps(&range));
+        assert_eq!(range.intersection(&other), None);
+    }
+}
+
 #[test]
 fn test_data_summary_can_service_epochs_request() {
     let summary = DataSummary {
@@ -67,6 +671,72 @@ fn test_data_summary_can_service_epochs_request() {
     }
 }
 
+#[test]
+fn test_data_summary_can_service_epochs_request_edges() {
+    let summary = DataSummary {
+        epoch_ending_ledger_infos: Some(create_range(0, 200)),
+        ..Default::default()
+    };
+
+    for compression in [true, false] {
+        // single epoch requests that are covered can be serviced
+        for epoch in [0, 1, 100, 200] {
+            let request = epochs_request(epoch, epoch, compression);
+            assert!(summary.can_service(&request));
+            assert_ok!(summary.can_service_with_reason(&request));
+        }
+
+        // single epoch requests that aren't covered can't be serviced
+        let request = epochs_request(201, 201, compression);
+        assert!(!summary.can_service(&request));
+        assert_eq!(
+            summary.can_service_with_reason(&request),
+            Err(UnserviceableReason::DataUnavailable)
+        );
+
+        // requests where start > end are rejected as degenerate
+        for (start, end) in [(1, 0), (200, 100), (u64::MAX, 0)] {
+            let request = epochs_request(start, end, compression);
+            assert!(!summary.can_service(&request));
+            assert_eq!(
+                summary.can_service_with_reason(&request),
+                Err(UnserviceableReason::DegenerateRange)
+            );
+        }
+    }
+}
+
+#[test]
+fn test_data_summary_can_service_epochs_request_synced_epoch() {
+    for compression in [true, false] {
+        let request = epochs_request(100, 150, compression);
+
+        // Verify a peer with a synced ledger info ahead of (or at) the end epoch can service the request
+        for synced_epoch in [150, 151, 300] {
+            let summary = DataSummary {
+                synced_ledger_info: Some(create_mock_ledger_info_at_epoch(synced_epoch, 1000)),
+                epoch_ending_ledger_infos: Some(create_range(0, 200)),
+                ..Default::default()
+            };
+            assert_ok!(summary.can_service_with_reason(&request));
+        }
+
+        // Verify a peer with a synced ledger info behind the end epoch can't service the request
+        for synced_epoch in [0, 100, 149] {
+            let summary = DataSummary {
+                synced_ledger_info: Some(create_mock_ledger_info_at_epoch(synced_epoch, 1000)),
+                epoch_ending_ledger_infos: Some(create_range(0, 200)),
+                ..Default::default()
+            };
+            assert!(!summary.can_service(&request));
+            assert_eq!(
+                summary.can_service_with_reason(&request),
+                Err(UnserviceableReason::SyncedEpochTooLow)
+            );
+        }
+    }
+}
+
 #[test]
 fn test_data_summary_can_service_txns_request() {
     let summary = DataSummary {
@@ -185,6 +855,74 @@ fn test_data_summary_can_service_txns_or_outputs_request() {
     }
 }
 
+#[test]
+fn test_data_summary_can_service_txn_range_proof_request() {
+    let summary = DataSummary {
+        synced_ledger_info: Some(create_mock_ledger_info(250)),
+        transactions: Some(create_range(100, 200)),
+        ..Default::default()
+    };
+
+    for compression in [true, false] {
+        // in range and can provide proof => can service
+        assert!(summary.can_service(&txn_range_proof_request(225, 100, 200, compression)));
+        assert!(summary.can_service(&txn_range_proof_request(225, 125, 175, compression)));
+        assert!(summary.can_service(&txn_range_proof_request(225, 150, 150, compression)));
+        assert!(summary.can_service(&txn_range_proof_request(250, 200, 200, compression)));
+
+        // can provide proof, but out of range => cannot service
+        assert!(!summary.can_service(&txn_range_proof_request(225, 99, 200, compression)));
+        assert!(!summary.can_service(&txn_range_proof_request(225, 100, 201, compression)));
+        assert!(!summary.can_service(&txn_range_proof_request(225, 50, 250, compression)));
+
+        // in range, but cannot provide proof => cannot service
+        assert!(!summary.can_service(&txn_range_proof_request(251, 100, 200, compression)));
+        assert!(!summary.can_service(&txn_range_proof_request(300, 150, 150, compression)));
+
+        // invalid range
+        assert!(!summary.can_service(&txn_range_proof_request(225, 175, 125, compression)));
+    }
+
+    // no transactions held => cannot service
+    let summary = DataSummary {
+        synced_ledger_info: Some(create_mock_ledger_info(250)),
+        ..Default::default()
+    };
+    assert!(!summary.can_service(&txn_range_proof_request(225, 100, 200, false)));
+}
+
+#[test]
+fn test_txn_range_proof_labels_and_conversion() {
+    // Verify the request labels
+    let request = txn_range_proof_request(225, 100, 200, false);
+    assert_eq!(request.get_label(), "get_transaction_range_proof");
+    let request = txn_range_proof_request(225, 100, 200, true);
+    assert_eq!(
+        request.get_label(),
+        "get_transaction_range_proof_compressed"
+    );
+
+    // Verify the response labels and conversions
+    let range_proof = TransactionAccumulatorRangeProof::new_empty();
+    let data_response = DataResponse::TransactionRangeProof(range_proof.clone());
+    assert_eq!(data_response.get_label(), "transaction_range_proof");
+    for compression in [true, false] {
+        let response = StorageServiceResponse::new(data_response.clone(), compression).unwrap();
+        assert_eq!(
+            TransactionAccumulatorRangeProof::try_from(response).unwrap(),
+            range_proof
+        );
+    }
+
+    // Verify that converting a different response type fails
+    let response = StorageServiceResponse::new(
+        DataResponse::TransactionsWithProof(TransactionListWithProof::new_empty()),
+        false,
+    )
+    .unwrap();
+    assert_err!(TransactionAccumulatorRangeProof::try_from(response));
+}
+
 #[test]
 fn test_data_summary_can_service_state_chunk_request() {
     let summary = DataSummary {
@@ -209,6 +947,63 @@ fn test_data_summary_can_service_state_chunk_request() {
     }
 }
 
+#[test]
+fn test_data_summary_can_service_observed() {
+    let summary = DataSummary {
+        synced_ledger_info: Some(create_mock_ledger_info(250)),
+        transactions: Some(create_range(100, 200)),
+        ..Default::default()
+    };
+    let metrics = ServiceMetrics::new();
+    let txns_label = "get_transactions_with_proof";
+    let epochs_label = "get_epoch_ending_ledger_infos";
+
+    // Check a set of serviceable requests
+    for _ in 0..3 {
+        assert!(summary.can_service_observed(&txns_request(225, 100, 200, false), &metrics));
+    }
+    assert_eq!(metrics.num_accepted(txns_label), 3);
+
+    // Check a set of unserviceable requests (for different reasons)
+    assert!(!summary.can_service_observed(&txns_request(225, 50, 200, false), &metrics));
+    assert!(!summary.can_service_observed(&txns_request(225, 150, 250, true), &metrics));
+    assert!(!summary.can_service_observed(&txns_request(300, 100, 200, false), &metrics));
+    assert!(!summary.can_service_observed(&txns_request(225, 200, 100, false), &metrics));
+    assert!(!summary.can_service_observed(&epochs_request(0, 10, false), &metrics));
+
+    // Verify the counters advanced correctly
+    assert_eq!(metrics.num_accepted(txns_label), 3);
+    assert_eq!(
+        metrics.num_rejected(txns_label, UnserviceableReason::DataUnavailable),
+        2
+    );
+    assert_eq!(
+        metrics.num_rejected(txns_label, UnserviceableReason::ProofUnavailable),
+        1
+    );
+    assert_eq!(
+        metrics.num_rejected(txns_label, UnserviceableReason::DegenerateRange),
+        1
+    );
+    assert_eq!(metrics.num_accepted(epochs_label), 0);
+    assert_eq!(
+        metrics.num_rejected(epochs_label, UnserviceableReason::DataUnavailable),
+        1
+    );
+
+    // Verify the observed and plain checks agree
+    for request in [
+        txns_request(225, 100, 200, false),
+        txns_request(300, 100, 200, false),
+        epochs_request(0, 10, false),
+    ] {
+        assert_eq!(
+            summary.can_service(&request),
+            summary.can_service_observed(&request, &metrics)
+        );
+    }
+}
+
 #[test]
 fn test_protocol_metadata_can_service() {
     let metadata = ProtocolMetadata {
@@ -233,41 +1028,3772 @@ fn test_protocol_metadata_can_service() {
     }
 }
 
-proptest! {
-    #![proptest_config(ProptestConfig::with_cases(1000))]
+#[test]
+fn test_protocol_metadata_max_chunk_size_for() {
+    // Create protocol metadata with distinct chunk sizes
+    let protocol_metadata = ProtocolMetadata {
+        max_epoch_chunk_size: 1,
+        max_state_chunk_size: 2,
+        max_transaction_chunk_size: 3,
+        max_transaction_output_chunk_size: 4,
+    };
 
-    #[test]
-    fn test_data_summary_length_invariant(range in any::<CompleteDataRange<u64>>()) {
-        // should not panic
-        let _ = range.len();
+    // Verify the correct chunk size is returned for each request kind
+    let new_transactions_request =
+        DataRequest::GetNewTransactionsWithProof(NewTransactionsWithProofRequest {
+            known_version: 100,
+            known_epoch: 10,
+            include_events: false,
+        });
+    let new_outputs_request =
+        DataRequest::GetNewTransactionOutputsWithProof(NewTransactionOutputsWithProofRequest {
+            known_version: 100,
+            known_epoch: 10,
+        });
+    let new_transactions_or_outputs_request = DataRequest::GetNewTransactionsOrOutputsWithProof(
+        NewTransactionsOrOutputsWithProofRequest {
+            known_version: 100,
+            known_epoch: 10,
+            include_events: false,
+            max_num_output_reductions: 0,
+        },
+    );
+    for (request, expected_chunk_size) in [
+        (epochs_request(0, 10, false), 1),
+        (state_values_request(100, 0, 10, false), 2),
+        (txns_request(200, 100, 199, false), 3),
+        (
+            StorageServiceRequest::new(new_transactions_request, false),
+            3,
+        ),
+        (outputs_request(200, 100, 199, false), 4),
+        (StorageServiceRequest::new(new_outputs_request, false), 4),
+        (txns_or_outputs_request(200, 100, 199, false), 4),
+        (
+            StorageServiceRequest::new(new_transactions_or_outputs_request, false),
+            4,
+        ),
+        (
+            StorageServiceRequest::new(DataRequest::GetNumberOfStatesAtVersion(100), false),
+            u64::MAX,
+        ),
+        (txn_range_proof_request(200, 100, 199, false), u64::MAX),
+        (txn_by_hash_request(200, 100, 199, false), u64::MAX),
+        (
+            StorageServiceRequest::new(DataRequest::GetServerProtocolVersion, false),
+            u64::MAX,
+        ),
+        (
+            StorageServiceRequest::new(DataRequest::GetStorageServerSummary, false),
+            u64::MAX,
+        ),
+        (
+            StorageServiceRequest::new(DataRequest::GetLatestLedgerInfo, false),
+            u64::MAX,
+        ),
+    ] {
+        assert_eq!(
+            protocol_metadata.max_chunk_size_for(&request),
+            expected_chunk_size
+        );
     }
 }
 
-fn create_mock_ledger_info(version: Version) -> LedgerInfoWithSignatures {
-    LedgerInfoWithSignatures::new(
-        LedgerInfo::new(
-            BlockInfo::new(0, 0, HashValue::zero(), HashValue::zero(), version, 0, None),
-            HashValue::zero(),
-        ),
-        AggregateSignature::empty(),
-    )
+#[test]
+fn test_protocol_metadata_chunk_sizes_shrank_since() {
+    // Create protocol metadata with distinct chunk sizes
+    let previous_metadata = ProtocolMetadata {
+        max_epoch_chunk_size: 100,
+        max_state_chunk_size: 200,
+        max_transaction_chunk_size: 300,
+        max_transaction_output_chunk_size: 400,
+    };
+
+    // Verify unchanged metadata hasn't shrunk
+    assert!(!previous_metadata.chunk_sizes_shrank_since(&previous_metadata));
+
+    // Verify shrinking any single chunk size is detected
+    let shrink_fns: [fn(&mut ProtocolMetadata); 4] = [
+        |metadata| metadata.max_epoch_chunk_size -= 1,
+        |metadata| metadata.max_state_chunk_size -= 1,
+        |metadata| metadata.max_transaction_chunk_size -= 1,
+        |metadata| metadata.max_transaction_output_chunk_size -= 1,
+    ];
+    for shrink_fn in shrink_fns {
+        let mut metadata = previous_metadata.clone();
+        shrink_fn(&mut metadata);
+        assert!(metadata.chunk_sizes_shrank_since(&previous_metadata));
+        assert!(!previous_metadata.chunk_sizes_shrank_since(&metadata));
+    }
+
+    // Verify growing a chunk size isn't reported as shrinking
+    let metadata = ProtocolMetadata {
+        max_state_chunk_size: 1000,
+        ..previous_metadata.clone()
+    };
+    assert!(!metadata.chunk_sizes_shrank_since(&previous_metadata));
+    assert!(previous_metadata.chunk_sizes_shrank_since(&metadata));
 }
 
-fn create_range(lowest: u64, highest: u64) -> CompleteDataRange<u64> {
-    CompleteDataRange::new(lowest, highest).unwrap()
+#[test]
+fn test_plan_within_byte_budget() {
+    // Create a batch of requests and the estimated size of each response
+    let metadata = ProtocolMetadata {
+        max_epoch_chunk_size: 100,
+        max_state_chunk_size: 100,
+        max_transaction_chunk_size: 100,
+        max_transaction_output_chunk_size: 100,
+    };
+    let requests = vec![
+        txns_request(200, 0, 9, false),
+        outputs_request(200, 10, 14, true),
+        StorageServiceRequest::new(DataRequest::GetStorageServerSummary, false),
+    ];
+    let estimated_bytes = |num_items: usize| {
+        num_items * ESTIMATED_RESPONSE_ITEM_BYTES + ESTIMATED_RESPONSE_OVERHEAD_BYTES
+    };
+    for (request, num_items) in requests.iter().zip([10, 5, 1]) {
+        assert_eq!(
+            estimate_response_bytes(request, &metadata),
+            estimated_bytes(num_items)
+        );
+    }
+
+    // Verify the estimate is bounded by the max chunk size
+    assert_eq!(
+        estimate_response_bytes(&txns_request(2000, 0, 999, false), &metadata),
+        estimated_bytes(100)
+    );
+
+    // Verify the estimate for a batch sums the estimates of the batched requests
+    let data_requests = requests
+        .iter()
+        .map(|request| request.data_request.clone())
+        .collect();
+    let batch_request = StorageServiceRequest::new(DataRequest::GetBatch(data_requests), false);
+    assert_eq!(
+        estimate_response_bytes(&batch_request, &metadata),
+        estimated_bytes(10)
+            + estimated_bytes(5)
+            + estimated_bytes(1)
+            + ESTIMATED_RESPONSE_OVERHEAD_BYTES
+    );
+
+    // Verify all requests are planned if they fit the budget
+    let total_bytes = estimated_bytes(10) + estimated_bytes(5) + estimated_bytes(1);
+    for budget_bytes in [total_bytes, total_bytes + 1, usize::MAX] {
+        assert_eq!(
+            plan_within_byte_budget(&requests, &metadata, budget_bytes),
+            requests
+        );
+    }
+
+    // Verify requests that can't be shrunk are dropped
+    let budget_bytes = total_bytes - 1;
+    assert_eq!(
+        plan_within_byte_budget(&requests, &metadata, budget_bytes),
+        requests[0..2].to_vec()
+    );
+
+    // Verify requests are shrunk to fit the remaining budget
+    let budget_bytes = estimated_bytes(10) + estimated_bytes(3) + 100;
+    assert_eq!(
+        plan_within_byte_budget(&requests, &metadata, budget_bytes),
+        vec![requests[0].clone(), outputs_request(200, 10, 12, true)]
+    );
+    let budget_bytes = estimated_bytes(6);
+    assert_eq!(
+        plan_within_byte_budget(&requests, &metadata, budget_bytes),
+        vec![txns_request(200, 0, 5, false)]
+    );
+
+    // Verify later (smaller) requests are still planned if they fit
+    let range_proof_request = txn_range_proof_request(200, 0, 9, false);
+    let budget_bytes = estimated_bytes(0) + 1000;
+    assert_eq!(
+        plan_within_byte_budget(
+            &[requests[1].clone(), range_proof_request.clone()],
+            &metadata,
+            budget_bytes
+        ),
+        vec![range_proof_request]
+    );
+
+    // Verify nothing is planned if the budget can't fit any response
+    assert!(
+        plan_within_byte_budget(&requests, &metadata, ESTIMATED_RESPONSE_OVERHEAD_BYTES).is_empty()
+    );
 }
 
-fn epochs_request(start: Epoch, end: Epoch, use_compression: bool) -> StorageServiceRequest {
-    let data_request = DataRequest::GetEpochEndingLedgerInfos(EpochEndingLedgerInfoRequest {
-        start_epoch: start,
-        expected_end_epoch: end,
-    });
-    StorageServiceRequest::new(data_request, use_compression)
+#[test]
+fn test_data_summary_batch_coverage() {
+    let summary = DataSummary {
+        synced_ledger_info: Some(create_mock_ledger_info_at_epoch(11, 250)),
+        epoch_ending_ledger_infos: Some(create_range(0, 10)),
+        transactions: Some(create_range(100, 200)),
+        transaction_outputs: Some(create_range(100, 200)),
+        ..Default::default()
+    };
+
+    // Create a batch mixing all three categories
+    let requests = vec![
+        txns_request(225, 100, 200, false),    // Serviceable
+        txns_request(225, 50, 150, false),     // Partially serviceable
+        outputs_request(225, 300, 400, true),  // Unserviceable (no data held)
+        epochs_request(5, 20, false),          // Partially serviceable
+        epochs_request(0, 10, true),           // Serviceable
+        txns_request(300, 100, 200, false),    // Unserviceable (no proof)
+        txns_request(225, 200, 100, false),    // Unserviceable (degenerate)
+        outputs_request(225, 150, 250, false), // Partially serviceable
+    ];
+
+    // Verify the bucket membership
+    let coverage_report = summary.batch_coverage(&requests);
+    assert_eq!(coverage_report.serviceable, vec![0, 4]);
+    assert_eq!(coverage_report.partially_serviceable, vec![1, 3, 7]);
+    assert_eq!(coverage_report.unserviceable, vec![2, 5, 6]);
+    assert_eq!(coverage_report.num_serviceable(), 2);
+    assert_eq!(coverage_report.num_partially_serviceable(), 3);
+    assert_eq!(coverage_report.num_unserviceable(), 3);
+
+    // Verify an empty batch produces an empty report
+    assert_eq!(summary.batch_coverage(&[]), CoverageReport::default());
 }
 
-fn txns_request(
-    proof: Version,
-    start: Version,
+#[test]
+fn test_data_summary_deserialize() {
+    // Verify a valid summary can be decoded
+    let data_summary = DataSummary {
+        synced_ledger_info: Some(create_mock_ledger_info(200)),
+        epoch_ending_ledger_infos: Some(create_range(0, 10)),
+        states: Some(create_range(150, 200)),
+        transactions: Some(create_range(100, 200)),
+        transaction_outputs: Some(create_range(100, 200)),
+    };
+    let bytes = bcs::to_bytes(&data_summary).unwrap();
+    let decoded_summary: DataSummary = bcs::from_bytes(&bytes).unwrap();
+    assert_eq!(decoded_summary, data_summary);
+
+    // Verify a summary without a synced ledger info can be decoded
+    let data_summary = DataSummary {
+        transactions: Some(create_range(100, 200)),
+        ..Default::default()
+    };
+    let bytes = bcs::to_bytes(&data_summary).unwrap();
+    let decoded_summary: DataSummary = bcs::from_bytes(&bytes).unwrap();
+    assert_eq!(decoded_summary, data_summary);
+
+    // Verify a summary with transactions beyond the synced version is rejected
+    let data_summary = DataSummary {
+        synced_ledger_info: Some(create_mock_ledger_info(200)),
+        transactions: Some(create_range(100, 201)),
+        ..Default::default()
+    };
+    let bytes = bcs::to_bytes(&data_summary).unwrap();
+    let error = bcs::from_bytes::<DataSummary>(&bytes).unwrap_err();
+    assert!(error.to_string().contains("Inconsistent data summary"));
+
+    // Verify the summary is also rejected when nested in a server summary
+    let storage_server_summary = StorageServerSummary {
+        data_summary,
+        ..Default::default()
+    };
+    let bytes = bcs::to_bytes(&storage_server_summary).unwrap();
+    assert_err!(bcs::from_bytes::<StorageServerSummary>(&bytes));
+}
+
+#[test]
+fn test_storage_server_summary_proto_round_trip() {
+    // Create a fully populated storage server summary
+    let storage_server_summary = StorageServerSummary {
+        protocol_metadata: ProtocolMetadata {
+            max_epoch_chunk_size: 100,
+            max_state_chunk_size: 200,
+            max_transaction_chunk_size: 300,
+            max_transaction_output_chunk_size: 400,
+        },
+        data_summary: DataSummary {
+            synced_ledger_info: Some(create_mock_ledger_info_at_epoch(10, 1000)),
+            epoch_ending_ledger_infos: Some(create_range(0, 9)),
+            states: Some(create_range(500, 1000)),
+            transactions: Some(create_range(0, 1000)),
+            transaction_outputs: Some(create_range(100, 1000)),
+        },
+    };
+
+    // Verify the summary round trips (including over the wire)
+    verify_proto_round_trip(storage_server_summary);
+
+    // Verify an empty summary also round trips
+    verify_proto_round_trip(StorageServerSummary::default());
+}
+
+#[test]
+fn test_complete_data_range_as_tuple() {
+    #[derive(Debug, Deserialize, PartialEq, Serialize)]
+    struct Config {
+        #[serde(with = "complete_data_range::as_tuple")]
+        range: CompleteDataRange<u64>,
+    }
+
+    // Verify the range round trips in the tuple form
+    let config = Config {
+        range: create_range(3, 5),
+    };
+    let json = serde_json::to_string(&config).unwrap();
+    assert_eq!(json, r#"{"range":[3,5]}"#);
+    assert_eq!(serde_json::from_str::<Config>(&json).unwrap(), config);
+    let config = Config {
+        range: create_range(0, u64::MAX - 1),
+    };
+    let json = serde_json::to_string(&config).unwrap();
+    assert_eq!(serde_json::from_str::<Config>(&json).unwrap(), config);
+
+    // Verify degenerate and malformed ranges are rejected
+    for json in [
+        r#"{"range":[5,3]}"#,
+        r#"{"range":[0,18446744073709551615]}"#,
+        r#"{"range":[5]}"#,
+        r#"{"range":{"lowest":3,"highest":5}}"#,
+    ] {
+        assert_err!(serde_json::from_str::<Config>(json));
+    }
+
+    // Verify the default struct form is unchanged
+    let range = create_range(3, 5);
+    let json = serde_json::to_string(&range).unwrap();
+    assert_eq!(json, r#"{"lowest":3,"highest":5}"#);
+    assert_eq!(
+        serde_json::from_str::<CompleteDataRange<u64>>(&json).unwrap(),
+        range
+    );
+}
+
+#[test]
+fn test_storage_server_summary_from_proto_errors() {
+    // Verify that degenerate ranges are rejected
+    let data_summary_proto = DataSummaryProto {
+        transactions: Some(CompleteDataRangeProto {
+            lowest: 100,
+            highest: 99,
+        }),
+        ..Default::default()
+    };
+    assert_err!(DataSummary::from_proto(data_summary_proto.clone()));
+    assert_err!(StorageServerSummary::from_proto(
+        StorageServerSummaryProto {
+            protocol_metadata: Some(ProtocolMetadata::default().to_proto()),
+            data_summary: Some(data_summary_proto),
+        }
+    ));
+
+    // Verify that malformed ledger infos are rejected
+    let data_summary_proto = DataSummaryProto {
+        synced_ledger_info: Some(vec![1, 2, 3]),
+        ..Default::default()
+    };
+    assert_err!(DataSummary::from_proto(data_summary_proto));
+
+    // Verify that inconsistent summaries (i.e., with transactions
+    // beyond the synced version) are rejected, as they are for BCS.
+    let data_summary = DataSummary {
+        synced_ledger_info: Some(create_mock_ledger_info(100)),
+        transactions: Some(create_range(0, 101)),
+        ..Default::default()
+    };
+    let data_summary_proto = data_summary.to_proto().unwrap();
+    assert_err!(bcs::from_bytes::<DataSummary>(
+        &bcs::to_bytes(&data_summary).unwrap()
+    ));
+    assert_err!(DataSummary::from_proto(data_summary_proto.clone()));
+    assert_err!(StorageServerSummary::from_proto(
+        StorageServerSummaryProto {
+            protocol_metadata: Some(ProtocolMetadata::default().to_proto()),
+            data_summary: Some(data_summary_proto),
+        }
+    ));
+
+    // Verify that missing protocol metadata and data summaries are rejected
+    let storage_server_summary_proto = StorageServerSummary::default().to_proto().unwrap();
+    assert_err!(StorageServerSummary::from_proto(
+        StorageServerSummaryProto {
+            protocol_metadata: None,
+            ..storage_server_summary_proto.clone()
+        }
+    ));
+    assert_err!(StorageServerSummary::from_proto(
+        StorageServerSummaryProto {
+            data_summary: None,
+            ..storage_server_summary_proto
+        }
+    ));
+}
+
+#[test]
+fn test_data_summary_project() {
+    let summary = DataSummary {
+        synced_ledger_info: Some(create_mock_ledger_info_at_epoch(11, 250)),
+        epoch_ending_ledger_infos: Some(create_range(0, 10)),
+        states: Some(create_range(200, 250)),
+        transactions: Some(create_range(100, 200)),
+        transaction_outputs: Some(create_range(150, 200)),
+    };
+
+    // Project the summary onto transaction outputs and verify only that range survives
+    let projected_summary = summary.project(&[DataCategory::TransactionOutputs]);
+    assert_eq!(projected_summary, DataSummary {
+        synced_ledger_info: summary.synced_ledger_info.clone(),
+        transaction_outputs: summary.transaction_outputs,
+        ..Default::default()
+    });
+
+    // Verify the serviceability of output requests is unchanged
+    for request in [
+        outputs_request(225, 150, 200, false),
+        outputs_request(225, 100, 200, true),
+        outputs_request(300, 150, 200, false),
+    ] {
+        assert_eq!(
+            summary.can_service(&request),
+            projected_summary.can_service(&request)
+        );
+    }
+
+    // Verify the other categories can no longer be serviced
+    assert!(summary.can_service(&txns_request(225, 100, 200, false)));
+    assert!(!projected_summary.can_service(&txns_request(225, 100, 200, false)));
+    assert!(summary.can_service(&epochs_request(0, 10, false)));
+    assert!(!projected_summary.can_service(&epochs_request(0, 10, false)));
+
+    // Verify projecting onto all categories is a no-op and onto none clears all ranges
+    let all_categories = [
+        DataCategory::EpochEndingLedgerInfos,
+        DataCategory::States,
+        DataCategory::Transactions,
+        DataCategory::TransactionOutputs,
+    ];
+    assert_eq!(summary.project(&all_categories), summary);
+    assert_eq!(summary.project(&[]), DataSummary {
+        synced_ledger_info: summary.synced_ledger_info.clone(),
+        ..Default::default()
+    });
+}
+
+#[test]
+fn test_normalize_requests_removes_duplicates() {
+    let requests = vec![
+        txns_request(300, 10, 20, false),
+        epochs_request(5, 10, false),
+        txns_request(300, 0, 9, false),
+        txns_request(300, 10, 20, false),
+        epochs_request(0, 4, true),
+        txns_request(300, 10, 20, true),
+        epochs_request(5, 10, false),
+        outputs_request(300, 0, 9, false),
+    ];
+
+    // Verify the duplicates are removed and the requests are sorted by kind then range
+    let normalized_requests = normalize_requests(requests);
+    assert_eq!(normalized_requests, vec![
+        epochs_request(0, 4, true),
+        epochs_request(5, 10, false),
+        outputs_request(300, 0, 9, false),
+        txns_request(300, 0, 9, false),
+        txns_request(300, 10, 20, false),
+        txns_request(300, 10, 20, true),
+    ]);
+
+    // Verify an empty list is left untouched
+    assert!(normalize_requests(vec![]).is_empty());
+}
+
+#[test]
+fn test_normalize_requests_is_stable() {
+    // Requests with the same kind and range should retain their relative order
+    let requests = vec![
+        txns_request(300, 10, 20, true),
+        txns_request(250, 10, 20, false),
+        txns_request(200, 10, 20, false),
+    ];
+    assert_eq!(normalize_requests(requests.clone()), requests);
+}
+
+#[test]
+fn test_storage_server_summary_advance() {
+    // Create a summary for the initial data held in storage
+    let mut storage_server_summary = StorageServerSummary {
+        protocol_metadata: ProtocolMetadata::default(),
+        data_summary: DataSummary {
+            synced_ledger_info: Some(create_mock_ledger_info(200)),
+            epoch_ending_ledger_infos: Some(create_range(0, 5)),
+            states: None,
+            transactions: Some(create_range(100, 200)),
+            transaction_outputs: Some(create_range(150, 200)),
+        },
+    };
+
+    // Advance the summary with the newly stored data
+    let newly_stored = DataSummaryDelta {
+        epoch_ending_ledger_infos: Some(create_range(6, 7)),
+        states: Some(create_range(250, 300)),
+        transactions: Some(create_range(201, 300)),
+        transaction_outputs: Some(create_range(201, 300)),
+    };
+    storage_server_summary.advance(create_mock_ledger_info(300), newly_stored);
+
+    // Verify the advanced summary matches a recomputed summary
+    let recomputed_summary = StorageServerSummary {
+        protocol_metadata: ProtocolMetadata::default(),
+        data_summary: DataSummary {
+            synced_ledger_info: Some(create_mock_ledger_info(300)),
+            epoch_ending_ledger_infos: Some(create_range(0, 7)),
+            states: Some(create_range(250, 300)),
+            transactions: Some(create_range(100, 300)),
+            transaction_outputs: Some(create_range(150, 300)),
+        },
+    };
+    assert_eq!(storage_server_summary, recomputed_summary);
+
+    // Advance the summary with no new data (other than the synced ledger info)
+    storage_server_summary.advance(create_mock_ledger_info(300), DataSummaryDelta::default());
+    assert_eq!(storage_server_summary, recomputed_summary);
+}
+
+#[test]
+fn test_storage_server_summary_compacted() {
+    // Create a storage server summary
+    let storage_server_summary = StorageServerSummary {
+        protocol_metadata: ProtocolMetadata::default(),
+        data_summary: DataSummary {
+            synced_ledger_info: Some(create_mock_ledger_info(250)),
+            epoch_ending_ledger_infos: Some(create_range(0, 20)),
+            states: Some(create_range(200, 250)),
+            transactions: Some(create_range(100, 250)),
+            transaction_outputs: Some(create_range(150, 250)),
+        },
+    };
+
+    // Verify the compact summary answers identically for version-gated requests
+    let compact_summary = storage_server_summary.compacted();
+    for version in (0..400).step_by(10) {
+        let known_version_request = StorageServiceRequest::new(
+            DataRequest::GetNewTransactionsWithProof(NewTransactionsWithProofRequest {
+                known_version: version * 100,
+                known_epoch: 1,
+                include_events: false,
+            }),
+            false,
+        );
+        for request in [
+            epochs_request(version / 10, version / 10 + 5, false),
+            txns_request(version, version, version + 20, false),
+            outputs_request(250, version, version + 20, true),
+            txns_or_outputs_request(version + 50, version, version + 50, false),
+            txn_range_proof_request(250, version, version + 20, false),
+            txn_by_hash_request(version, 100, version, true),
+            states_request(version, false),
+            known_version_request,
+        ] {
+            assert_eq!(
+                compact_summary.can_service(&request),
+                storage_server_summary.can_service(&request)
+            );
+        }
+    }
+
+    // Verify the compact summary answers identically without any data
+    let storage_server_summary = StorageServerSummary::default();
+    let compact_summary = storage_server_summary.compacted();
+    for request in [txns_request(0, 0, 0, false), states_request(0, true)] {
+        assert!(!compact_summary.can_service(&request));
+        assert!(!storage_server_summary.can_service(&request));
+    }
+
+    // Verify the compact summary uses materially less memory
+    assert!(
+        2 * std::mem::size_of::<CompactStorageServerSummary>()
+            < std::mem::size_of::<StorageServerSummary>()
+    );
+}
+
+#[test]
+fn test_freshest_capable_peer_selector() {
+    // Create several candidate summaries
+    let create_summary =
+        |synced_version: Version, transactions: (Version, Version)| StorageServerSummary {
+            protocol_metadata: ProtocolMetadata::default(),
+            data_summary: DataSummary {
+                synced_ledger_info: Some(create_mock_ledger_info(synced_version)),
+                transactions: Some(create_range(transactions.0, transactions.1)),
+                ..DataSummary::default()
+            },
+        };
+    let candidates = vec![
+        create_summary(200, (100, 200)),
+        create_summary(500, (300, 500)),
+        create_summary(400, (100, 400)),
+        create_summary(400, (0, 400)),
+    ];
+
+    // Verify the freshest capable peer is selected
+    let mut peer_selector = FreshestCapable;
+    let request = txns_request(200, 150, 200, false);
+    assert_eq!(
+        peer_selector.select(&candidates, &request),
+        Some(&candidates[2])
+    );
+
+    // Verify the freshest peer is selected when all peers are capable
+    let candidates = &candidates[1..];
+    let request = txns_request(350, 350, 400, true);
+    assert_eq!(
+        peer_selector.select(candidates, &request),
+        Some(&candidates[0])
+    );
+
+    // Verify no peer is selected if no peer is capable
+    let request = txns_request(600, 500, 600, false);
+    assert_eq!(peer_selector.select(candidates, &request), None);
+    assert_eq!(peer_selector.select(&[], &request), None);
+}
+
+#[test]
+fn test_single_source_ranges() {
+    let create_summary = |transactions: Option<CompleteDataRange<Version>>| StorageServerSummary {
+        data_summary: DataSummary {
+            transactions,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let desired_range = create_range(100, 199);
+
+    // Verify fully redundant coverage has no single source ranges
+    let summaries = vec![
+        create_summary(Some(create_range(0, 199))),
+        create_summary(Some(create_range(100, 300))),
+        create_summary(Some(create_range(50, 150))),
+    ];
+    assert!(single_source_ranges(&summaries, desired_range).is_empty());
+
+    // Verify the ranges held by only one peer are reported
+    let summaries = vec![
+        create_summary(Some(create_range(0, 149))),
+        create_summary(Some(create_range(120, 179))),
+        create_summary(None),
+        create_summary(Some(create_range(190, 500))),
+    ];
+    assert_eq!(single_source_ranges(&summaries, desired_range), vec![
+        create_range(100, 119),
+        create_range(150, 179),
+        create_range(190, 199),
+    ]);
+
+    // Verify adjacent ranges held by different peers are merged
+    let summaries = vec![
+        create_summary(Some(create_range(0, 149))),
+        create_summary(Some(create_range(150, u64::MAX))),
+    ];
+    assert_eq!(single_source_ranges(&summaries, desired_range), vec![
+        desired_range
+    ]);
+    let desired_range = create_range(0, u64::MAX - 1);
+    assert_eq!(single_source_ranges(&summaries, desired_range), vec![
+        desired_range
+    ]);
+
+    // Verify no ranges are reported without any coverage
+    assert!(single_source_ranges(&[], desired_range).is_empty());
+}
+
+#[test]
+fn test_min_cover() {
+    let create_summary = |transactions: Option<CompleteDataRange<Version>>| StorageServerSummary {
+        data_summary: DataSummary {
+            transactions,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let desired_range = create_range(100, 199);
+
+    // Verify a single peer covering the whole range is selected
+    let summaries = vec![
+        create_summary(Some(create_range(0, 150))),
+        create_summary(None),
+        create_summary(Some(create_range(50, 250))),
+        create_summary(Some(create_range(150, 300))),
+    ];
+    assert_eq!(
+        min_cover(&summaries, desired_range),
+        Some(vec![&summaries[2]])
+    );
+
+    // Verify two peers are selected if no single peer covers the range
+    let summaries = vec![
+        create_summary(Some(create_range(0, 120))),
+        create_summary(Some(create_range(100, 160))),
+        create_summary(Some(create_range(110, 170))),
+        create_summary(Some(create_range(150, 180))),
+        create_summary(Some(create_range(161, 500))),
+    ];
+    assert_eq!(
+        min_cover(&summaries, desired_range),
+        Some(vec![&summaries[1], &summaries[4]])
+    );
+
+    // Verify adjacent ranges at the edge of the version space are covered
+    let summaries = vec![
+        create_summary(Some(create_range(u64::MAX - 10, u64::MAX))),
+        create_summary(Some(create_range(0, u64::MAX - 11))),
+    ];
+    assert_eq!(
+        min_cover(&summaries, create_range(0, u64::MAX)),
+        Some(vec![&summaries[1], &summaries[0]])
+    );
+
+    // Verify an uncoverable range (i.e., with a gap) returns none
+    let summaries = vec![
+        create_summary(Some(create_range(0, 149))),
+        create_summary(Some(create_range(151, 300))),
+    ];
+    assert_eq!(min_cover(&summaries, desired_range), None);
+    assert_eq!(
+        min_cover(&summaries, create_range(0, 149)),
+        Some(vec![&summaries[0]])
+    );
+    assert_eq!(min_cover(&[], desired_range), None);
+}
+
+#[test]
+fn test_specialize_fused_request() {
+    let fused_request = txns_or_outputs_request(1000, 100, 199, true);
+    let summary = DataSummary {
+        synced_ledger_info: Some(create_mock_ledger_info(1000)),
+        ..Default::default()
+    };
+
+    // Verify an outputs-only peer specializes the request to outputs
+    let outputs_summary = DataSummary {
+        transaction_outputs: Some(create_range(0, 500)),
+        ..summary.clone()
+    };
+    let specialized_request = specialize_fused_request(&fused_request, &outputs_summary).unwrap();
+    assert_eq!(specialized_request, outputs_request(1000, 100, 199, true));
+
+    // Verify a transactions-only peer specializes the request to transactions
+    let transactions_summary = DataSummary {
+        transactions: Some(create_range(0, 500)),
+        ..summary.clone()
+    };
+    let specialized_request =
+        specialize_fused_request(&fused_request, &transactions_summary).unwrap();
+    assert_eq!(specialized_request, txns_request(1000, 100, 199, true));
+
+    // Verify a peer that can service both sides specializes the request to outputs
+    let full_summary = DataSummary {
+        transactions: Some(create_range(0, 500)),
+        transaction_outputs: Some(create_range(0, 500)),
+        ..summary.clone()
+    };
+    let specialized_request = specialize_fused_request(&fused_request, &full_summary).unwrap();
+    assert_eq!(specialized_request, outputs_request(1000, 100, 199, true));
+
+    // Verify requests are not specialized if neither side can be serviced
+    assert_eq!(specialize_fused_request(&fused_request, &summary), None);
+    let partial_summary = DataSummary {
+        transactions: Some(create_range(0, 150)),
+        transaction_outputs: Some(create_range(150, 500)),
+        ..summary
+    };
+    assert_eq!(
+        specialize_fused_request(&fused_request, &partial_summary),
+        None
+    );
+
+    // Verify non-fused requests are not specialized
+    let request = txns_request(1000, 100, 199, true);
+    assert_eq!(specialize_fused_request(&request, &full_summary), None);
+}
+
+#[test]
+fn test_redundancy_for() {
+    let create_summary = |transactions: Option<CompleteDataRange<Version>>| StorageServerSummary {
+        data_summary: DataSummary {
+            synced_ledger_info: Some(create_mock_ledger_info(1000)),
+            transactions,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let request = txns_request(1000, 100, 199, false);
+
+    // Verify there's no redundancy without capable peers
+    assert_eq!(redundancy_for(&[], &request), 0);
+    let summaries = vec![
+        create_summary(None),
+        create_summary(Some(create_range(0, 150))),
+        create_summary(Some(create_range(150, 300))),
+    ];
+    assert_eq!(redundancy_for(&summaries, &request), 0);
+
+    // Verify a single capable peer is counted
+    let mut summaries = summaries;
+    summaries.push(create_summary(Some(create_range(0, 500))));
+    assert_eq!(redundancy_for(&summaries, &request), 1);
+
+    // Verify several capable peers are counted
+    summaries.push(create_summary(Some(create_range(100, 199))));
+    summaries.push(create_summary(Some(create_range(50, 250))));
+    assert_eq!(redundancy_for(&summaries, &request), 3);
+
+    // Verify peers that can't create the proof aren't counted
+    let mut summary = create_summary(Some(create_range(0, 500)));
+    summary.data_summary.synced_ledger_info = Some(create_mock_ledger_info(999));
+    summaries.push(summary);
+    assert_eq!(redundancy_for(&summaries, &request), 3);
+}
+
+#[test]
+fn test_data_summary_first_servable_version_at_or_after() {
+    // Create a data summary with a transaction range
+    let summary = DataSummary {
+        synced_ledger_info: Some(create_mock_ledger_info(250)),
+        transactions: Some(create_range(100, 200)),
+        transaction_outputs: Some(create_range(0, 250)),
+        ..DataSummary::default()
+    };
+
+    // Verify versions below the range realign to the lowest version
+    for version in [0, 50, 99] {
+        assert_eq!(
+            summary.first_servable_version_at_or_after(version),
+            Some(100)
+        );
+    }
+
+    // Verify versions inside the range are returned directly
+    for version in [100, 150, 200] {
+        assert_eq!(
+            summary.first_servable_version_at_or_after(version),
+            Some(version)
+        );
+    }
+
+    // Verify versions above the range are not servable
+    for version in [201, 250, u64::MAX] {
+        assert_eq!(summary.first_servable_version_at_or_after(version), None);
+    }
+
+    // Verify nothing is servable without a transaction range
+    let summary = DataSummary::default();
+    assert_eq!(summary.first_servable_version_at_or_after(0), None);
+}
+
+#[test]
+fn test_data_summary_optimistic_known_version() {
+    // Verify no known version is returned without a synced ledger info
+    let summary = DataSummary::default();
+    assert_eq!(summary.optimistic_known_version(0), None);
+
+    // Verify the known version is returned within the delta window
+    let synced_version = 1000;
+    let summary = DataSummary {
+        synced_ledger_info: Some(create_mock_ledger_info(synced_version)),
+        ..Default::default()
+    };
+    let highest_known_version = synced_version + OPTIMISTIC_FETCH_VERSION_DELTA - 1;
+    for my_version in [0, synced_version, highest_known_version] {
+        assert_eq!(
+            summary.optimistic_known_version(my_version),
+            Some(my_version)
+        );
+    }
+
+    // Verify no known version is returned outside the delta window
+    for my_version in [highest_known_version + 1, highest_known_version + 100] {
+        assert_eq!(summary.optimistic_known_version(my_version), None);
+    }
+}
+
+#[test]
+fn test_optimistic_fetch_window() {
+    // Verify the default window matches the version delta
+    let default_window = OptimisticFetchWindow::default();
+    assert_eq!(
+        default_window.version_delta(),
+        OPTIMISTIC_FETCH_VERSION_DELTA
+    );
+    assert_eq!(
+        default_window,
+        OptimisticFetchWindow::from_version_delta(OPTIMISTIC_FETCH_VERSION_DELTA)
+    );
+
+    // Verify the window is constructed from the TPS and seconds
+    assert_eq!(
+        OptimisticFetchWindow::from_tps_and_seconds(5000, 5),
+        default_window
+    );
+    assert_eq!(
+        OptimisticFetchWindow::from_tps_and_seconds(100, 3).version_delta(),
+        300
+    );
+    assert_eq!(
+        OptimisticFetchWindow::from_tps_and_seconds(u64::MAX, 2).version_delta(),
+        u64::MAX
+    );
+
+    // Verify the within-check at the window boundary
+    let window = OptimisticFetchWindow::from_version_delta(100);
+    let synced_version = 1000;
+    for known_version in [0, synced_version, synced_version + 99] {
+        assert!(window.is_within(synced_version, known_version));
+    }
+    for known_version in [synced_version + 100, synced_version + 101] {
+        assert!(!window.is_within(synced_version, known_version));
+    }
+    assert!(!OptimisticFetchWindow::from_version_delta(0).is_within(synced_version, synced_version));
+    assert!(window.is_within(u64::MAX, u64::MAX - 1));
+
+    // Verify optimistic fetch requests are serviced using the default window
+    let summary = DataSummary {
+        synced_ledger_info: Some(create_mock_ledger_info(synced_version)),
+        ..Default::default()
+    };
+    for known_version in [
+        synced_version,
+        synced_version + OPTIMISTIC_FETCH_VERSION_DELTA,
+    ] {
+        let request = DataRequest::GetNewTransactionsWithProof(NewTransactionsWithProofRequest {
+            known_version,
+            known_epoch: 1,
+            include_events: false,
+        });
+        let request = StorageServiceRequest::new(request, false);
+        assert_eq!(
+            summary.can_service(&request),
+            default_window.is_within(synced_version, known_version)
+        );
+    }
+}
+
+#[test]
+fn test_data_summary_optimistic_fetch_reasons() {
+    let create_optimistic_requests = |known_version| {
+        let new_transactions_request =
+            DataRequest::GetNewTransactionsWithProof(NewTransactionsWithProofRequest {
+                known_version,
+                known_epoch: 1,
+                include_events: false,
+            });
+        let new_outputs_request =
+            DataRequest::GetNewTransactionOutputsWithProof(NewTransactionOutputsWithProofRequest {
+                known_version,
+                known_epoch: 1,
+            });
+        vec![
+            StorageServiceRequest::new(new_transactions_request, false),
+            StorageServiceRequest::new(new_outputs_request, false),
+        ]
+    };
+
+    // Verify a peer with no synced ledger info declines with a distinct reason
+    let summary = DataSummary {
+        transactions: Some(create_range(0, 1000)),
+        ..Default::default()
+    };
+    for request in create_optimistic_requests(100) {
+        assert_eq!(
+            summary.can_service_with_reason(&request),
+            Err(UnserviceableReason::NoSyncedLedgerInfo)
+        );
+    }
+
+    // Verify a peer outside the window declines with a distinct reason
+    let synced_version = 1000;
+    let summary = DataSummary {
+        synced_ledger_info: Some(create_mock_ledger_info(synced_version)),
+        ..summary
+    };
+    for request in create_optimistic_requests(synced_version + OPTIMISTIC_FETCH_VERSION_DELTA) {
+        assert_eq!(
+            summary.can_service_with_reason(&request),
+            Err(UnserviceableReason::OptimisticOutsideWindow)
+        );
+    }
+
+    // Verify a peer within the window can service the requests
+    for request in create_optimistic_requests(synced_version) {
+        assert_ok!(summary.can_service_with_reason(&request));
+    }
+}
+
+#[test]
+fn test_data_summary_common_serviceable_window() {
+    let create_summary = |synced_version, transactions, outputs, states| DataSummary {
+        synced_ledger_info: Some(create_mock_ledger_info(synced_version)),
+        transactions,
+        transaction_outputs: outputs,
+        states,
+        ..Default::default()
+    };
+
+    // Verify the window when all categories are identical
+    let summary = create_summary(
+        1000,
+        Some(create_range(0, 1000)),
+        Some(create_range(0, 1000)),
+        Some(create_range(0, 1000)),
+    );
+    assert_eq!(
+        summary.common_serviceable_window(),
+        Some(create_range(0, 1000))
+    );
+
+    // Verify a single category limits the window (e.g., pruned states)
+    let summary = create_summary(
+        1000,
+        Some(create_range(0, 1000)),
+        Some(create_range(0, 1000)),
+        Some(create_range(800, 1000)),
+    );
+    assert_eq!(
+        summary.common_serviceable_window(),
+        Some(create_range(800, 1000))
+    );
+
+    // Verify the synced version limits the window
+    let summary = create_summary(
+        900,
+        Some(create_range(100, 1000)),
+        Some(create_range(200, 1000)),
+        Some(create_range(300, 1000)),
+    );
+    assert_eq!(
+        summary.common_serviceable_window(),
+        Some(create_range(300, 900))
+    );
+
+    // Verify no window is returned if the categories don't overlap
+    let summary = create_summary(
+        1000,
+        Some(create_range(0, 500)),
+        Some(create_range(0, 1000)),
+        Some(create_range(501, 1000)),
+    );
+    assert_eq!(summary.common_serviceable_window(), None);
+
+    // Verify no window is returned if any category is absent
+    let range = Some(create_range(0, 1000));
+    for (transactions, outputs, states) in [
+        (None, range, range),
+        (range, None, range),
+        (range, range, None),
+    ] {
+        let summary = create_summary(1000, transactions, outputs, states);
+        assert_eq!(summary.common_serviceable_window(), None);
+    }
+
+    // Verify no window is returned without a synced ledger info
+    let summary = DataSummary {
+        synced_ledger_info: None,
+        ..create_summary(1000, range, range, range)
+    };
+    assert_eq!(summary.common_serviceable_window(), None);
+}
+
+#[test]
+fn test_data_summary_jointly_serviceable_transactions() {
+    let create_summary = |synced_version, lowest, highest| DataSummary {
+        synced_ledger_info: Some(create_mock_ledger_info(synced_version)),
+        transactions: Some(create_range(lowest, highest)),
+        ..Default::default()
+    };
+
+    // Verify the ranges are the limiter
+    let summary = create_summary(1000, 100, 800);
+    let other_summary = create_summary(1000, 500, 1000);
+    let expected_range = Some(create_range(500, 800));
+    assert_eq!(
+        summary.jointly_serviceable_transactions(&other_summary),
+        expected_range
+    );
+    assert_eq!(
+        other_summary.jointly_serviceable_transactions(&summary),
+        expected_range
+    );
+
+    // Verify the proofs are the limiter (i.e., the synced versions)
+    let summary = create_summary(600, 0, 1000);
+    let other_summary = create_summary(900, 200, 1000);
+    let expected_range = Some(create_range(200, 600));
+    assert_eq!(
+        summary.jointly_serviceable_transactions(&other_summary),
+        expected_range
+    );
+    assert_eq!(
+        other_summary.jointly_serviceable_transactions(&summary),
+        expected_range
+    );
+
+    // Verify nothing is returned if the proofs don't reach the intersection
+    let summary = create_summary(150, 0, 1000);
+    let other_summary = create_summary(1000, 200, 1000);
+    assert_eq!(
+        summary.jointly_serviceable_transactions(&other_summary),
+        None
+    );
+
+    // Verify nothing is returned if the ranges don't overlap
+    let summary = create_summary(1000, 0, 100);
+    let other_summary = create_summary(1000, 101, 1000);
+    assert_eq!(
+        summary.jointly_serviceable_transactions(&other_summary),
+        None
+    );
+
+    // Verify nothing is returned if either peer is missing data
+    let summary = create_summary(1000, 0, 1000);
+    assert_eq!(
+        summary.jointly_serviceable_transactions(&DataSummary::default()),
+        None
+    );
+    assert_eq!(
+        DataSummary::default().jointly_serviceable_transactions(&summary),
+        None
+    );
+}
+
+#[test]
+fn test_data_summary_clamp_known_version() {
+    // Verify no version is returned without a synced ledger info
+    let summary = DataSummary::default();
+    assert_eq!(summary.clamp_known_version(0), None);
+
+    // Verify versions within the window are returned unchanged
+    let synced_version = 1000;
+    let summary = DataSummary {
+        synced_ledger_info: Some(create_mock_ledger_info(synced_version)),
+        ..Default::default()
+    };
+    let highest_known_version = synced_version + OPTIMISTIC_FETCH_VERSION_DELTA - 1;
+    for desired in [0, synced_version, highest_known_version] {
+        assert_eq!(summary.clamp_known_version(desired), Some(desired));
+    }
+
+    // Verify versions beyond the window are clamped to the highest version
+    for desired in [highest_known_version + 1, u64::MAX] {
+        assert_eq!(
+            summary.clamp_known_version(desired),
+            Some(highest_known_version)
+        );
+    }
+
+    // Verify the clamped versions can always be serviced
+    for desired in [0, synced_version, highest_known_version + 1, u64::MAX] {
+        let known_version = summary.clamp_known_version(desired).unwrap();
+        assert_eq!(
+            summary.optimistic_known_version(known_version),
+            Some(known_version)
+        );
+    }
+}
+
+#[test]
+fn test_data_summary_suggested_poll_interval_ms() {
+    let create_summary = |synced_version| DataSummary {
+        synced_ledger_info: Some(create_mock_ledger_info(synced_version)),
+        ..Default::default()
+    };
+
+    // Verify the interval for a fast-advancing peer (i.e., 10k TPS)
+    let previous_summary = create_summary(1000);
+    let summary = create_summary(1000 + 10_000);
+    let poll_interval_ms = summary
+        .suggested_poll_interval_ms(&previous_summary, 1000)
+        .unwrap();
+    assert_eq!(poll_interval_ms, OPTIMISTIC_FETCH_VERSION_DELTA / 10);
+
+    // Verify the interval keeps the client within the fetch delta
+    let versions_per_ms = 10_000 / 1000;
+    assert!(poll_interval_ms * versions_per_ms <= OPTIMISTIC_FETCH_VERSION_DELTA);
+
+    // Verify a faster peer results in a shorter interval
+    let faster_summary = create_summary(1000 + 100_000);
+    assert!(
+        faster_summary
+            .suggested_poll_interval_ms(&previous_summary, 1000)
+            .unwrap()
+            < poll_interval_ms
+    );
+
+    // Verify no interval is suggested for a stalled peer
+    assert_eq!(
+        previous_summary.suggested_poll_interval_ms(&previous_summary, 1000),
+        None
+    );
+
+    // Verify no interval is suggested if the peer went backwards
+    assert_eq!(
+        previous_summary.suggested_poll_interval_ms(&summary, 1000),
+        None
+    );
+
+    // Verify no interval is suggested without elapsed time or ledger infos
+    assert_eq!(
+        summary.suggested_poll_interval_ms(&previous_summary, 0),
+        None
+    );
+    assert_eq!(
+        summary.suggested_poll_interval_ms(&DataSummary::default(), 1000),
+        None
+    );
+    assert_eq!(
+        DataSummary::default().suggested_poll_interval_ms(&previous_summary, 1000),
+        None
+    );
+}
+
+#[test]
+fn test_data_summary_regressed_since() {
+    let previous_summary = DataSummary {
+        synced_ledger_info: Some(create_mock_ledger_info(200)),
+        epoch_ending_ledger_infos: Some(create_range(0, 5)),
+        states: Some(create_range(150, 200)),
+        transactions: Some(create_range(100, 200)),
+        transaction_outputs: Some(create_range(100, 200)),
+    };
+
+    // Verify that growth is not considered a regression
+    let grown_summary = DataSummary {
+        synced_ledger_info: Some(create_mock_ledger_info(300)),
+        epoch_ending_ledger_infos: Some(create_range(0, 6)),
+        states: Some(create_range(150, 300)),
+        transactions: Some(create_range(50, 300)),
+        transaction_outputs: Some(create_range(100, 300)),
+    };
+    assert!(!grown_summary.regressed_since(&previous_summary));
+    assert!(!previous_summary.regressed_since(&previous_summary));
+    assert!(!previous_summary.regressed_since(&DataSummary::default()));
+
+    // Verify that pruning the low end is considered a regression
+    let pruned_summary = DataSummary {
+        transactions: Some(create_range(101, 300)),
+        ..grown_summary.clone()
+    };
+    assert!(pruned_summary.regressed_since(&previous_summary));
+    let pruned_summary = DataSummary {
+        states: None,
+        ..grown_summary.clone()
+    };
+    assert!(pruned_summary.regressed_since(&previous_summary));
+
+    // Verify that rolling back the high end is considered a regression
+    let rolled_back_summary = DataSummary {
+        transaction_outputs: Some(create_range(100, 199)),
+        ..grown_summary.clone()
+    };
+    assert!(rolled_back_summary.regressed_since(&previous_summary));
+    let rolled_back_summary = DataSummary {
+        synced_ledger_info: Some(create_mock_ledger_info(199)),
+        ..previous_summary.clone()
+    };
+    assert!(rolled_back_summary.regressed_since(&previous_summary));
+}
+
+#[test]
+fn test_data_response_merge_adjacent_transactions() {
+    // Create two adjacent transaction lists
+    let first_list =
+        create_transaction_list(
+            100,
+            10,
+            vec![HashValue::random()],
+            vec![HashValue::random()],
+        );
+    let second_list =
+        create_transaction_list(110, 5, vec![HashValue::random()], vec![HashValue::random()]);
+
+    // Merge the lists and verify the result
+    let merged_response = DataResponse::TransactionsWithProof(first_list.clone())
+        .try_merge_adjacent(DataResponse::TransactionsWithProof(second_list.clone()))
+        .unwrap();
+    let merged_list = match merged_response {
+        DataResponse::TransactionsWithProof(merged_list) => merged_list,
+        response => panic!("Expected transactions with proof, found: {:?}", response),
+    };
+    assert_eq!(merged_list.first_transaction_version, Some(100));
+    assert_eq!(merged_list.transactions.len(), 15);
+    assert_eq!(merged_list.events.unwrap().len(), 15);
+    assert_eq!(merged_list.proof.transaction_infos.len(), 15);
+
+    // Verify the proofs were combined
+    let merged_proof = merged_list.proof.ledger_info_to_transaction_infos_proof;
+    assert_eq!(
+        merged_proof.left_siblings(),
+        first_list
+            .proof
+            .ledger_info_to_transaction_infos_proof
+            .left_siblings()
+    );
+    assert_eq!(
+        merged_proof.right_siblings(),
+        second_list
+            .proof
+            .ledger_info_to_transaction_infos_proof
+            .right_siblings()
+    );
+
+    // Verify merging with an empty list is a no-op
+    let merged_response = DataResponse::TransactionsWithProof(first_list.clone())
+        .try_merge_adjacent(DataResponse::TransactionsWithProof(
+            TransactionListWithProof::new_empty(),
+        ))
+        .unwrap();
+    assert_eq!(
+        merged_response,
+        DataResponse::TransactionsWithProof(first_list)
+    );
+}
+
+#[test]
+fn test_data_response_truncated_to() {
+    // Create a transaction list with a valid proof (against the accumulator root)
+    let (transaction_list, root_hash) = create_transaction_list_with_valid_proof(64, 5, 46);
+    let verify_proof = |transaction_list: &TransactionListWithProof| {
+        let transaction_info_hashes: Vec<_> = transaction_list
+            .proof
+            .transaction_infos
+            .iter()
+            .map(CryptoHash::hash)
+            .collect();
+        assert_ok!(transaction_list
+            .proof
+            .ledger_info_to_transaction_infos_proof
+            .verify(
+                root_hash,
+                transaction_list.first_transaction_version,
+                &transaction_info_hashes
+            ));
+    };
+
+    // Truncate the response and verify the retained prefix has a valid proof
+    let ledger_info = create_mock_ledger_info(100);
+    for max_items in [1, 10, 45] {
+        let data_response =
+            DataResponse::NewTransactionsWithProof((transaction_list.clone(), ledger_info.clone()));
+        match data_response.truncated_to(max_items).unwrap() {
+            DataResponse::NewTransactionsWithProof((truncated_list, truncated_ledger_info)) => {
+                assert_eq!(truncated_list.transactions.len(), max_items);
+                assert_eq!(
+                    truncated_list.transactions[..],
+                    transaction_list.transactions[..max_items]
+                );
+                assert_eq!(truncated_list.first_transaction_version, Some(5));
+                assert_eq!(truncated_ledger_info, ledger_info);
+                verify_proof(&truncated_list);
+            },
+            data_response => panic!("Expected a transaction list but got: {:?}", data_response),
+        }
+    }
+
+    // Verify lists that already fit (and non-list responses) are unchanged
+    let data_response = DataResponse::TransactionsWithProof(transaction_list.clone());
+    assert_eq!(data_response.truncated_to(46).unwrap(), data_response);
+    assert_eq!(data_response.truncated_to(1000).unwrap(), data_response);
+    let data_response = DataResponse::NumberOfStatesAtVersion(10);
+    assert_eq!(data_response.truncated_to(0).unwrap(), data_response);
+
+    // Verify truncating to zero items returns an empty list
+    let data_response =
+        DataResponse::TransactionsOrOutputsWithProof((Some(transaction_list), None));
+    assert_eq!(
+        data_response.truncated_to(0).unwrap(),
+        DataResponse::TransactionsOrOutputsWithProof((
+            Some(TransactionListWithProof::new_empty()),
+            None
+        ))
+    );
+
+    // Verify truncated epoch change proofs are marked as having more
+    let ledger_infos = (0..5).map(create_mock_ledger_info).collect::<Vec<_>>();
+    let data_response =
+        DataResponse::EpochEndingLedgerInfos(EpochChangeProof::new(ledger_infos.clone(), false));
+    assert_eq!(
+        data_response.truncated_to(2).unwrap(),
+        DataResponse::EpochEndingLedgerInfos(EpochChangeProof::new(
+            ledger_infos[..2].to_vec(),
+            true
+        ))
+    );
+
+    // Verify state value chunks can't be truncated (but are unchanged if they fit)
+    let state_value_chunk = StateValueChunkWithProof {
+        first_index: 0,
+        last_index: 1,
+        first_key: HashValue::random(),
+        last_key: HashValue::random(),
+        raw_values: (0..2)
+            .map(|index| (StateKey::raw(vec![index]), StateValue::from(vec![index])))
+            .collect(),
+        proof: SparseMerkleRangeProof::new(vec![]),
+        root_hash: HashValue::random(),
+    };
+    let data_response = DataResponse::StateValueChunkWithProof(state_value_chunk);
+    assert_eq!(data_response.truncated_to(2).unwrap(), data_response);
+    assert_err!(data_response.truncated_to(1));
+}
+
+#[test]
+fn test_data_response_split_for_wire() {
+    // Create a transaction list with a valid proof (against the accumulator root)
+    let (transaction_list, root_hash) = create_transaction_list_with_valid_proof(64, 5, 46);
+    let verify_proof = |transaction_list: &TransactionListWithProof| {
+        let transaction_info_hashes: Vec<_> = transaction_list
+            .proof
+            .transaction_infos
+            .iter()
+            .map(CryptoHash::hash)
+            .collect();
+        assert_ok!(transaction_list
+            .proof
+            .ledger_info_to_transaction_infos_proof
+            .verify(
+                root_hash,
+                transaction_list.first_transaction_version,
+                &transaction_info_hashes
+            ));
+    };
+    verify_proof(&transaction_list);
+
+    // Split the response and verify each piece fits and has a valid proof
+    let data_response = DataResponse::TransactionsWithProof(transaction_list.clone());
+    let max_bytes = bcs::serialized_size(&data_response).unwrap() / 4;
+    let pieces = data_response.clone().split_for_wire(max_bytes).unwrap();
+    assert!(pieces.len() > 1);
+    for piece in pieces.iter() {
+        assert!(bcs::serialized_size(piece).unwrap() <= max_bytes);
+        match piece {
+            DataResponse::TransactionsWithProof(transaction_list) => verify_proof(transaction_list),
+            piece => panic!("Expected a transaction list but got: {:?}", piece),
+        }
+    }
+
+    // Verify the pieces reassemble to the original response
+    let reassembled_response = pieces
+        .into_iter()
+        .reduce(|first, second| first.try_merge_adjacent(second).unwrap())
+        .unwrap();
+    assert_eq!(reassembled_response, data_response);
+
+    // Verify responses that already fit (and non-list responses) aren't split
+    let max_bytes = bcs::serialized_size(&data_response).unwrap();
+    assert_eq!(
+        data_response.clone().split_for_wire(max_bytes).unwrap(),
+        vec![data_response.clone()]
+    );
+    let data_response = DataResponse::NumberOfStatesAtVersion(100);
+    assert_eq!(data_response.clone().split_for_wire(1).unwrap(), vec![
+        data_response
+    ]);
+
+    // Verify an error is returned if a single item doesn't fit
+    let data_response = DataResponse::TransactionsWithProof(transaction_list);
+    assert_err!(data_response.split_for_wire(10));
+}
+
+#[test]
+fn test_try_concat_transactions() {
+    // Create three adjacent transaction lists (and the corresponding responses)
+    let transaction_lists = vec![
+        create_transaction_list(100, 10, vec![HashValue::random()], vec![]),
+        create_transaction_list(110, 5, vec![], vec![]),
+        create_transaction_list(115, 20, vec![], vec![HashValue::random()]),
+    ];
+    let create_responses = |transaction_lists: &[TransactionListWithProof]| {
+        transaction_lists
+            .iter()
+            .map(|transaction_list| {
+                StorageServiceResponse::new(
+                    DataResponse::TransactionsWithProof(transaction_list.clone()),
+                    true,
+                )
+                .unwrap()
+            })
+            .collect::<Vec<_>>()
+    };
+
+    // Concatenate the lists and verify the result
+    let concatenated_list = try_concat_transactions(create_responses(&transaction_lists)).unwrap();
+    assert_eq!(concatenated_list.first_transaction_version, Some(100));
+    assert_eq!(concatenated_list.transactions.len(), 35);
+    assert_eq!(concatenated_list.proof.transaction_infos.len(), 35);
+    let expected_transactions: Vec<_> = transaction_lists
+        .iter()
+        .flat_map(|transaction_list| transaction_list.transactions.clone())
+        .collect();
+    assert_eq!(concatenated_list.transactions, expected_transactions);
+
+    // Verify the proofs were combined
+    let concatenated_proof = concatenated_list
+        .proof
+        .ledger_info_to_transaction_infos_proof;
+    assert_eq!(
+        concatenated_proof.left_siblings(),
+        transaction_lists[0]
+            .proof
+            .ledger_info_to_transaction_infos_proof
+            .left_siblings()
+    );
+    assert_eq!(
+        concatenated_proof.right_siblings(),
+        transaction_lists[2]
+            .proof
+            .ledger_info_to_transaction_infos_proof
+            .right_siblings()
+    );
+
+    // Verify out of order lists are rejected
+    let mut out_of_order_lists = transaction_lists.clone();
+    out_of_order_lists.swap(1, 2);
+    assert_err!(try_concat_transactions(create_responses(
+        &out_of_order_lists
+    )));
+
+    // Verify lists with gaps or overlaps are rejected
+    for transaction_list in [
+        create_transaction_list(111, 5, vec![], vec![]),
+        create_transaction_list(109, 5, vec![], vec![]),
+    ] {
+        let invalid_lists = vec![transaction_lists[0].clone(), transaction_list];
+        assert_err!(try_concat_transactions(create_responses(&invalid_lists)));
+    }
+
+    // Verify responses with the wrong variant are rejected
+    let mut responses = create_responses(&transaction_lists);
+    responses.push(
+        StorageServiceResponse::new(DataResponse::NumberOfStatesAtVersion(10), false).unwrap(),
+    );
+    assert_err!(try_concat_transactions(responses));
+}
+
+#[test]
+fn test_data_response_to_log_fields() {
+    // Verify the fields of a transactions with proof response
+    let transaction_list = create_transaction_list(100, 10, vec![], vec![]);
+    let data_response = DataResponse::TransactionsWithProof(transaction_list);
+    assert_eq!(data_response.to_log_fields(), vec![
+        ("label", "transactions_with_proof".to_string()),
+        ("version_range", "[100, 109]".to_string()),
+        ("item_count", "10".to_string()),
+    ]);
+
+    // Verify the fields of an empty transactions with proof response
+    let data_response = DataResponse::TransactionsWithProof(TransactionListWithProof::new_empty());
+    assert_eq!(data_response.to_log_fields(), vec![
+        ("label", "transactions_with_proof".to_string()),
+        ("item_count", "0".to_string()),
+    ]);
+
+    // Verify the fields of a response without a data list
+    let data_response = DataResponse::NumberOfStatesAtVersion(10);
+    assert_eq!(data_response.to_log_fields(), vec![(
+        "label",
+        "number_of_states_at_version".to_string()
+    )]);
+}
+
+#[test]
+fn test_data_response_served_range() {
+    // Verify a truncated response reports the served range
+    let transaction_list = create_transaction_list(100, 10, vec![], vec![]);
+    let response = DataResponse::TransactionsWithProof(transaction_list.clone());
+    assert_eq!(response.get_served_range(), Some(create_range(100, 109)));
+    assert!(response.is_truncated(&txns_request(200, 100, 199, false)));
+
+    // Verify the same holds for transaction or output lists
+    let response = DataResponse::TransactionsOrOutputsWithProof((Some(transaction_list), None));
+    assert_eq!(response.get_served_range(), Some(create_range(100, 109)));
+    assert!(response.is_truncated(&txns_or_outputs_request(200, 100, 150, false)));
+
+    // Verify a full response reports a served range that matches the request
+    assert!(!response.is_truncated(&txns_or_outputs_request(200, 100, 109, false)));
+
+    // Verify the served range is omitted for empty lists and non-list responses
+    for response in [
+        DataResponse::TransactionOutputsWithProof(TransactionOutputListWithProof::new_empty()),
+        DataResponse::NumberOfStatesAtVersion(10),
+        DataResponse::StorageServerSummary(StorageServerSummary::default()),
+    ] {
+        assert_eq!(response.get_served_range(), None);
+        assert!(!response.is_truncated(&outputs_request(200, 100, 199, false)));
+    }
+}
+
+#[test]
+fn test_data_response_merge_adjacent_rejects_invalid_lists() {
+    // Verify lists with a gap are rejected
+    let first_list = create_transaction_list(100, 10, vec![], vec![]);
+    let second_list = create_transaction_list(111, 5, vec![], vec![]);
+    assert_err!(DataResponse::TransactionsWithProof(first_list.clone())
+        .try_merge_adjacent(DataResponse::TransactionsWithProof(second_list)));
+
+    // Verify overlapping lists are rejected
+    let second_list = create_transaction_list(105, 10, vec![], vec![]);
+    assert_err!(DataResponse::TransactionsWithProof(first_list.clone())
+        .try_merge_adjacent(DataResponse::TransactionsWithProof(second_list)));
+
+    // Verify mismatched variants are rejected
+    assert_err!(DataResponse::TransactionsWithProof(first_list)
+        .try_merge_adjacent(DataResponse::NumberOfStatesAtVersion(10)));
+}
+
+#[test]
+fn test_contradicts() {
+    let summary = StorageServerSummary {
+        protocol_metadata: ProtocolMetadata::default(),
+        data_summary: DataSummary {
+            synced_ledger_info: Some(create_mock_ledger_info(250)),
+            transactions: Some(create_range(100, 200)),
+            ..Default::default()
+        },
+    };
+    let unexpected_response_error = Error::UnexpectedResponseError("Invalid response!".into());
+
+    // Verify an unexpected response for an advertised request contradicts the summary
+    let advertised_request = txns_request(225, 100, 200, false);
+    assert!(contradicts(
+        &summary,
+        &advertised_request,
+        &unexpected_response_error
+    ));
+
+    // Verify an unexpected response for a non-advertised request doesn't contradict the summary
+    let non_advertised_request = txns_request(225, 150, 250, false);
+    assert!(!contradicts(
+        &summary,
+        &non_advertised_request,
+        &unexpected_response_error
+    ));
+
+    // Verify other errors don't contradict the summary
+    for error in [
+        Error::DegenerateRangeError,
+        Error::UnexpectedErrorEncountered("Local error!".into()),
+    ] {
+        assert!(!contradicts(&summary, &advertised_request, &error));
+    }
+}
+
+#[test]
+fn test_error_with_attempt() {
+    for error in [
+        Error::DegenerateRangeError,
+        Error::UnexpectedErrorEncountered("Local error!".into()),
+        Error::UnexpectedResponseError("Bad response!".into()),
+        Error::UnsupportedRequest {
+            label: "get_transactions_with_proof".into(),
+        },
+    ] {
+        // Verify a fresh error carries no attempt count
+        assert_eq!(error.attempt_count(), None);
+
+        // Wrap the error and verify the display and attempt count
+        let retried_error = error.clone().with_attempt(3);
+        assert_eq!(retried_error.to_string(), error.to_string());
+        assert_eq!(retried_error.attempt_count(), Some(3));
+
+        // Wrap the error again and verify the attempt count is replaced
+        let retried_error = retried_error.with_attempt(4);
+        assert_eq!(retried_error.to_string(), error.to_string());
+        assert_eq!(retried_error.attempt_count(), Some(4));
+    }
+}
+
+#[test]
+fn test_data_response_all_labels() {
+    // Verify there's a label for every variant
+    let all_labels = DataResponse::all_labels();
+    assert_eq!(all_labels.len(), NUM_DATA_RESPONSE_VARIANTS);
+
+    // Verify the labels are unique and non-empty
+    let unique_labels: HashSet<_> = all_labels.iter().collect();
+    assert_eq!(unique_labels.len(), all_labels.len());
+    assert!(all_labels.iter().all(|label| !label.is_empty()));
+
+    // Verify the labels match the variants
+    for response in [
+        DataResponse::NumberOfStatesAtVersion(10),
+        DataResponse::StorageServerSummary(StorageServerSummary::default()),
+        DataResponse::TransactionsWithProof(TransactionListWithProof::new_empty()),
+        DataResponse::TransactionRangeProof(TransactionAccumulatorRangeProof::new_empty()),
+        DataResponse::TransactionByHashWithProof(None),
+    ] {
+        let variant_index = get_data_response_variant_index(&response);
+        assert_eq!(all_labels[variant_index], response.get_label());
+    }
+}
+
+#[test]
+fn test_data_response_batch() {
+    // Create a batch of several small responses
+    let data_responses = vec![
+        DataResponse::LatestLedgerInfo(create_mock_ledger_info(100)),
+        DataResponse::NumberOfStatesAtVersion(10),
+        DataResponse::TransactionsWithProof(create_transaction_list(100, 10, vec![], vec![])),
+    ];
+    let batch = DataResponse::new_batch(data_responses.clone()).unwrap();
+    assert_eq!(batch.get_label(), "batch");
+
+    // Verify the batch is encoded and decoded (with and without compression)
+    for compression in [true, false] {
+        let response = StorageServiceResponse::new(batch.clone(), compression).unwrap();
+        let decoded_responses: Vec<DataResponse> = response.clone().try_into().unwrap();
+        assert_eq!(decoded_responses, data_responses);
+        assert!(
+            matches!(response.decode().unwrap(), DecodedResponse::Batch(decoded) if decoded.len() == 3)
+        );
+    }
+
+    // Verify non-batch responses are rejected
+    let response =
+        StorageServiceResponse::new(DataResponse::NumberOfStatesAtVersion(10), false).unwrap();
+    assert_err!(Vec::<DataResponse>::try_from(response));
+
+    // Verify nested batches are rejected (when created and when decoded)
+    let nested_batch = vec![batch.clone(), DataResponse::NumberOfStatesAtVersion(10)];
+    assert_err!(DataResponse::new_batch(nested_batch.clone()));
+    let serialized_nested_batch =
+        bcs::to_bytes(&DataResponse::Batch(nested_batch.clone())).unwrap();
+    assert_err!(bcs::from_bytes::<DataResponse>(&serialized_nested_batch));
+    let response = StorageServiceResponse::new(DataResponse::Batch(nested_batch), true).unwrap();
+    assert_err!(Vec::<DataResponse>::try_from(response.clone()));
+    assert_err!(response.decode());
+
+    // Verify the estimated size accounts for every response in the batch
+    // (plus the variant index and length prefix of the batch itself).
+    let inner_sizes: usize = data_responses
+        .iter()
+        .map(|data_response| data_response.estimated_serialized_size().unwrap())
+        .sum();
+    assert_eq!(batch.estimated_serialized_size().unwrap(), inner_sizes + 2);
+    assert_eq!(
+        batch.estimated_serialized_size().unwrap(),
+        bcs::serialized_size(&batch).unwrap()
+    );
+
+    // Verify batches exceeding the max message size are rejected
+    let state_value_chunk = StateValueChunkWithProof {
+        first_index: 0,
+        last_index: 0,
+        first_key: HashValue::random(),
+        last_key: HashValue::random(),
+        raw_values: vec![(
+            StateKey::raw(vec![0]),
+            StateValue::from(vec![0; MAX_APPLICATION_MESSAGE_SIZE / 2]),
+        )],
+        proof: SparseMerkleRangeProof::new(vec![]),
+        root_hash: HashValue::random(),
+    };
+    let large_response = DataResponse::StateValueChunkWithProof(state_value_chunk);
+    assert_ok!(DataResponse::new_batch(vec![large_response.clone()]));
+    assert_err!(DataResponse::new_batch(vec![
+        large_response.clone(),
+        large_response
+    ]));
+}
+
+#[test]
+fn test_data_response_canonical_labels_are_stable() {
+    // The stable set of canonical labels. These must never change!
+    let stable_labels = [
+        "epoch_ending_ledger_infos",
+        "new_transaction_outputs_with_proof",
+        "new_transactions_with_proof",
+        "number_of_states_at_version",
+        "server_protocol_version",
+        "state_value_chunk_with_proof",
+        "storage_server_summary",
+        "transaction_outputs_with_proof",
+        "transactions_with_proof",
+        "new_transactions_or_outputs_with_proof",
+        "transactions_or_outputs_with_proof",
+        "transaction_range_proof",
+        "transaction_by_hash_with_proof",
+        "latest_ledger_info",
+        "epoch_change_proof_between",
+        "batch",
+    ];
+    assert_eq!(DataResponse::all_labels(), stable_labels);
+
+    // Verify the canonical labels never collide
+    let unique_labels: HashSet<_> = stable_labels.iter().collect();
+    assert_eq!(unique_labels.len(), NUM_DATA_RESPONSE_VARIANTS);
+
+    // Verify the canonical labels of each variant match the stable set
+    for response in [
+        DataResponse::NumberOfStatesAtVersion(10),
+        DataResponse::StorageServerSummary(StorageServerSummary::default()),
+        DataResponse::TransactionsWithProof(TransactionListWithProof::new_empty()),
+        DataResponse::TransactionRangeProof(TransactionAccumulatorRangeProof::new_empty()),
+        DataResponse::TransactionByHashWithProof(None),
+    ] {
+        let variant_index = get_data_response_variant_index(&response);
+        assert_eq!(stable_labels[variant_index], response.canonical_label());
+    }
+}
+
+#[test]
+fn test_bcs_golden_complete_data_range() {
+    assert_bcs_stable(&create_range(100, 200), "6400000000000000c800000000000000");
+}
+
+#[test]
+fn test_bcs_golden_storage_server_summary() {
+    // Verify the wire layout of an empty summary (with the default chunk sizes)
+    assert_bcs_stable(
+        &StorageServerSummary::default(),
+        "c800000000000000a00f000000000000d007000000000000e8030000000000000000000000",
+    );
+
+    // Verify the wire layout of a populated summary
+    let storage_server_summary = StorageServerSummary {
+        protocol_metadata: ProtocolMetadata {
+            max_epoch_chunk_size: 1,
+            max_state_chunk_size: 2,
+            max_transaction_chunk_size: 3,
+            max_transaction_output_chunk_size: 4,
+        },
+        data_summary: DataSummary {
+            synced_ledger_info: None,
+            epoch_ending_ledger_infos: Some(create_range(0, 9)),
+            states: None,
+            transactions: Some(create_range(100, 200)),
+            transaction_outputs: None,
+        },
+    };
+    assert_bcs_stable(
+        &storage_server_summary,
+        "0100000000000000020000000000000003000000000000000400000000000000000100000000000000000900\
+         00000000000000016400000000000000c80000000000000000",
+    );
+}
+
+#[test]
+fn test_bcs_golden_data_responses() {
+    // Verify the wire layout of several data responses
+    assert_bcs_stable(
+        &DataResponse::NumberOfStatesAtVersion(10),
+        "030a00000000000000",
+    );
+    assert_bcs_stable(
+        &DataResponse::StorageServerSummary(StorageServerSummary::default()),
+        "06c800000000000000a00f000000000000d007000000000000e8030000000000000000000000",
+    );
+    assert_bcs_stable(
+        &DataResponse::TransactionsWithProof(TransactionListWithProof::new_empty()),
+        "08000000000000",
+    );
+    assert_bcs_stable(
+        &DataResponse::EpochEndingLedgerInfos(EpochChangeProof {
+            ledger_info_with_sigs: vec![],
+            more: true,
+        }),
+        "000001",
+    );
+    assert_bcs_stable(&DataResponse::TransactionByHashWithProof(None), "0c00");
+    assert_bcs_stable(
+        &DataResponse::EpochChangeProofBetween(MinimalEpochChangeProof(EpochChangeProof::new(
+            vec![],
+            false,
+        ))),
+        "0e0000",
+    );
+
+    // Verify the wire layout of a raw (i.e., uncompressed) list response
+    assert_bcs_stable(
+        &StorageServiceResponse::RawResponse(DataResponse::TransactionsWithProof(
+            TransactionListWithProof::new_empty(),
+        )),
+        "0108000000000000",
+    );
+}
+
+#[test]
+fn test_storage_service_response_compression_scheme() {
+    // Create a compressed response (with the supported compression scheme)
+    let data_response =
+        DataResponse::TransactionsWithProof(create_transaction_list(100, 10, vec![], vec![]));
+    let response = StorageServiceResponse::new(data_response.clone(), true).unwrap();
+    assert_eq!(response.get_data_response().unwrap(), data_response);
+    let compressed_data = match response {
+        StorageServiceResponse::CompressedResponse(_, compressed_data) => compressed_data,
+        response => panic!("Expected a compressed response but got: {:?}", response),
+    };
+
+    // Verify that unknown compression schemes are rejected up front
+    for (label, scheme) in [
+        ("transactions_with_proof_zstd", "zstd"),
+        ("new_transactions_with_proof_snappy", "snappy"),
+        ("unknown_label", "unknown_label"),
+    ] {
+        let response =
+            StorageServiceResponse::CompressedResponse(label.into(), compressed_data.clone());
+        assert_eq!(
+            response.get_data_response().unwrap_err(),
+            Error::UnsupportedCompressionScheme {
+                scheme: scheme.into()
+            }
+        );
+    }
+
+    // Verify that data not produced by the (known) scheme produces a clear error
+    let label = "transactions_with_proof_compressed";
+    let response = StorageServiceResponse::CompressedResponse(label.into(), vec![1, 2, 3]);
+    match response.get_data_response().unwrap_err() {
+        Error::UnexpectedResponseError(error) => {
+            assert!(error.contains("Failed to decompress the response"));
+            assert!(error.contains(label));
+        },
+        error => panic!("Expected an unexpected response error but got: {:?}", error),
+    }
+}
+
+#[test]
+fn test_storage_service_response_checksum() {
+    // Create a checksummed response and verify the label
+    let data_response =
+        DataResponse::TransactionsWithProof(create_transaction_list(100, 10, vec![], vec![]));
+    let response = StorageServiceResponse::new_with_checksum(data_response.clone()).unwrap();
+    let label = "transactions_with_proof_compressed_checksummed";
+    assert_eq!(response.get_label(), label);
+    assert!(response.is_compressed());
+
+    // Verify the response is decoded (i.e., the checksum is valid)
+    assert_eq!(response.get_data_response().unwrap(), data_response);
+    assert_eq!(
+        response.clone().decode().unwrap(),
+        DecodedResponse::TransactionsWithProof(create_transaction_list(100, 10, vec![], vec![]))
+    );
+
+    // Verify that flipping any single bit is detected
+    let checksummed_data = match response {
+        StorageServiceResponse::CompressedResponse(_, compressed_data) => compressed_data,
+        response => panic!("Expected a compressed response but got: {:?}", response),
+    };
+    for index in [0, checksummed_data.len() / 2, checksummed_data.len() - 1] {
+        let mut corrupted_data = checksummed_data.clone();
+        corrupted_data[index] ^= 0x01;
+        let response = StorageServiceResponse::CompressedResponse(label.into(), corrupted_data);
+        assert_eq!(
+            response.get_data_response().unwrap_err(),
+            Error::ChecksumMismatch {
+                label: label.into()
+            }
+        );
+    }
+
+    // Verify that truncated data (i.e., without a full checksum) is rejected
+    let response = StorageServiceResponse::CompressedResponse(label.into(), vec![1, 2, 3]);
+    assert_eq!(
+        response.get_data_response().unwrap_err(),
+        Error::ChecksumMismatch {
+            label: label.into()
+        }
+    );
+}
+
+#[test]
+fn test_storage_service_response_requires_compression() {
+    // Verify the protocol version response may be raw, but still respects the
+    // compression flag (as older clients reject raw responses).
+    let protocol_version_response = DataResponse::ServerProtocolVersion(ServerProtocolVersion {
+        protocol_version: 1,
+    });
+    assert!(!requires_compression(
+        protocol_version_response.canonical_label()
+    ));
+
+    // Verify the transactions response must be compressed
+    let transactions_response =
+        DataResponse::TransactionsWithProof(create_transaction_list(100, 10, vec![], vec![]));
+    assert!(requires_compression(
+        transactions_response.canonical_label()
+    ));
+
+    // Verify both responses respect the compression flag
+    for data_response in [protocol_version_response, transactions_response] {
+        for perform_compression in [false, true] {
+            let response =
+                StorageServiceResponse::new(data_response.clone(), perform_compression).unwrap();
+            assert_eq!(response.is_compressed(), perform_compression);
+            assert_eq!(response.get_data_response().unwrap(), data_response);
+        }
+    }
+
+    // Verify all raw tolerated labels are valid response labels
+    for label in RAW_TOLERATED_RESPONSE_LABELS {
+        assert!(DataResponse::all_labels().contains(label));
+        assert!(!requires_compression(label));
+    }
+}
+
+#[test]
+fn test_storage_service_response_try_item_count() {
+    // Create a data response (and the expected item count) for several variants
+    let ledger_info = create_mock_ledger_info(100);
+    let transaction_list = create_transaction_list(100, 10, vec![], vec![]);
+    let output_list = TransactionOutputListWithProof::new_empty();
+    let state_value_chunk = StateValueChunkWithProof {
+        first_index: 0,
+        last_index: 2,
+        first_key: HashValue::random(),
+        last_key: HashValue::random(),
+        raw_values: (0..3)
+            .map(|index| (StateKey::raw(vec![index]), StateValue::from(vec![index])))
+            .collect(),
+        proof: SparseMerkleRangeProof::new(vec![]),
+        root_hash: HashValue::random(),
+    };
+    let epoch_change_proof =
+        EpochChangeProof::new(vec![ledger_info.clone(), ledger_info.clone()], false);
+    let data_responses = vec![
+        (
+            DataResponse::EpochEndingLedgerInfos(epoch_change_proof.clone()),
+            Some(2),
+        ),
+        (
+            DataResponse::NewTransactionsWithProof((transaction_list.clone(), ledger_info.clone())),
+            Some(10),
+        ),
+        (
+            DataResponse::StateValueChunkWithProof(state_value_chunk),
+            Some(3),
+        ),
+        (
+            DataResponse::TransactionOutputsWithProof(output_list.clone()),
+            Some(0),
+        ),
+        (
+            DataResponse::TransactionsWithProof(transaction_list.clone()),
+            Some(10),
+        ),
+        (
+            DataResponse::NewTransactionsOrOutputsWithProof((
+                (None, Some(output_list)),
+                ledger_info.clone(),
+            )),
+            Some(0),
+        ),
+        (
+            DataResponse::TransactionsOrOutputsWithProof((Some(transaction_list), None)),
+            Some(10),
+        ),
+        (
+            DataResponse::EpochChangeProofBetween(MinimalEpochChangeProof(epoch_change_proof)),
+            Some(2),
+        ),
+        (DataResponse::LatestLedgerInfo(ledger_info), None),
+        (
+            DataResponse::StorageServerSummary(StorageServerSummary::default()),
+            None,
+        ),
+    ];
+
+    // Verify the item count is read correctly for each compression scheme
+    for (data_response, expected_item_count) in data_responses {
+        for scheme in [
+            CompressionScheme::None,
+            CompressionScheme::Compressed,
+            CompressionScheme::CompressedWithChecksum,
+        ] {
+            let response = StorageServiceResponse::RawResponse(data_response.clone())
+                .recompress_as(scheme)
+                .unwrap();
+            assert_eq!(response.try_item_count().unwrap(), expected_item_count);
+        }
+    }
+
+    // Verify a corrupted checksum is detected
+    let response = StorageServiceResponse::RawResponse(DataResponse::TransactionsWithProof(
+        create_transaction_list(100, 10, vec![], vec![]),
+    ))
+    .recompress_as(CompressionScheme::CompressedWithChecksum)
+    .unwrap();
+    if let StorageServiceResponse::CompressedResponse(label, mut compressed_data) = response {
+        *compressed_data.last_mut().unwrap() ^= 1;
+        let response = StorageServiceResponse::CompressedResponse(label, compressed_data);
+        assert!(matches!(
+            response.try_item_count(),
+            Err(Error::ChecksumMismatch { .. })
+        ));
+    } else {
+        panic!("Expected a compressed response!");
+    }
+}
+
+#[test]
+fn test_storage_service_response_recompress_as() {
+    // Create a response for each compression scheme
+    let data_response =
+        DataResponse::TransactionsWithProof(create_transaction_list(100, 10, vec![], vec![]));
+    let schemes = [
+        CompressionScheme::None,
+        CompressionScheme::Compressed,
+        CompressionScheme::CompressedWithChecksum,
+    ];
+    let responses = [
+        StorageServiceResponse::new(data_response.clone(), false).unwrap(),
+        StorageServiceResponse::new(data_response.clone(), true).unwrap(),
+        StorageServiceResponse::new_with_checksum(data_response.clone()).unwrap(),
+    ];
+
+    // Verify recompressing between all schemes produces the expected responses
+    for response in responses.iter() {
+        for (scheme, expected_response) in schemes.iter().zip(responses.iter()) {
+            let recompressed_response = response.recompress_as(*scheme).unwrap();
+            assert_eq!(
+                recompressed_response.get_label(),
+                expected_response.get_label()
+            );
+            assert_eq!(
+                recompressed_response.is_compressed(),
+                *scheme != CompressionScheme::None
+            );
+
+            // Verify the data is unchanged
+            assert!(recompressed_response.same_data(response).unwrap());
+            assert!(recompressed_response.same_data(expected_response).unwrap());
+            assert_eq!(
+                recompressed_response.get_data_response().unwrap(),
+                data_response
+            );
+        }
+    }
+
+    // Verify responses holding different data are not the same
+    let other_response = StorageServiceResponse::new(
+        DataResponse::TransactionsWithProof(create_transaction_list(200, 10, vec![], vec![])),
+        true,
+    )
+    .unwrap();
+    assert!(!responses[1].same_data(&other_response).unwrap());
+
+    // Verify responses that fail to decode can't be recompressed
+    let response = StorageServiceResponse::CompressedResponse(
+        "transactions_with_proof_compressed_checksummed".into(),
+        vec![1, 2, 3],
+    );
+    assert!(response.recompress_as(CompressionScheme::None).is_err());
+    assert!(response.same_data(&responses[0]).is_err());
+}
+
+#[test]
+fn test_decode_many() {
+    // Create a batch of raw, compressed and checksummed responses
+    let mut responses = vec![];
+    let mut expected_data_responses = vec![];
+    for index in 0..30 {
+        let data_response = DataResponse::TransactionsWithProof(create_transaction_list(
+            index * 100,
+            10,
+            vec![],
+            vec![],
+        ));
+        let response = match index % 3 {
+            0 => StorageServiceResponse::new(data_response.clone(), false),
+            1 => StorageServiceResponse::new(data_response.clone(), true),
+            _ => StorageServiceResponse::new_with_checksum(data_response.clone()),
+        };
+        responses.push(response.unwrap());
+        expected_data_responses.push(data_response);
+    }
+
+    // Verify the responses are decoded in order
+    let decoded_responses: Vec<_> = decode_many(responses.clone())
+        .into_iter()
+        .map(|result| result.unwrap())
+        .collect();
+    assert_eq!(decoded_responses, expected_data_responses);
+
+    // Corrupt one of the responses and verify only that item fails
+    let bad_index = 10;
+    responses[bad_index] = StorageServiceResponse::CompressedResponse(
+        "transactions_with_proof_compressed".into(),
+        vec![1, 2, 3],
+    );
+    let results = decode_many(responses);
+    assert_eq!(results.len(), expected_data_responses.len());
+    for (index, result) in results.into_iter().enumerate() {
+        if index == bad_index {
+            assert_err!(result);
+        } else {
+            assert_eq!(result.unwrap(), expected_data_responses[index]);
+        }
+    }
+
+    // Verify an empty batch is decoded
+    assert!(decode_many(vec![]).is_empty());
+}
+
+#[test]
+fn test_storage_service_response_decode() {
+    // Create a data response (and the expected decoded response) for each variant
+    let ledger_info = create_mock_ledger_info(100);
+    let transaction_list = create_transaction_list(100, 10, vec![], vec![]);
+    let output_list = TransactionOutputListWithProof::new_empty();
+    let state_value_chunk = StateValueChunkWithProof {
+        first_index: 0,
+        last_index: 0,
+        first_key: HashValue::random(),
+        last_key: HashValue::random(),
+        raw_values: vec![],
+        proof: SparseMerkleRangeProof::new(vec![]),
+        root_hash: HashValue::random(),
+    };
+    let responses = vec![
+        (
+            DataResponse::EpochEndingLedgerInfos(EpochChangeProof::new(vec![], false)),
+            DecodedResponse::EpochEndingLedgerInfos(EpochChangeProof::new(vec![], false)),
+        ),
+        (
+            DataResponse::NewTransactionOutputsWithProof((
+                output_list.clone(),
+                ledger_info.clone(),
+            )),
+            DecodedResponse::NewTransactionOutputsWithProof {
+                output_list: output_list.clone(),
+                target_ledger_info: ledger_info.clone(),
+            },
+        ),
+        (
+            DataResponse::NewTransactionsWithProof((transaction_list.clone(), ledger_info.clone())),
+            DecodedResponse::NewTransactionsWithProof {
+                transaction_list: transaction_list.clone(),
+                target_ledger_info: ledger_info.clone(),
+            },
+        ),
+        (
+            DataResponse::NumberOfStatesAtVersion(10),
+            DecodedResponse::NumberOfStatesAtVersion(10),
+        ),
+        (
+            DataResponse::ServerProtocolVersion(ServerProtocolVersion {
+                protocol_version: 1,
+            }),
+            DecodedResponse::ServerProtocolVersion(ServerProtocolVersion {
+                protocol_version: 1,
+            }),
+        ),
+        (
+            DataResponse::StateValueChunkWithProof(state_value_chunk.clone()),
+            DecodedResponse::StateValueChunkWithProof(state_value_chunk),
+        ),
+        (
+            DataResponse::StorageServerSummary(StorageServerSummary::default()),
+            DecodedResponse::StorageServerSummary(StorageServerSummary::default()),
+        ),
+        (
+            DataResponse::TransactionOutputsWithProof(output_list.clone()),
+            DecodedResponse::TransactionOutputsWithProof(output_list.clone()),
+        ),
+        (
+            DataResponse::TransactionsWithProof(transaction_list.clone()),
+            DecodedResponse::TransactionsWithProof(transaction_list.clone()),
+        ),
+        (
+            DataResponse::NewTransactionsOrOutputsWithProof((
+                (None, Some(output_list.clone())),
+                ledger_info.clone(),
+            )),
+            DecodedResponse::NewTransactionsOrOutputsWithProof {
+                transactions_or_outputs: TxnsOrOutputs::Outputs(output_list),
+                target_ledger_info: ledger_info.clone(),
+            },
+        ),
+        (
+            DataResponse::TransactionsOrOutputsWithProof((Some(transaction_list.clone()), None)),
+            DecodedResponse::TransactionsOrOutputsWithProof(TxnsOrOutputs::Transactions(
+                transaction_list,
+            )),
+        ),
+        (
+            DataResponse::TransactionRangeProof(TransactionAccumulatorRangeProof::new_empty()),
+            DecodedResponse::TransactionRangeProof(TransactionAccumulatorRangeProof::new_empty()),
+        ),
+        (
+            DataResponse::TransactionByHashWithProof(None),
+            DecodedResponse::TransactionByHashWithProof(None),
+        ),
+        (
+            DataResponse::LatestLedgerInfo(ledger_info.clone()),
+            DecodedResponse::LatestLedgerInfo(ledger_info.clone()),
+        ),
+        (
+            DataResponse::EpochChangeProofBetween(MinimalEpochChangeProof(EpochChangeProof::new(
+                vec![],
+                false,
+            ))),
+            DecodedResponse::EpochChangeProofBetween(MinimalEpochChangeProof(
+                EpochChangeProof::new(vec![], false),
+            )),
+        ),
+        (
+            DataResponse::Batch(vec![DataResponse::LatestLedgerInfo(ledger_info.clone())]),
+            DecodedResponse::Batch(vec![DecodedResponse::LatestLedgerInfo(ledger_info.clone())]),
+        ),
+    ];
+
+    // Verify every variant is covered
+    let covered_variants: HashSet<_> = responses
+        .iter()
+        .map(|(data_response, _)| get_data_response_variant_index(data_response))
+        .collect();
+    assert_eq!(covered_variants.len(), NUM_DATA_RESPONSE_VARIANTS);
+
+    // Verify each variant index matches the index used by BCS
+    for (data_response, _) in &responses {
+        let variant_index = get_data_response_variant_index(data_response);
+        assert_eq!(data_response.variant_index(), variant_index);
+        assert_eq!(
+            bcs::to_bytes(data_response).unwrap()[0] as usize,
+            variant_index
+        );
+    }
+
+    // Verify each response is decoded into the matching variant
+    for (data_response, expected_decoded_response) in responses {
+        for compression in [true, false] {
+            let response = StorageServiceResponse::new(data_response.clone(), compression).unwrap();
+            assert_eq!(response.decode().unwrap(), expected_decoded_response);
+        }
+    }
+
+    // Verify invalid transaction or output lists are rejected
+    let response = StorageServiceResponse::new(
+        DataResponse::TransactionsOrOutputsWithProof((None, None)),
+        false,
+    )
+    .unwrap();
+    assert_err!(response.decode());
+}
+
+#[test]
+fn test_data_summary_can_service_txn_by_hash_request() {
+    let summary = DataSummary {
+        synced_ledger_info: Some(create_mock_ledger_info(250)),
+        transactions: Some(create_range(100, 200)),
+        ..Default::default()
+    };
+
+    for compression in [true, false] {
+        // Requests with a candidate range that is held and a valid proof version can be serviced
+        let valid_requests = vec![
+            txn_by_hash_request(225, 100, 200, compression),
+            txn_by_hash_request(200, 125, 175, compression),
+            txn_by_hash_request(250, 150, 150, compression),
+        ];
+        for request in valid_requests {
+            assert!(summary.can_service(&request));
+        }
+
+        // Requests with a candidate range that isn't held can't be serviced
+        for request in [
+            txn_by_hash_request(225, 99, 200, compression),
+            txn_by_hash_request(225, 150, 201, compression),
+        ] {
+            assert_eq!(
+                summary.can_service_with_reason(&request),
+                Err(UnserviceableReason::DataUnavailable)
+            );
+        }
+
+        // Requests with invalid proof versions or degenerate ranges can't be serviced
+        assert_eq!(
+            summary.can_service_with_reason(&txn_by_hash_request(251, 100, 200, compression)),
+            Err(UnserviceableReason::ProofUnavailable)
+        );
+        assert_eq!(
+            summary.can_service_with_reason(&txn_by_hash_request(225, 200, 100, compression)),
+            Err(UnserviceableReason::DegenerateRange)
+        );
+    }
+}
+
+#[test]
+fn test_txn_by_hash_labels_and_conversion() {
+    // Verify the request and response labels
+    let request = txn_by_hash_request(225, 100, 200, false);
+    assert_eq!(
+        request.data_request.get_label(),
+        "get_transaction_by_hash_with_proof"
+    );
+    let data_response = DataResponse::TransactionByHashWithProof(None);
+    assert_eq!(data_response.get_label(), "transaction_by_hash_with_proof");
+
+    // Verify the response conversions (with and without compression)
+    for compression in [true, false] {
+        let response = StorageServiceResponse::new(data_response.clone(), compression).unwrap();
+        let transaction_with_proof: Option<TransactionWithProof> = response.try_into().unwrap();
+        assert_eq!(transaction_with_proof, None);
+
+        // Verify the conversion fails for other response types
+        let response =
+            StorageServiceResponse::new(DataResponse::NumberOfStatesAtVersion(10), compression)
+                .unwrap();
+        let result: Result<Option<TransactionWithProof>, _> = response.try_into();
+        assert_err!(result);
+    }
+}
+
+#[test]
+fn test_data_summary_can_service_latest_ledger_info_request() {
+    for compression in [true, false] {
+        let request = StorageServiceRequest::new(DataRequest::GetLatestLedgerInfo, compression);
+
+        // Verify the request can't be serviced without a synced ledger info
+        let summary = DataSummary {
+            transactions: Some(create_range(100, 200)),
+            ..Default::default()
+        };
+        assert_eq!(
+            summary.can_service_with_reason(&request),
+            Err(UnserviceableReason::DataUnavailable)
+        );
+
+        // Verify the request can be serviced with any synced ledger info
+        for version in [0, 250] {
+            let summary = DataSummary {
+                synced_ledger_info: Some(create_mock_ledger_info(version)),
+                ..Default::default()
+            };
+            assert!(summary.can_service(&request));
+        }
+    }
+}
+
+#[test]
+fn test_data_summary_can_service_batch_request() {
+    let summary = DataSummary {
+        synced_ledger_info: Some(create_mock_ledger_info(250)),
+        transactions: Some(create_range(100, 200)),
+        ..Default::default()
+    };
+
+    for compression in [true, false] {
+        // All batched requests can be serviced, can service
+        let data_requests = vec![
+            DataRequest::GetLatestLedgerInfo,
+            txns_request(225, 100, 200, compression).data_request,
+        ];
+        let request =
+            StorageServiceRequest::new(DataRequest::GetBatch(data_requests.clone()), compression);
+        assert_eq!(request.get_label(), "get_batch");
+        assert_ok!(summary.can_service_with_reason(&request));
+
+        // An empty batch can be serviced
+        let request = StorageServiceRequest::new(DataRequest::GetBatch(vec![]), compression);
+        assert_ok!(summary.can_service_with_reason(&request));
+
+        // A single batched request can't be serviced, can't service
+        let mut unserviceable_requests = data_requests.clone();
+        unserviceable_requests.push(txns_request(225, 150, 201, compression).data_request);
+        let request =
+            StorageServiceRequest::new(DataRequest::GetBatch(unserviceable_requests), compression);
+        assert_eq!(
+            summary.can_service_with_reason(&request),
+            Err(UnserviceableReason::DataUnavailable)
+        );
+
+        // Nested batches can't be serviced
+        let nested_requests = vec![DataRequest::GetBatch(data_requests)];
+        let request =
+            StorageServiceRequest::new(DataRequest::GetBatch(nested_requests), compression);
+        assert_eq!(
+            summary.can_service_with_reason(&request),
+            Err(UnserviceableReason::DataUnavailable)
+        );
+    }
+}
+
+#[test]
+fn test_data_summary_can_service_full_u64_range_request() {
+    // Create a data summary for a peer holding the full history
+    let highest_version = u64::MAX - 1;
+    let summary = DataSummary {
+        synced_ledger_info: Some(create_mock_ledger_info(highest_version)),
+        epoch_ending_ledger_infos: Some(CompleteDataRange::from_genesis(highest_version)),
+        transactions: Some(CompleteDataRange::from_genesis(highest_version)),
+        transaction_outputs: Some(CompleteDataRange::from_genesis(highest_version)),
+        ..Default::default()
+    };
+
+    for compression in [true, false] {
+        // Verify that requests for the full u64 range are rejected as degenerate
+        for request in [
+            epochs_request(0, u64::MAX, compression),
+            txns_request(0, 0, u64::MAX, compression),
+            outputs_request(0, 0, u64::MAX, compression),
+            txns_or_outputs_request(0, 0, u64::MAX, compression),
+            txn_range_proof_request(0, 0, u64::MAX, compression),
+        ] {
+            assert_eq!(
+                summary.can_service_with_reason(&request),
+                Err(UnserviceableReason::DegenerateRange)
+            );
+        }
+
+        // Verify that the largest valid request can still be serviced
+        let request = txns_request(0, 0, highest_version, compression);
+        assert_ok!(summary.can_service_with_reason(&request));
+    }
+}
+
+#[test]
+fn test_latest_ledger_info_labels_and_conversion() {
+    // Verify the request and response labels
+    let request = DataRequest::GetLatestLedgerInfo;
+    assert_eq!(request.get_label(), "get_latest_ledger_info");
+    let ledger_info = create_mock_ledger_info(250);
+    let data_response = DataResponse::LatestLedgerInfo(ledger_info.clone());
+    assert_eq!(data_response.get_label(), "latest_ledger_info");
+
+    // Verify the response conversions (with and without compression)
+    for compression in [true, false] {
+        let response = StorageServiceResponse::new(data_response.clone(), compression).unwrap();
+        let latest_ledger_info: LedgerInfoWithSignatures = response.try_into().unwrap();
+        assert_eq!(latest_ledger_info, ledger_info);
+
+        // Verify the conversion fails for other response types
+        let response =
+            StorageServiceResponse::new(DataResponse::NumberOfStatesAtVersion(10), compression)
+                .unwrap();
+        let result: Result<LedgerInfoWithSignatures, _> = response.try_into();
+        assert_err!(result);
+    }
+}
+
+#[test]
+fn test_epoch_change_proof_between_labels_and_conversion() {
+    // Verify the request and response labels
+    let request = DataRequest::GetEpochChangeProofBetween(EpochChangeProofBetweenRequest {
+        start_epoch: 10,
+        end_epoch: 20,
+    });
+    assert_eq!(request.get_label(), "get_epoch_change_proof_between");
+    let epoch_change_proof = EpochChangeProof::new(
+        vec![
+            create_mock_ledger_info_at_epoch(10, 100),
+            create_mock_ledger_info_at_epoch(11, 200),
+        ],
+        false,
+    );
+    let epoch_change_proof = MinimalEpochChangeProof(epoch_change_proof);
+    let data_response = DataResponse::EpochChangeProofBetween(epoch_change_proof.clone());
+    assert_eq!(data_response.get_label(), "epoch_change_proof_between");
+
+    // Verify the response conversions (with and without compression)
+    for compression in [true, false] {
+        let response = StorageServiceResponse::new(data_response.clone(), compression).unwrap();
+        let response_proof: MinimalEpochChangeProof = response.try_into().unwrap();
+        assert_eq!(response_proof, epoch_change_proof);
+
+        // Verify the response isn't mistaken for epoch ending ledger infos
+        let response = StorageServiceResponse::new(data_response.clone(), compression).unwrap();
+        let result: Result<EpochChangeProof, _> = response.try_into();
+        assert_err!(result);
+
+        // Verify the conversion fails for other response types
+        let response =
+            StorageServiceResponse::new(DataResponse::NumberOfStatesAtVersion(10), compression)
+                .unwrap();
+        let result: Result<MinimalEpochChangeProof, _> = response.try_into();
+        assert_err!(result);
+    }
+
+    // Verify the decoded response
+    assert_eq!(
+        DecodedResponse::try_from(data_response).unwrap(),
+        DecodedResponse::EpochChangeProofBetween(epoch_change_proof)
+    );
+}
+
+#[test]
+fn test_data_summary_can_service_epoch_change_proof_between_request() {
+    let summary = DataSummary {
+        synced_ledger_info: Some(create_mock_ledger_info_at_epoch(201, 1000)),
+        epoch_ending_ledger_infos: Some(create_range(100, 200)),
+        ..Default::default()
+    };
+
+    for compression in [true, false] {
+        // The full epoch range is held, can service
+        for (start, end) in [(100, 201), (100, 101), (150, 175), (200, 201)] {
+            let request = epoch_change_proof_request(start, end, compression);
+            assert_ok!(summary.can_service_with_reason(&request));
+        }
+
+        // The full epoch range is not held, can't service
+        for (start, end) in [(99, 150), (150, 202), (50, 250), (201, 202)] {
+            let request = epoch_change_proof_request(start, end, compression);
+            assert_eq!(
+                summary.can_service_with_reason(&request),
+                Err(UnserviceableReason::DataUnavailable)
+            );
+        }
+
+        // Degenerate ranges (i.e., start >= end), can't service
+        for (start, end) in [(150, 150), (150, 149), (0, 0)] {
+            let request = epoch_change_proof_request(start, end, compression);
+            assert_eq!(
+                summary.can_service_with_reason(&request),
+                Err(UnserviceableReason::DegenerateRange)
+            );
+        }
+
+        // The synced ledger info predates the end epoch, can't service
+        let stale_summary = DataSummary {
+            synced_ledger_info: Some(create_mock_ledger_info_at_epoch(150, 1000)),
+            ..summary.clone()
+        };
+        let request = epoch_change_proof_request(100, 151, compression);
+        assert_eq!(
+            stale_summary.can_service_with_reason(&request),
+            Err(UnserviceableReason::SyncedEpochTooLow)
+        );
+    }
+}
+
+#[test]
+fn test_makes_sufficient_progress() {
+    let requested = create_range(100, 1099);
+
+    // Verify a full response makes sufficient progress
+    assert!(makes_sufficient_progress(&requested, &requested, 0.5));
+    assert!(makes_sufficient_progress(&requested, &requested, 1.0));
+
+    // Verify a reasonably truncated response makes sufficient progress
+    let served = create_range(100, 599);
+    assert!(makes_sufficient_progress(&served, &requested, 0.5));
+    assert!(makes_sufficient_progress(&served, &requested, 0.1));
+    assert!(!makes_sufficient_progress(&served, &requested, 0.75));
+
+    // Verify a one version response doesn't make sufficient progress
+    let served = create_range(100, 100);
+    assert!(!makes_sufficient_progress(&served, &requested, 0.1));
+    assert!(makes_sufficient_progress(
+        &served,
+        &create_range(100, 100),
+        1.0
+    ));
+
+    // Verify responses that aren't a prefix of the request are rejected
+    assert!(!makes_sufficient_progress(
+        &create_range(101, 1099),
+        &requested,
+        0.1
+    ));
+    assert!(!makes_sufficient_progress(
+        &create_range(100, 1100),
+        &requested,
+        0.1
+    ));
+
+    // Verify the full version range doesn't overflow
+    let requested = create_range(0, u64::MAX);
+    assert!(makes_sufficient_progress(&requested, &requested, 1.0));
+    assert!(!makes_sufficient_progress(
+        &create_range(0, 0),
+        &requested,
+        0.1
+    ));
+}
+
+#[test]
+fn test_estimate_sync_bytes() {
+    // Verify the estimate for no ranges (or free versions) is zero
+    assert_eq!(estimate_sync_bytes(&[], 100), 0);
+    assert_eq!(estimate_sync_bytes(&[create_range(0, 99)], 0), 0);
+
+    // Verify the estimate sums the lengths of several ranges
+    let ranges = [
+        create_range(0, 99),
+        create_range(500, 500),
+        create_range(1000, 1049),
+    ];
+    assert_eq!(estimate_sync_bytes(&ranges, 10), (100 + 1 + 50) * 10);
+
+    // Verify the estimate doesn't overflow for spans near u64::MAX
+    let full_range = create_range(0, u64::MAX);
+    let full_len = u128::from(u64::MAX) + 1;
+    assert_eq!(estimate_sync_bytes(&[full_range], 1), full_len);
+    assert_eq!(
+        estimate_sync_bytes(&[full_range, full_range], 1000),
+        2 * full_len * 1000
+    );
+    assert_eq!(
+        estimate_sync_bytes(&[full_range], usize::MAX),
+        full_len * usize::MAX as u128
+    );
+    assert_eq!(
+        estimate_sync_bytes(&[create_range(1, u64::MAX)], 2),
+        u128::from(u64::MAX) * 2
+    );
+}
+
+#[test]
+fn test_storage_server_summary_can_service_any_and_all() {
+    let summary = StorageServerSummary {
+        data_summary: DataSummary {
+            synced_ledger_info: Some(create_mock_ledger_info(200)),
+            transactions: Some(create_range(100, 200)),
+            epoch_ending_ledger_infos: Some(create_range(0, 10)),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+
+    for compression in [true, false] {
+        let serviceable_requests = vec![
+            txns_request(200, 100, 200, compression),
+            epochs_request(0, 10, compression),
+            StorageServiceRequest::new(DataRequest::GetServerProtocolVersion, compression),
+        ];
+        let unserviceable_requests = vec![
+            txns_request(300, 250, 300, compression),
+            outputs_request(200, 100, 200, compression),
+            epochs_request(5, 15, compression),
+        ];
+
+        // Verify the methods for requests that can all be serviced
+        assert!(summary.can_service_any(&serviceable_requests));
+        assert!(summary.can_service_all(&serviceable_requests));
+
+        // Verify the methods for requests that can't be serviced at all
+        assert!(!summary.can_service_any(&unserviceable_requests));
+        assert!(!summary.can_service_all(&unserviceable_requests));
+
+        // Verify the methods for a mix of requests (in either order)
+        let mut mixed_requests = unserviceable_requests.clone();
+        mixed_requests.push(serviceable_requests[0].clone());
+        assert!(summary.can_service_any(&mixed_requests));
+        assert!(!summary.can_service_all(&mixed_requests));
+        mixed_requests.reverse();
+        assert!(summary.can_service_any(&mixed_requests));
+        assert!(!summary.can_service_all(&mixed_requests));
+    }
+
+    // Verify the methods for an empty set of requests
+    assert!(!summary.can_service_any(&[]));
+    assert!(summary.can_service_all(&[]));
+}
+
+#[test]
+fn test_fleet_progress() {
+    let create_summary = |epochs, states, transactions| StorageServerSummary {
+        data_summary: DataSummary {
+            epoch_ending_ledger_infos: epochs,
+            states,
+            transactions,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+
+    // Create a fleet snapshot with overlapping and disjoint ranges
+    let before = vec![
+        create_summary(Some(create_range(0, 10)), None, Some(create_range(0, 100))),
+        create_summary(
+            None,
+            Some(create_range(50, 60)),
+            Some(create_range(50, 150)),
+        ),
+        create_summary(None, None, Some(create_range(300, 399))),
+    ];
+
+    // Verify the progress of an unchanged fleet is zero
+    assert_eq!(fleet_progress(&before, &before), FleetProgress::default());
+    assert_eq!(fleet_progress(&[], &[]), FleetProgress::default());
+
+    // Create a fleet snapshot that advanced (with a reordered and new peer)
+    let advanced = vec![
+        create_summary(None, None, Some(create_range(300, 499))),
+        create_summary(Some(create_range(0, 15)), None, Some(create_range(0, 100))),
+        create_summary(
+            None,
+            Some(create_range(55, 80)),
+            Some(create_range(50, 200)),
+        ),
+        create_summary(
+            None,
+            Some(create_range(100, 109)),
+            Some(create_range(150, 160)),
+        ),
+    ];
+
+    // Verify the progress of the advancing fleet
+    let progress = fleet_progress(&before, &advanced);
+    assert_eq!(progress, FleetProgress {
+        epoch_ending_ledger_infos_delta: 5,
+        states_delta: (26 + 10) - 11,
+        transactions_delta: (201 + 200) - (151 + 100),
+    });
+
+    // Verify the progress of a regressing fleet (i.e., the reverse)
+    let progress = fleet_progress(&advanced, &before);
+    assert_eq!(progress, FleetProgress {
+        epoch_ending_ledger_infos_delta: -5,
+        states_delta: 11 - (26 + 10),
+        transactions_delta: (151 + 100) - (201 + 200),
+    });
+
+    // Verify the progress of a fleet that lost all its peers
+    let progress = fleet_progress(&before, &[]);
+    assert_eq!(progress, FleetProgress {
+        epoch_ending_ledger_infos_delta: -11,
+        states_delta: -11,
+        transactions_delta: -(151 + 100),
+    });
+
+    // Verify the progress doesn't overflow for ranges spanning the full u64 range
+    let full_fleet = vec![
+        create_summary(None, None, Some(create_range(0, u64::MAX))),
+        create_summary(None, None, Some(create_range(u64::MAX - 10, u64::MAX))),
+    ];
+    let progress = fleet_progress(&[], &full_fleet);
+    assert_eq!(progress.transactions_delta, i128::from(u64::MAX) + 1);
+}
+
+#[test]
+fn test_data_summary_synced_epoch() {
+    // Verify there's no synced epoch without a synced ledger info
+    let summary = DataSummary {
+        epoch_ending_ledger_infos: Some(create_range(0, 10)),
+        ..Default::default()
+    };
+    assert_eq!(summary.synced_epoch(), None);
+    assert_eq!(summary.compacted().synced_epoch, None);
+
+    // Verify the synced epoch matches the synced ledger info
+    let summary = DataSummary {
+        synced_ledger_info: Some(create_mock_ledger_info_at_epoch(11, 500)),
+        ..summary
+    };
+    assert_eq!(summary.synced_epoch(), Some(11));
+    assert_eq!(summary.compacted().synced_epoch, Some(11));
+
+    // Verify the synced epoch is used to cross-check epoch requests
+    assert!(summary.can_service(&epochs_request(0, 10, false)));
+    let summary = DataSummary {
+        synced_ledger_info: Some(create_mock_ledger_info_at_epoch(9, 500)),
+        ..summary
+    };
+    assert_eq!(summary.synced_epoch(), Some(9));
+    assert_eq!(
+        summary.can_service_with_reason(&epochs_request(0, 10, false)),
+        Err(UnserviceableReason::SyncedEpochTooLow)
+    );
+}
+
+#[test]
+fn test_data_summary_can_serve_state_values() {
+    // Create a data summary with states and a synced ledger info
+    let summary = DataSummary {
+        synced_ledger_info: Some(create_mock_ledger_info(150)),
+        states: Some(create_range(100, 200)),
+        ..Default::default()
+    };
+
+    // Verify the state values can be served when both conditions hold
+    assert!(summary.can_serve_state_values(100, 150));
+    assert!(summary.can_serve_state_values(200, 120));
+
+    // Verify the state values can't be served if the state version isn't held
+    assert!(!summary.can_serve_state_values(99, 150));
+    assert!(!summary.can_serve_state_values(201, 150));
+
+    // Verify the state values can't be served if the proof can't be created
+    assert!(!summary.can_serve_state_values(100, 151));
+    assert!(!summary.can_serve_state_values(200, 1000));
+
+    // Verify the state values can't be served without states or a synced ledger info
+    let summary_without_states = DataSummary {
+        states: None,
+        ..summary.clone()
+    };
+    assert!(!summary_without_states.can_serve_state_values(100, 150));
+    let summary_without_ledger_info = DataSummary {
+        synced_ledger_info: None,
+        ..summary
+    };
+    assert!(!summary_without_ledger_info.can_serve_state_values(100, 0));
+}
+
+#[test]
+fn test_data_summary_safe_request_end() {
+    // Create a data summary and protocol metadata
+    let summary = DataSummary {
+        transactions: Some(create_range(100, 1000)),
+        ..Default::default()
+    };
+    let metadata = ProtocolMetadata {
+        max_transaction_chunk_size: 250,
+        ..Default::default()
+    };
+
+    // Verify the request end is limited by the chunk size
+    assert_eq!(summary.safe_request_end(100, &metadata), Some(349));
+    assert_eq!(summary.safe_request_end(500, &metadata), Some(749));
+
+    // Verify the request end is limited by the advertised range
+    assert_eq!(summary.safe_request_end(900, &metadata), Some(1000));
+    assert_eq!(summary.safe_request_end(1000, &metadata), Some(1000));
+
+    // Verify there's no request end if the start isn't covered
+    assert_eq!(summary.safe_request_end(99, &metadata), None);
+    assert_eq!(summary.safe_request_end(1001, &metadata), None);
+    assert_eq!(
+        DataSummary::default().safe_request_end(100, &metadata),
+        None
+    );
+
+    // Verify there's no request end if the chunk size is zero
+    let empty_metadata = ProtocolMetadata {
+        max_transaction_chunk_size: 0,
+        ..Default::default()
+    };
+    assert_eq!(summary.safe_request_end(100, &empty_metadata), None);
+
+    // Verify the request end doesn't overflow
+    let summary = DataSummary {
+        transactions: Some(create_range(u64::MAX - 10, u64::MAX)),
+        ..Default::default()
+    };
+    let metadata = ProtocolMetadata {
+        max_transaction_chunk_size: u64::MAX,
+        ..Default::default()
+    };
+    assert_eq!(
+        summary.safe_request_end(u64::MAX - 5, &metadata),
+        Some(u64::MAX)
+    );
+}
+
+#[test]
+fn test_data_summary_serviceable_state_version_range() {
+    // Verify there's no serviceable range without states or a synced ledger info
+    let summary = DataSummary {
+        states: Some(create_range(100, 200)),
+        ..Default::default()
+    };
+    assert_eq!(summary.serviceable_state_version_range(), None);
+    let summary = DataSummary {
+        synced_ledger_info: Some(create_mock_ledger_info(150)),
+        ..Default::default()
+    };
+    assert_eq!(summary.serviceable_state_version_range(), None);
+
+    // Verify the synced version limits the serviceable range
+    let summary = DataSummary {
+        synced_ledger_info: Some(create_mock_ledger_info(150)),
+        states: Some(create_range(100, 200)),
+        ..Default::default()
+    };
+    let serviceable_range = summary.serviceable_state_version_range().unwrap();
+    assert_eq!(serviceable_range, create_range(100, 150));
+    for version in [100, 150, 151] {
+        let request = DataRequest::GetStateValuesWithProof(StateValuesWithProofRequest {
+            version,
+            start_index: 0,
+            end_index: 1000,
+        });
+        let request = StorageServiceRequest::new(request, false);
+        assert_eq!(
+            summary.can_service(&request),
+            serviceable_range.contains(version)
+        );
+    }
+
+    // Verify the states range limits the serviceable range
+    let summary = DataSummary {
+        synced_ledger_info: Some(create_mock_ledger_info(500)),
+        ..summary
+    };
+    assert_eq!(
+        summary.serviceable_state_version_range(),
+        Some(create_range(100, 200))
+    );
+
+    // Verify there's no serviceable range if the synced version predates the states
+    let summary = DataSummary {
+        synced_ledger_info: Some(create_mock_ledger_info(50)),
+        ..summary
+    };
+    assert_eq!(summary.serviceable_state_version_range(), None);
+}
+
+#[test]
+fn test_data_summary_contiguous_from_genesis_version() {
+    // Verify a full archival peer reports the lowest highest version
+    let summary = DataSummary {
+        synced_ledger_info: Some(create_mock_ledger_info(1000)),
+        states: Some(create_range(0, 1000)),
+        transactions: Some(create_range(0, 990)),
+        transaction_outputs: Some(create_range(0, 995)),
+        ..Default::default()
+    };
+    assert_eq!(summary.contiguous_from_genesis_version(), Some(990));
+    let summary = DataSummary {
+        transactions: Some(create_range(0, 1000)),
+        transaction_outputs: Some(create_range(0, 1000)),
+        ..summary
+    };
+    assert_eq!(summary.contiguous_from_genesis_version(), Some(1000));
+
+    // Verify a peer pruned from the low end has no contiguous history
+    let pruned_summary = DataSummary {
+        states: Some(create_range(1, 1000)),
+        ..summary.clone()
+    };
+    assert_eq!(pruned_summary.contiguous_from_genesis_version(), None);
+    let pruned_summary = DataSummary {
+        transactions: Some(create_range(500, 1000)),
+        transaction_outputs: Some(create_range(500, 1000)),
+        ..summary.clone()
+    };
+    assert_eq!(pruned_summary.contiguous_from_genesis_version(), None);
+
+    // Verify a peer missing a category has no contiguous history
+    let missing_summary = DataSummary {
+        transaction_outputs: None,
+        ..summary
+    };
+    assert_eq!(missing_summary.contiguous_from_genesis_version(), None);
+    assert_eq!(
+        DataSummary::default().contiguous_from_genesis_version(),
+        None
+    );
+}
+
+#[test]
+fn test_data_summary_epochs_for_version_range() {
+    let versions = create_range(100, 200);
+
+    // Verify the mapping isn't derivable without a synced ledger info
+    let summary = DataSummary {
+        epoch_ending_ledger_infos: Some(create_range(0, 9)),
+        transactions: Some(create_range(0, 1000)),
+        ..Default::default()
+    };
+    assert_eq!(summary.epochs_for_version_range(&versions), None);
+
+    // Verify the epochs are bounded by the synced epoch
+    let summary = DataSummary {
+        synced_ledger_info: Some(create_mock_ledger_info_at_epoch(10, 500)),
+        ..summary
+    };
+    assert_eq!(
+        summary.epochs_for_version_range(&versions),
+        Some(create_range(0, 10))
+    );
+    assert_eq!(
+        summary.epochs_for_version_range(&create_range(0, 500)),
+        Some(create_range(0, 10))
+    );
+
+    // Verify the synced version maps to the synced epoch
+    assert_eq!(
+        summary.epochs_for_version_range(&create_range(500, 500)),
+        Some(create_range(10, 10))
+    );
+
+    // Verify the mapping isn't derivable beyond the synced version
+    for versions in [create_range(100, 501), create_range(501, 600)] {
+        assert_eq!(summary.epochs_for_version_range(&versions), None);
+    }
+
+    // Verify the mapping isn't derivable if the epoch data is inconsistent
+    let summary = DataSummary {
+        epoch_ending_ledger_infos: Some(create_range(0, 11)),
+        ..summary
+    };
+    assert_eq!(summary.epochs_for_version_range(&versions), None);
+}
+
+#[test]
+fn test_request_priority() {
+    // Verify requests are high-priority by default
+    assert_eq!(RequestPriority::default(), RequestPriority::High);
+
+    // Verify the priority doesn't change the wire format of the request
+    let request = txns_request(200, 100, 199, false);
+    let request_bytes = bcs::to_bytes(&request).unwrap();
+    for priority in [RequestPriority::High, RequestPriority::Low] {
+        let message = StorageServiceMessage::PrioritizedRequest(request.clone(), priority);
+        let message_bytes = bcs::to_bytes(&message).unwrap();
+        assert_eq!(&message_bytes[1..=request_bytes.len()], &request_bytes[..]);
+
+        // Verify the message is decoded into the same request and priority
+        match bcs::from_bytes(&message_bytes).unwrap() {
+            StorageServiceMessage::PrioritizedRequest(decoded_request, decoded_priority) => {
+                assert_eq!(decoded_request, request);
+                assert_eq!(decoded_priority, priority);
+            },
+            message => panic!("Expected a prioritized request, found: {:?}", message),
+        }
+    }
+
+    // Verify the prioritized request is appended to the existing messages
+    let message = StorageServiceMessage::Request(request.clone());
+    assert_eq!(bcs::to_bytes(&message).unwrap()[0], 0);
+    let message = StorageServiceMessage::PrioritizedRequest(request, RequestPriority::Low);
+    assert_eq!(bcs::to_bytes(&message).unwrap()[0], 2);
+}
+
+#[test]
+fn test_validate_epoch_proof_range() {
+    let create_proof = |start_epoch: Epoch, end_epoch: Epoch| {
+        let ledger_infos = (start_epoch..=end_epoch)
+            .map(|epoch| create_mock_ledger_info_at_epoch(epoch, epoch * 100))
+            .collect();
+        EpochChangeProof::new(ledger_infos, false)
+    };
+    let requested = create_range(10, 20);
+
+    // Verify a proof that exactly matches the requested range
+    assert_ok!(validate_epoch_proof_range(
+        &create_proof(10, 20),
+        &requested
+    ));
+    let single_epoch = create_range(15, 15);
+    assert_ok!(validate_epoch_proof_range(
+        &create_proof(15, 15),
+        &single_epoch
+    ));
+
+    // Verify short proofs (i.e., that end early) are rejected
+    for proof in [create_proof(10, 19), create_proof(10, 10)] {
+        let result = validate_epoch_proof_range(&proof, &requested);
+        assert!(matches!(result, Err(Error::UnexpectedResponseError(_))));
+    }
+
+    // Verify proofs with an off-by-one start are rejected
+    for proof in [create_proof(9, 20), create_proof(11, 20)] {
+        let result = validate_epoch_proof_range(&proof, &requested);
+        assert!(matches!(result, Err(Error::UnexpectedResponseError(_))));
+    }
+
+    // Verify overly long and empty proofs are rejected
+    for proof in [create_proof(10, 21), EpochChangeProof::new(vec![], false)] {
+        let result = validate_epoch_proof_range(&proof, &requested);
+        assert!(matches!(result, Err(Error::UnexpectedResponseError(_))));
+    }
+}
+
+#[test]
+fn test_storage_server_summary_serves_same_as() {
+    let create_summary = |lowest, highest| StorageServerSummary {
+        data_summary: DataSummary {
+            synced_ledger_info: Some(create_mock_ledger_info(highest)),
+            transactions: Some(create_range(lowest, highest)),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let summary = create_summary(100, 200);
+    let other_summary = create_summary(150, 300);
+
+    for compression in [true, false] {
+        // Verify the summaries are interchangeable if both can service the request
+        let request = txns_request(200, 150, 200, compression);
+        assert!(summary.can_service(&request));
+        assert!(summary.serves_same_as(&other_summary, &request));
+        assert!(other_summary.serves_same_as(&summary, &request));
+
+        // Verify the summaries are interchangeable if neither can service the request
+        let request = txns_request(400, 350, 400, compression);
+        assert!(!summary.can_service(&request));
+        assert!(summary.serves_same_as(&other_summary, &request));
+        assert!(other_summary.serves_same_as(&summary, &request));
+
+        // Verify the summaries are not interchangeable if only one can service the request
+        let request = txns_request(200, 100, 200, compression);
+        assert!(summary.can_service(&request));
+        assert!(!other_summary.can_service(&request));
+        assert!(!summary.serves_same_as(&other_summary, &request));
+        assert!(!other_summary.serves_same_as(&summary, &request));
+
+        // Verify a summary is always interchangeable with itself
+        assert!(summary.serves_same_as(&summary, &request));
+    }
+}
+
+#[test]
+fn test_storage_server_summary_ordering() {
+    // Create summaries with and without synced ledger infos
+    let create_summary = |synced_version: Option<Version>| StorageServerSummary {
+        data_summary: DataSummary {
+            synced_ledger_info: synced_version.map(create_mock_ledger_info),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let mut summaries = vec![
+        create_summary(Some(200)),
+        create_summary(None),
+        create_summary(Some(1000)),
+        create_summary(Some(100)),
+        create_summary(None),
+    ];
+
+    // Verify sorting ranks the summaries by freshness
+    summaries.sort();
+    let synced_versions: Vec<_> = summaries
+        .iter()
+        .map(|summary| summary.data_summary.get_synced_ledger_info_version())
+        .collect();
+    assert_eq!(synced_versions, vec![
+        None,
+        None,
+        Some(100),
+        Some(200),
+        Some(1000)
+    ]);
+    assert_eq!(summaries.iter().max(), Some(&create_summary(Some(1000))));
+
+    // Verify the order is consistent with equality
+    let summary = create_summary(Some(100));
+    assert_eq!(summary.cmp(&summary.clone()), std::cmp::Ordering::Equal);
+    let mut other_summary = summary.clone();
+    other_summary.protocol_metadata.max_epoch_chunk_size += 1;
+    assert_ne!(summary.cmp(&other_summary), std::cmp::Ordering::Equal);
+    assert_eq!(
+        summary.cmp(&other_summary),
+        other_summary.cmp(&summary).reverse()
+    );
+
+    // Verify summaries that only differ in their synced ledger infos are unequal
+    let mut other_summary = summary.clone();
+    other_summary.data_summary.synced_ledger_info = Some(create_mock_ledger_info_at_epoch(1, 100));
+    assert_ne!(summary.cmp(&other_summary), std::cmp::Ordering::Equal);
+    assert_eq!(
+        summary.cmp(&other_summary),
+        other_summary.cmp(&summary).reverse()
+    );
+}
+
+#[test]
+fn test_storage_server_summary_is_fresh() {
+    let now_unix_secs = 1_000_000;
+    let max_skew_secs = 30;
+    let create_summary = |synced_at_unix_secs: u64| {
+        let block_info = BlockInfo::new(
+            0,
+            0,
+            HashValue::zero(),
+            HashValue::zero(),
+            100,
+            synced_at_unix_secs * 1_000_000,
+            None,
+        );
+        let synced_ledger_info = LedgerInfoWithSignatures::new(
+            LedgerInfo::new(block_info, HashValue::zero()),
+            AggregateSignature::empty(),
+        );
+        StorageServerSummary {
+            data_summary: DataSummary {
+                synced_ledger_info: Some(synced_ledger_info),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    };
+
+    // Verify fresh summaries pass the check
+    for synced_at_unix_secs in [
+        now_unix_secs,
+        now_unix_secs - 10,
+        now_unix_secs - max_skew_secs,
+        now_unix_secs + max_skew_secs,
+    ] {
+        let summary = create_summary(synced_at_unix_secs);
+        assert!(summary.is_fresh(now_unix_secs, max_skew_secs));
+    }
+
+    // Verify stale (or far future) summaries fail the check
+    for synced_at_unix_secs in [
+        0,
+        now_unix_secs - max_skew_secs - 1,
+        now_unix_secs + max_skew_secs + 1,
+        u64::MAX / 1_000_000,
+    ] {
+        let summary = create_summary(synced_at_unix_secs);
+        assert!(!summary.is_fresh(now_unix_secs, max_skew_secs));
+    }
+
+    // Verify summaries without a synced ledger info are never fresh
+    assert!(!StorageServerSummary::default().is_fresh(now_unix_secs, max_skew_secs));
+}
+
+#[test]
+fn test_transaction_or_output_list_into_either() {
+    // Verify transactions only
+    let transaction_list = TransactionListWithProof::new_empty();
+    let txns_or_outputs: TransactionOrOutputListWithProof = (Some(transaction_list.clone()), None);
+    assert_eq!(
+        txns_or_outputs.into_either().unwrap(),
+        TxnsOrOutputs::Transactions(transaction_list.clone())
+    );
+
+    // Verify outputs only
+    let output_list = TransactionOutputListWithProof::new_empty();
+    let txns_or_outputs: TransactionOrOutputListWithProof = (None, Some(output_list.clone()));
+    assert_eq!(
+        txns_or_outputs.into_either().unwrap(),
+        TxnsOrOutputs::Outputs(output_list.clone())
+    );
+
+    // Verify both present is an error
+    let txns_or_outputs: TransactionOrOutputListWithProof =
+        (Some(transaction_list), Some(output_list));
+    assert_err!(txns_or_outputs.into_either());
+
+    // Verify neither present is an error
+    let txns_or_outputs: TransactionOrOutputListWithProof = (None, None);
+    assert_err!(txns_or_outputs.into_either());
+}
+
+#[test]
+fn test_coalesce_transaction_requests() {
+    // Adjacent single version requests are merged into one
+    let requests = vec![
+        txns_request(500, 100, 100, false),
+        txns_request(500, 101, 101, false),
+        txns_request(500, 102, 102, false),
+    ];
+    let coalesced_requests = coalesce_transaction_requests(&requests, 10);
+    assert_eq!(coalesced_requests, vec![txns_request(500, 100, 102, false)]);
+
+    // Requests across a gap are not merged
+    let requests = vec![
+        txns_request(500, 100, 109, false),
+        txns_request(500, 111, 120, false),
+    ];
+    let coalesced_requests = coalesce_transaction_requests(&requests, 100);
+    assert_eq!(coalesced_requests, requests);
+
+    // Requests with different proof versions are not merged
+    let requests = vec![
+        txns_request(500, 100, 109, false),
+        txns_request(501, 110, 120, false),
+    ];
+    let coalesced_requests = coalesce_transaction_requests(&requests, 100);
+    assert_eq!(coalesced_requests, requests);
+
+    // Requests are merged up to the max chunk size
+    let requests = vec![
+        txns_request(500, 0, 39, true),
+        txns_request(500, 40, 79, true),
+        txns_request(500, 80, 119, true),
+    ];
+    let coalesced_requests = coalesce_transaction_requests(&requests, 100);
+    assert_eq!(coalesced_requests, vec![
+        txns_request(500, 0, 79, true),
+        txns_request(500, 80, 119, true),
+    ]);
+
+    // Other requests are left untouched
+    let requests = vec![
+        txns_request(500, 0, 9, false),
+        epochs_request(0, 10, false),
+        txns_request(500, 10, 19, false),
+    ];
+    let coalesced_requests = coalesce_transaction_requests(&requests, 100);
+    assert_eq!(coalesced_requests, requests);
+}
+
+#[test]
+fn test_deprecated_requests() {
+    // Verify no request kinds are deprecated by default
+    let default_requests = DeprecatedRequests::from_config(&StorageServiceConfig::default());
+    assert_eq!(default_requests, DeprecatedRequests::default());
+
+    // Deprecate the pre-fusion requests
+    let config = StorageServiceConfig {
+        deprecate_pre_fusion_requests: true,
+        ..Default::default()
+    };
+    let deprecated_requests = DeprecatedRequests::from_config(&config);
+
+    for compression in [true, false] {
+        // Verify the deprecated request kinds return the specific error
+        for (request, label) in [
+            (
+                txns_request(225, 100, 200, compression),
+                "get_transactions_with_proof",
+            ),
+            (
+                outputs_request(225, 100, 200, compression),
+                "get_transaction_outputs_with_proof",
+            ),
+        ] {
+            assert_eq!(
+                deprecated_requests.check_request_supported(&request),
+                Err(Error::UnsupportedRequest {
+                    label: label.into()
+                })
+            );
+            assert_ok!(default_requests.check_request_supported(&request));
+        }
+
+        // Verify the other request kinds remain serviceable
+        for request in [
+            txns_or_outputs_request(225, 100, 200, compression),
+            epochs_request(100, 199, compression),
+            states_request(200, compression),
+        ] {
+            assert_ok!(deprecated_requests.check_request_supported(&request));
+        }
+    }
+}
+
+#[test]
+fn test_storage_server_summary_ref() {
+    let storage_server_summary = StorageServerSummary {
+        protocol_metadata: ProtocolMetadata {
+            max_epoch_chunk_size: 10,
+            max_state_chunk_size: 20,
+            max_transaction_chunk_size: 30,
+            max_transaction_output_chunk_size: 40,
+        },
+        data_summary: DataSummary {
+            synced_ledger_info: Some(create_mock_ledger_info(250)),
+            transactions: Some(create_range(100, 200)),
+            ..Default::default()
+        },
+    };
+    let data_response = DataResponse::StorageServerSummary(storage_server_summary.clone());
+
+    // Verify raw responses are borrowed (i.e., not cloned)
+    let response = StorageServiceResponse::new(data_response.clone(), false).unwrap();
+    let inner_summary = match &response {
+        StorageServiceResponse::RawResponse(DataResponse::StorageServerSummary(summary)) => summary,
+        response => panic!("Unexpected response: {:?}", response),
+    };
+    match response.data_summary_ref().unwrap() {
+        Cow::Borrowed(data_summary) => {
+            assert!(std::ptr::eq(data_summary, &inner_summary.data_summary))
+        },
+        Cow::Owned(_) => panic!("The data summary should be borrowed!"),
+    }
+    match response.protocol_metadata_ref().unwrap() {
+        Cow::Borrowed(protocol_metadata) => assert!(std::ptr::eq(
+            protocol_metadata,
+            &inner_summary.protocol_metadata
+        )),
+        Cow::Owned(_) => panic!("The protocol metadata should be borrowed!"),
+    }
+
+    // Verify compressed responses are decoded correctly
+    let response = StorageServiceResponse::new(data_response, true).unwrap();
+    let data_summary = response.data_summary_ref().unwrap();
+    assert!(matches!(data_summary, Cow::Owned(_)));
+    assert_eq!(
+        data_summary.into_owned(),
+        storage_server_summary.data_summary
+    );
+    assert_eq!(
+        response.protocol_metadata_ref().unwrap().into_owned(),
+        storage_server_summary.protocol_metadata
+    );
+
+    // Verify other response types return an error
+    for compression in [true, false] {
+        let response =
+            StorageServiceResponse::new(DataResponse::NumberOfStatesAtVersion(10), compression)
+                .unwrap();
+        assert_err!(response.data_summary_ref());
+        assert_err!(response.protocol_metadata_ref());
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(1000))]
+
+    #[test]
+    fn test_data_summary_length_invariant(range in any::<CompleteDataRange<u64>>()) {
+        // should not panic
+        let _ = range.len();
+    }
+
+    #[test]
+    fn test_normalize_requests_is_idempotent(
+        ranges in proptest::collection::vec((0u64..10, 0u64..10, any::<bool>()), 0..50),
+    ) {
+        let requests: Vec<_> = ranges
+            .into_iter()
+            .map(|(start, end, use_compression)| {
+                if use_compression {
+                    epochs_request(start, end, use_compression)
+                } else {
+                    txns_request(100, start, end, use_compression)
+                }
+            })
+            .collect();
+
+        // Verify that normalizing twice yields the same result
+        let normalized_requests = normalize_requests(requests);
+        prop_assert_eq!(normalize_requests(normalized_requests.clone()), normalized_requests);
+    }
+
+    #[test]
+    fn test_data_summary_bounded_length(
+        range in CompleteDataRange::<u64>::arbitrary_bounded(100),
+        params_range in any_with::<CompleteDataRange<u64>>(Some(1)),
+    ) {
+        prop_assert!(range.len().unwrap() <= 100);
+        prop_assert_eq!(params_range.len().unwrap(), 1);
+    }
+}
+
+/// The number of variants in `DataResponse`
+const NUM_DATA_RESPONSE_VARIANTS: usize = 16;
+
+/// Returns the index of the given response variant (in declaration order).
+/// Note: this match is deliberately exhaustive (i.e., without a wildcard), so
+/// that adding a new variant fails to compile until the labels are updated.
+fn get_data_response_variant_index(response: &DataResponse) -> usize {
+    match response {
+        DataResponse::EpochEndingLedgerInfos(_) => 0,
+        DataResponse::NewTransactionOutputsWithProof(_) => 1,
+        DataResponse::NewTransactionsWithProof(_) => 2,
+        DataResponse::NumberOfStatesAtVersion(_) => 3,
+        DataResponse::ServerProtocolVersion(_) => 4,
+        DataResponse::StateValueChunkWithProof(_) => 5,
+        DataResponse::StorageServerSummary(_) => 6,
+        DataResponse::TransactionOutputsWithProof(_) => 7,
+        DataResponse::TransactionsWithProof(_) => 8,
+        DataResponse::NewTransactionsOrOutputsWithProof(_) => 9,
+        DataResponse::TransactionsOrOutputsWithProof(_) => 10,
+        DataResponse::TransactionRangeProof(_) => 11,
+        DataResponse::TransactionByHashWithProof(_) => 12,
+        DataResponse::LatestLedgerInfo(_) => 13,
+        DataResponse::EpochChangeProofBetween(_) => 14,
+        DataResponse::Batch(_) => 15,
+    }
+}
+
+fn create_mock_ledger_info(version: Version) -> LedgerInfoWithSignatures {
+    create_mock_ledger_info_at_epoch(0, version)
+}
+
+fn create_mock_ledger_info_at_epoch(epoch: Epoch, version: Version) -> LedgerInfoWithSignatures {
+    LedgerInfoWithSignatures::new(
+        LedgerInfo::new(
+            BlockInfo::new(
+                epoch,
+                0,
+                HashValue::zero(),
+                HashValue::zero(),
+                version,
+                0,
+                None,
+            ),
+            HashValue::zero(),
+        ),
+        AggregateSignature::empty(),
+    )
+}
+
+/// Verifies that the BCS serialization of the given value matches the golden
+/// hex string, and that the golden bytes deserialize back into the value. This
+/// catches accidental changes to the wire layout (e.g., reordered fields).
+fn assert_bcs_stable<T: Serialize + DeserializeOwned + PartialEq + Debug>(
+    value: &T,
+    golden_hex: &str,
+) {
+    let bytes = bcs::to_bytes(value).unwrap();
+    let hex: String = bytes.iter().map(|byte| format!("{:02x}", byte)).collect();
+    assert_eq!(hex, golden_hex, "The BCS wire layout has changed!");
+    assert_eq!(&bcs::from_bytes::<T>(&bytes).unwrap(), value);
+}
+
+/// Verifies that the given summary round trips through its protobuf
+/// representation (including encoding and decoding the protobuf bytes)
+fn verify_proto_round_trip(storage_server_summary: StorageServerSummary) {
+    let proto = storage_server_summary.to_proto().unwrap();
+    let decoded_proto =
+        StorageServerSummaryProto::decode(proto.encode_to_vec().as_slice()).unwrap();
+    assert_eq!(decoded_proto, proto);
+    assert_eq!(
+        StorageServerSummary::from_proto(decoded_proto).unwrap(),
+        storage_server_summary
+    );
+}
+
+/// Creates a transaction list (with events) of the given length, starting at the
+/// given version and using the given left and right siblings for the range proof.
+fn create_transaction_list(
+    first_version: Version,
+    num_transactions: usize,
+    left_siblings: Vec<HashValue>,
+    right_siblings: Vec<HashValue>,
+) -> TransactionListWithProof {
+    let transactions = (0..num_transactions)
+        .map(|_| Transaction::StateCheckpoint(HashValue::random()))
+        .collect();
+    let events = (0..num_transactions).map(|_| vec![]).collect();
+    let transaction_infos = (0..num_transactions)
+        .map(|_| {
+            TransactionInfo::new(
+                HashValue::random(),
+                HashValue::zero(),
+                HashValue::zero(),
+                None,
+                0,
+                ExecutionStatus::Success,
+            )
+        })
+        .collect();
+    let proof = TransactionInfoListWithProof::new(
+        TransactionAccumulatorRangeProof::new(left_siblings, right_siblings),
+        transaction_infos,
+    );
+    TransactionListWithProof::new(transactions, Some(events), Some(first_version), proof)
+}
+
+/// Creates a transaction list (with a valid range proof) for the given range
+/// of leaves in a (full) accumulator with `num_leaves` leaves. The accumulator
+/// root hash is also returned. Note: `num_leaves` must be a power of two.
+fn create_transaction_list_with_valid_proof(
+    num_leaves: usize,
+    first_version: Version,
+    num_transactions: usize,
+) -> (TransactionListWithProof, HashValue) {
+    // Create the transaction infos for all leaves
+    let transaction_list = create_transaction_list(0, num_leaves, vec![], vec![]);
+    let transaction_infos = transaction_list.proof.transaction_infos;
+
+    // Compute all levels of the accumulator (starting at the leaves)
+    let mut levels = vec![transaction_infos
+        .iter()
+        .map(CryptoHash::hash)
+        .collect::<Vec<_>>()];
+    while levels.last().unwrap().len() > 1 {
+        let parent_hashes = levels
+            .last()
+            .unwrap()
+            .chunks_exact(2)
+            .map(|children| {
+                TransactionAccumulatorInternalNode::new(children[0], children[1]).hash()
+            })
+            .collect();
+        levels.push(parent_hashes);
+    }
+    let root_hash = levels.last().unwrap()[0];
+
+    // Collect the siblings of the range (ordered from the leaves to the root)
+    let mut left_siblings = vec![];
+    let mut right_siblings = vec![];
+    let mut first_position = first_version as usize;
+    let mut last_position = first_position + num_transactions - 1;
+    for level in levels.iter().take(levels.len() - 1) {
+        if first_position % 2 == 1 {
+            left_siblings.push(level[first_position - 1]);
+        }
+        if last_position % 2 == 0 {
+            right_siblings.push(level[last_position + 1]);
+        }
+        first_position /= 2;
+        last_position /= 2;
+    }
+
+    // Create the transaction list for the range
+    let range = first_version as usize..first_version as usize + num_transactions;
+    let proof = TransactionInfoListWithProof::new(
+        TransactionAccumulatorRangeProof::new(left_siblings, right_siblings),
+        transaction_infos[range.clone()].to_vec(),
+    );
+    let transaction_list = TransactionListWithProof::new(
+        transaction_list.transactions[range.clone()].to_vec(),
+        transaction_list.events.map(|events| events[range].to_vec()),
+        Some(first_version),
+        proof,
+    );
+    (transaction_list, root_hash)
+}
+
+/// Verifies that the given chunks (ordered from highest to lowest) are
+/// contiguous, and that they reassemble into the given range.
+fn verify_reassembled_chunks<T: PrimInt + Debug>(
+    range: CompleteDataRange<T>,
+    chunks: Vec<CompleteDataRange<T>>,
+) {
+    let mut reassembled_range: Option<CompleteDataRange<T>> = None;
+    for chunk in chunks.into_iter().rev() {
+        reassembled_range = match reassembled_range {
+            None => Some(chunk),
+            Some(reassembled_range) => {
+                assert_eq!(reassembled_range.highest() + T::one(), chunk.lowest());
+                Some(CompleteDataRange::new(reassembled_range.lowest(), chunk.highest()).unwrap())
+            },
+        };
+    }
+    assert_eq!(reassembled_range, Some(range));
+}
+
+fn create_range(lowest: u64, highest: u64) -> CompleteDataRange<u64> {
+    CompleteDataRange::new(lowest, highest).unwrap()
+}
+
+/// A simple version newtype that is ordered, but not a primitive integer
+#[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd)]
+struct WrappedVersion(u64);
+
+fn create_ordered_range(lowest: u64, highest: u64) -> OrderedRange<WrappedVersion> {
+    OrderedRange::new(WrappedVersion(lowest), WrappedVersion(highest)).unwrap()
+}
+
+fn txn_by_hash_request(
+    proof: Version,
+    start: Version,
+    end: Version,
+    use_compression: bool,
+) -> StorageServiceRequest {
+    let data_request =
+        DataRequest::GetTransactionByHashWithProof(TransactionByHashWithProofRequest {
+            transaction_hash: HashValue::random(),
+            proof_version: proof,
+            start_version: start,
+            end_version: end,
+            include_events: true,
+        });
+    StorageServiceRequest::new(data_request, use_compression)
+}
+
+fn epoch_change_proof_request(
+    start: Epoch,
+    end: Epoch,
+    use_compression: bool,
+) -> StorageServiceRequest {
+    let data_request = DataRequest::GetEpochChangeProofBetween(EpochChangeProofBetweenRequest {
+        start_epoch: start,
+        end_epoch: end,
+    });
+    StorageServiceRequest::new(data_request, use_compression)
+}
+
+fn epochs_request(start: Epoch, end: Epoch, use_compression: bool) -> StorageServiceRequest {
+    let data_request = DataRequest::GetEpochEndingLedgerInfos(EpochEndingLedgerInfoRequest {
+        start_epoch: start,
+        expected_end_epoch: end,
+    });
+    StorageServiceRequest::new(data_request, use_compression)
+}
+
+fn txns_request(
+    proof: Version,
+    start: Version,
     end: Version,
     use_compression: bool,
 ) -> StorageServiceRequest {
@@ -295,6 +4821,20 @@ fn outputs_request(
     StorageServiceRequest::new(data_request, use_compression)
 }
 
+fn txn_range_proof_request(
+    proof_version: Version,
+    start_version: Version,
+    end_version: Version,
+    use_compression: bool,
+) -> StorageServiceRequest {
+    let data_request = DataRequest::GetTransactionRangeProof(TransactionRangeProofRequest {
+        proof_version,
+        start_version,
+        end_version,
+    });
+    StorageServiceRequest::new(data_request, use_compression)
+}
+
 fn txns_or_outputs_request(
     proof: Version,
     start: Version,